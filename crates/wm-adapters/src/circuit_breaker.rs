@@ -0,0 +1,335 @@
+//! Circuit breaker that sheds Wolf traffic after repeated connection failures
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed,
+    Open { until: Instant },
+    /// Cooldown elapsed; a single probe request is in flight
+    HalfOpen,
+}
+
+/// Operator override of the breaker's own decisions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BreakerMode {
+    /// Open and close on failures and cooldowns as usual
+    #[default]
+    Auto,
+    /// Reject every request, e.g. to drain traffic during Wolf maintenance
+    Open,
+    /// Let every request through regardless of failures
+    Closed,
+}
+
+/// Point-in-time view of the breaker, as reported by the admin endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct BreakerStatus {
+    pub mode: BreakerMode,
+    /// Whether requests are let through: `closed`, `open`, or `half_open` (one probe in flight)
+    pub state: &'static str,
+    pub consecutive_failures: u32,
+    /// `0` means the breaker is disabled and never opens on its own
+    pub failure_threshold: u32,
+    pub cooldown_ms: u64,
+    /// Time left before a probe is allowed, while open on its own
+    pub retry_after_ms: Option<u64>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: State,
+    consecutive_failures: u32,
+    mode: BreakerMode,
+}
+
+/// Opens after `failure_threshold` consecutive failures and stays open for `cooldown`.
+/// Once the cooldown elapses one probe is let through: success closes the breaker,
+/// failure (or a probe dropped before reporting) re-opens it for another cooldown. An
+/// operator can pin it open or closed with [`CircuitBreaker::set_mode`].
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    /// A `failure_threshold` of 0 disables the breaker
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold,
+            cooldown,
+            inner: Mutex::new(Inner {
+                state: State::Closed,
+                consecutive_failures: 0,
+                mode: BreakerMode::Auto,
+            }),
+        }
+    }
+
+    /// Ask to send a request. Returns the time left in the cooldown when rejected.
+    pub fn try_acquire(&self) -> Result<Attempt<'_>, Duration> {
+        let mut inner = self.inner.lock().unwrap();
+        let attempt = |probe| Attempt {
+            breaker: self,
+            probe,
+            resolved: false,
+        };
+        match inner.mode {
+            BreakerMode::Open => return Err(self.cooldown),
+            BreakerMode::Closed => return Ok(attempt(false)),
+            BreakerMode::Auto if self.failure_threshold == 0 => return Ok(attempt(false)),
+            BreakerMode::Auto => {}
+        }
+
+        match inner.state {
+            State::Closed => Ok(attempt(false)),
+            State::Open { until } => {
+                let now = Instant::now();
+                if now >= until {
+                    inner.state = State::HalfOpen;
+                    Ok(attempt(true))
+                } else {
+                    Err(until - now)
+                }
+            }
+            // Only the probe may pass until it reports back
+            State::HalfOpen => Err(Duration::ZERO),
+        }
+    }
+
+    /// Wolf was reachable
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = State::Closed;
+        inner.consecutive_failures = 0;
+    }
+
+    /// Wolf could not be reached
+    pub fn record_failure(&self) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        // Failures keep counting under an override, but only `auto` acts on them
+        let trip = inner.mode == BreakerMode::Auto
+            && (inner.state == State::HalfOpen || inner.consecutive_failures >= self.failure_threshold);
+        if trip {
+            inner.state = State::Open {
+                until: Instant::now() + self.cooldown,
+            };
+        }
+    }
+
+    /// Whether requests are currently being rejected
+    pub fn is_open(&self) -> bool {
+        let inner = self.inner.lock().unwrap();
+        match inner.mode {
+            BreakerMode::Open => true,
+            BreakerMode::Closed => false,
+            BreakerMode::Auto => !matches!(inner.state, State::Closed),
+        }
+    }
+
+    /// Pin the breaker open or closed, or hand control back with `Auto`.
+    /// Releasing starts over closed with no failures counted, as after a restart.
+    pub fn set_mode(&self, mode: BreakerMode) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.mode = mode;
+        if mode == BreakerMode::Auto {
+            inner.state = State::Closed;
+            inner.consecutive_failures = 0;
+        }
+    }
+
+    pub fn status(&self) -> BreakerStatus {
+        let inner = self.inner.lock().unwrap();
+        let (state, retry_after) = match (inner.mode, inner.state) {
+            (BreakerMode::Open, _) => ("open", None),
+            (BreakerMode::Closed, _) => ("closed", None),
+            (BreakerMode::Auto, State::Closed) => ("closed", None),
+            (BreakerMode::Auto, State::Open { until }) => {
+                ("open", Some(until.saturating_duration_since(Instant::now())))
+            }
+            (BreakerMode::Auto, State::HalfOpen) => ("half_open", None),
+        };
+        BreakerStatus {
+            mode: inner.mode,
+            state,
+            consecutive_failures: inner.consecutive_failures,
+            failure_threshold: self.failure_threshold,
+            cooldown_ms: self.cooldown.as_millis() as u64,
+            retry_after_ms: retry_after.map(|d| d.as_millis() as u64),
+        }
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.inner.lock().unwrap().consecutive_failures
+    }
+}
+
+/// A request the breaker let through, to be reported with [`Attempt::succeeded`] or
+/// [`Attempt::failed`]. A half-open probe dropped unreported (its caller was cancelled)
+/// counts as failed, so the breaker re-opens and a later probe can close it; any other
+/// unreported attempt is forgotten.
+#[derive(Debug)]
+#[must_use = "report the outcome, or the breaker can't tell how Wolf is doing"]
+pub struct Attempt<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+    resolved: bool,
+}
+
+impl Attempt<'_> {
+    /// Whether this is the single probe let through after a cooldown
+    pub fn is_probe(&self) -> bool {
+        self.probe
+    }
+
+    /// Wolf was reachable
+    pub fn succeeded(mut self) {
+        self.resolved = true;
+        self.breaker.record_success();
+    }
+
+    /// Wolf could not be reached
+    pub fn failed(mut self) {
+        self.resolved = true;
+        self.breaker.record_failure();
+    }
+}
+
+impl Drop for Attempt<'_> {
+    fn drop(&mut self) {
+        if self.probe && !self.resolved {
+            self.breaker.record_failure();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+        for _ in 0..2 {
+            breaker.record_failure();
+            assert!(breaker.try_acquire().is_ok());
+        }
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert!(breaker.try_acquire().is_err());
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.consecutive_failures(), 1);
+    }
+
+    #[test]
+    fn test_single_probe_after_cooldown() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+        breaker.record_failure();
+        assert!(breaker.try_acquire().is_err());
+
+        std::thread::sleep(Duration::from_millis(30));
+        let probe = breaker.try_acquire().expect("probe should pass");
+        assert!(probe.is_probe());
+        assert!(breaker.try_acquire().is_err(), "only one probe at a time");
+
+        // Failed probe re-opens, successful probe closes
+        probe.failed();
+        assert!(breaker.try_acquire().is_err());
+        std::thread::sleep(Duration::from_millis(30));
+        breaker.try_acquire().unwrap().succeeded();
+        assert!(!breaker.is_open());
+        assert!(!breaker.try_acquire().unwrap().is_probe());
+    }
+
+    #[test]
+    fn test_dropped_probe_reopens_and_a_later_probe_can_close() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+        breaker.record_failure();
+        std::thread::sleep(Duration::from_millis(30));
+        drop(breaker.try_acquire().unwrap());
+        assert_eq!(breaker.status().state, "open", "a dropped probe counts as failed");
+
+        std::thread::sleep(Duration::from_millis(30));
+        breaker.try_acquire().expect("a fresh probe after the cooldown").succeeded();
+        assert_eq!(breaker.status().state, "closed");
+
+        // Dropping an ordinary attempt reports nothing
+        drop(breaker.try_acquire().unwrap());
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_forced_open_rejects_until_released() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+        breaker.set_mode(BreakerMode::Open);
+        assert!(breaker.is_open());
+        assert_eq!(breaker.try_acquire().err(), Some(Duration::from_secs(60)));
+        // Successes don't close a pinned breaker
+        breaker.record_success();
+        assert!(breaker.try_acquire().is_err());
+        assert_eq!(breaker.status().state, "open");
+
+        breaker.set_mode(BreakerMode::Auto);
+        assert!(!breaker.is_open());
+        assert!(breaker.try_acquire().is_ok());
+    }
+
+    #[test]
+    fn test_forced_closed_ignores_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.set_mode(BreakerMode::Closed);
+        for _ in 0..5 {
+            breaker.record_failure();
+            assert!(breaker.try_acquire().is_ok());
+        }
+        let status = breaker.status();
+        assert_eq!((status.mode, status.state, status.consecutive_failures), (BreakerMode::Closed, "closed", 5));
+    }
+
+    #[test]
+    fn test_auto_resets_and_trips_normally_again() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert!(breaker.status().retry_after_ms.is_some_and(|ms| ms <= 60_000));
+
+        // Manual reset after fixing Wolf
+        breaker.set_mode(BreakerMode::Auto);
+        let status = breaker.status();
+        assert_eq!((status.state, status.consecutive_failures, status.retry_after_ms), ("closed", 0, None));
+
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+        breaker.record_failure();
+        assert!(breaker.is_open(), "auto trips after the threshold again");
+    }
+
+    #[test]
+    fn test_zero_threshold_disables() {
+        let breaker = CircuitBreaker::new(0, Duration::from_secs(60));
+        for _ in 0..10 {
+            breaker.record_failure();
+        }
+        assert!(breaker.try_acquire().is_ok());
+    }
+}
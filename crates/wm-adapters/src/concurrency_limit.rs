@@ -0,0 +1,122 @@
+//! Cap on simultaneous Wolf requests, with a bounded queue for the overflow
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::wolf_error::WolfProxyError;
+
+/// Lets at most `max` requests through at once. Up to `queue` more wait, each for at most
+/// `timeout`, for a slot to free up; anything beyond that is rejected straight away.
+#[derive(Debug)]
+pub struct ConcurrencyLimit {
+    /// `None` when unlimited
+    slots: Option<Semaphore>,
+    max: usize,
+    queue: usize,
+    timeout: Duration,
+    waiting: AtomicUsize,
+}
+
+/// Decrements the waiter count however the wait ends, including cancellation
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl ConcurrencyLimit {
+    /// `max` of 0 disables the limit
+    pub fn new(max: usize, queue: usize, timeout: Duration) -> Self {
+        Self {
+            slots: (max > 0).then(|| Semaphore::new(max)),
+            max,
+            queue,
+            timeout,
+            waiting: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait for a slot, holding it until the returned permit is dropped
+    pub async fn acquire(&self) -> Result<Option<SemaphorePermit<'_>>, WolfProxyError> {
+        let Some(slots) = &self.slots else {
+            return Ok(None);
+        };
+        if let Ok(permit) = slots.try_acquire() {
+            return Ok(Some(permit));
+        }
+
+        let queued = self
+            .waiting
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < self.queue).then_some(n + 1));
+        if queued.is_err() {
+            return Err(WolfProxyError::QueueFull { limit: self.max });
+        }
+        let _waiting = Waiting(&self.waiting);
+
+        match tokio::time::timeout(self.timeout, slots.acquire()).await {
+            Ok(Ok(permit)) => Ok(Some(permit)),
+            // The semaphore is never closed
+            Ok(Err(_)) | Err(_) => Err(WolfProxyError::QueueTimeout(self.timeout)),
+        }
+    }
+
+    /// Requests currently holding a slot
+    pub fn in_flight(&self) -> usize {
+        self.slots.as_ref().map_or(0, |s| self.max - s.available_permits())
+    }
+
+    /// Requests currently waiting for a slot
+    pub fn queued(&self) -> usize {
+        self.waiting.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_unlimited_by_default() {
+        let limit = ConcurrencyLimit::new(0, 0, Duration::ZERO);
+        let permits: Vec<_> = futures_util::future::join_all((0..100).map(|_| limit.acquire())).await;
+        assert!(permits.iter().all(|p| matches!(p, Ok(None))));
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_fails_fast() {
+        let limit = Arc::new(ConcurrencyLimit::new(2, 1, Duration::from_secs(5)));
+        let _a = limit.acquire().await.unwrap();
+        let _b = limit.acquire().await.unwrap();
+        assert_eq!(limit.in_flight(), 2);
+
+        let waiter = tokio::spawn({
+            let limit = limit.clone();
+            async move { limit.acquire().await.map(|p| p.is_some()) }
+        });
+        while limit.queued() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let start = std::time::Instant::now();
+        assert_eq!(limit.acquire().await.unwrap_err(), WolfProxyError::QueueFull { limit: 2 });
+        assert!(start.elapsed() < Duration::from_secs(1), "rejected without waiting");
+
+        // The queued request gets the first slot that frees up
+        drop(_a);
+        assert_eq!(waiter.await.unwrap(), Ok(true));
+        assert_eq!(limit.queued(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_queued_request_times_out() {
+        let limit = ConcurrencyLimit::new(1, 4, Duration::from_millis(30));
+        let _held = limit.acquire().await.unwrap();
+        let err = limit.acquire().await.unwrap_err();
+        assert_eq!(err, WolfProxyError::QueueTimeout(Duration::from_millis(30)));
+        assert_eq!(limit.queued(), 0, "timed-out waiters leave the queue");
+    }
+}
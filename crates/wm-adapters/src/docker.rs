@@ -0,0 +1,287 @@
+//! Docker Engine access for inspecting the containers Wolf runs alongside
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bollard::query_parameters::ListContainersOptionsBuilder;
+use bollard::Docker;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Seconds bollard waits on a Docker API call
+const DOCKER_TIMEOUT_SECS: u64 = 10;
+
+/// Failures callers map to specific HTTP statuses
+#[derive(Debug, thiserror::Error)]
+pub enum DockerError {
+    #[error("docker.sock not reachable at {path}: {reason}")]
+    Unavailable { path: String, reason: String },
+    #[error("container {0} not found")]
+    NotFound(String),
+}
+
+/// One row of `docker ps -a`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContainerSummary {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    /// Machine-readable state (`running`, `exited`, ...)
+    pub state: String,
+    /// Human-readable status (`Up 2 hours`, `Exited (0) 5 minutes ago`)
+    pub status: String,
+    pub labels: HashMap<String, String>,
+}
+
+/// Current lifecycle state of a single container
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContainerStatus {
+    pub id: String,
+    pub name: String,
+    pub state: String,
+    pub running: bool,
+    pub exit_code: Option<i64>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+/// Trait for Docker Engine queries
+#[async_trait]
+pub trait DockerApi: Send + Sync {
+    /// All containers, running or not
+    async fn list_containers(&self) -> Result<Vec<ContainerSummary>>;
+
+    /// Status of the container with this ID or name
+    async fn container_status(&self, id: &str) -> Result<ContainerStatus>;
+}
+
+/// Mock implementation for testing and scaffolding
+#[derive(Debug, Clone)]
+pub struct MockDockerApi {
+    pub containers: Vec<ContainerSummary>,
+}
+
+impl Default for MockDockerApi {
+    fn default() -> Self {
+        Self {
+            containers: vec![ContainerSummary {
+                id: "0123456789ab".into(),
+                name: "wolf".into(),
+                image: "ghcr.io/games-on-whales/wolf:stable".into(),
+                state: "running".into(),
+                status: "Up 5 minutes".into(),
+                labels: HashMap::new(),
+            }],
+        }
+    }
+}
+
+#[async_trait]
+impl DockerApi for MockDockerApi {
+    async fn list_containers(&self) -> Result<Vec<ContainerSummary>> {
+        Ok(self.containers.clone())
+    }
+
+    async fn container_status(&self, id: &str) -> Result<ContainerStatus> {
+        let container = self
+            .containers
+            .iter()
+            .find(|c| c.id == id || c.name == id)
+            .ok_or_else(|| DockerError::NotFound(id.to_string()))?;
+        Ok(ContainerStatus {
+            id: container.id.clone(),
+            name: container.name.clone(),
+            state: container.state.clone(),
+            running: container.state == "running",
+            exit_code: None,
+            started_at: None,
+            finished_at: None,
+        })
+    }
+}
+
+/// Smart constructor for mock implementation
+pub fn mock_docker() -> Arc<dyn DockerApi> {
+    Arc::new(MockDockerApi::default())
+}
+
+/// Production implementation talking to the Docker Engine over its Unix socket
+pub struct UnixDockerApi {
+    socket_path: String,
+    /// Connected lazily so a missing socket at startup isn't fatal
+    docker: Mutex<Option<Docker>>,
+}
+
+impl UnixDockerApi {
+    pub fn new(socket_path: String) -> Self {
+        Self {
+            socket_path,
+            docker: Mutex::new(None),
+        }
+    }
+
+    fn docker(&self) -> Result<Docker, DockerError> {
+        let mut docker = self.docker.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(docker) = docker.as_ref() {
+            return Ok(docker.clone());
+        }
+        let connected = Docker::connect_with_unix(
+            &self.socket_path,
+            DOCKER_TIMEOUT_SECS,
+            bollard::API_DEFAULT_VERSION,
+        )
+        .map_err(|e| self.unavailable(e))?;
+        *docker = Some(connected.clone());
+        Ok(connected)
+    }
+
+    fn unavailable(&self, e: bollard::errors::Error) -> DockerError {
+        DockerError::Unavailable {
+            path: self.socket_path.clone(),
+            reason: e.to_string(),
+        }
+    }
+
+    /// A 404 for `container` becomes `NotFound`; transport failures become `Unavailable`
+    fn map_error(&self, e: bollard::errors::Error, container: Option<&str>) -> anyhow::Error {
+        use bollard::errors::Error;
+        match (e, container) {
+            (Error::DockerResponseServerError { status_code: 404, .. }, Some(id)) => {
+                DockerError::NotFound(id.to_string()).into()
+            }
+            (
+                e @ (Error::DockerResponseServerError { .. }
+                | Error::JsonDataError { .. }
+                | Error::JsonSerdeError { .. }),
+                _,
+            ) => anyhow::Error::from(e).context("Docker API request failed"),
+            (e, _) => self.unavailable(e).into(),
+        }
+    }
+}
+
+#[async_trait]
+impl DockerApi for UnixDockerApi {
+    async fn list_containers(&self) -> Result<Vec<ContainerSummary>> {
+        let options = ListContainersOptionsBuilder::default().all(true).build();
+        let containers = self
+            .docker()?
+            .list_containers(Some(options))
+            .await
+            .map_err(|e| self.map_error(e, None))?;
+
+        Ok(containers
+            .into_iter()
+            .map(|c| ContainerSummary {
+                id: c.id.unwrap_or_default(),
+                // Docker reports names with a leading slash
+                name: c
+                    .names
+                    .and_then(|names| names.into_iter().next())
+                    .map(|name| name.trim_start_matches('/').to_string())
+                    .unwrap_or_default(),
+                image: c.image.unwrap_or_default(),
+                state: c.state.map(|s| s.to_string()).unwrap_or_default(),
+                status: c.status.unwrap_or_default(),
+                labels: c.labels.unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    async fn container_status(&self, id: &str) -> Result<ContainerStatus> {
+        let container = self
+            .docker()?
+            .inspect_container(id, None)
+            .await
+            .map_err(|e| self.map_error(e, Some(id)))?;
+        let state = container.state.unwrap_or_default();
+
+        Ok(ContainerStatus {
+            id: container.id.unwrap_or_default(),
+            name: container
+                .name
+                .map(|name| name.trim_start_matches('/').to_string())
+                .unwrap_or_default(),
+            state: state.status.map(|s| s.to_string()).unwrap_or_default(),
+            running: state.running.unwrap_or(false),
+            exit_code: state.exit_code,
+            started_at: state.started_at,
+            finished_at: state.finished_at,
+        })
+    }
+}
+
+/// Smart constructor for the Unix socket implementation
+pub fn unix_docker(socket_path: String) -> Arc<dyn DockerApi> {
+    Arc::new(UnixDockerApi::new(socket_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_list_and_status() -> Result<()> {
+        let docker = mock_docker();
+        let containers = docker.list_containers().await?;
+        assert_eq!(containers.len(), 1);
+
+        let status = docker.container_status("wolf").await?;
+        assert_eq!(status.id, containers[0].id);
+        assert!(status.running);
+
+        let err = docker.container_status("missing").await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<DockerError>(), Some(DockerError::NotFound(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unix_list_and_status() -> Result<()> {
+        use crate::test_util::{full, spawn_unix_server};
+        use http::{Response, StatusCode};
+
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            let path = req.uri().path().to_owned();
+            if path.ends_with("/containers/json") {
+                assert!(req.uri().query().unwrap_or("").contains("all=true"));
+                Response::new(full(
+                    r#"[{"Id":"abc123","Names":["/wolf"],"Image":"wolf:stable","State":"running","Status":"Up 2 hours","Labels":{"com.example":"x"}}]"#,
+                ))
+            } else if path.ends_with("/containers/abc123/json") {
+                Response::new(full(
+                    r#"{"Id":"abc123","Name":"/wolf","State":{"Status":"exited","Running":false,"ExitCode":137,"StartedAt":"2025-01-01T00:00:00Z","FinishedAt":"2025-01-01T01:00:00Z"}}"#,
+                ))
+            } else {
+                let mut resp = Response::new(full(r#"{"message":"No such container"}"#));
+                *resp.status_mut() = StatusCode::NOT_FOUND;
+                resp
+            }
+        });
+        let docker = unix_docker(path);
+
+        let containers = docker.list_containers().await?;
+        assert_eq!(containers[0].name, "wolf");
+        assert_eq!(containers[0].state, "running");
+        assert_eq!(containers[0].labels["com.example"], "x");
+
+        let status = docker.container_status("abc123").await?;
+        assert_eq!(status.name, "wolf");
+        assert_eq!(status.state, "exited");
+        assert!(!status.running);
+        assert_eq!(status.exit_code, Some(137));
+
+        let err = docker.container_status("missing").await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<DockerError>(), Some(DockerError::NotFound(_))), "{err:#}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_socket_is_unavailable() {
+        let docker = unix_docker("/nonexistent/docker.sock".into());
+        let err = docker.list_containers().await.unwrap_err();
+        assert!(
+            matches!(err.downcast_ref::<DockerError>(), Some(DockerError::Unavailable { .. })),
+            "{err:#}"
+        );
+    }
+}
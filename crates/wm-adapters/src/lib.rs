@@ -1,14 +1,58 @@
+pub mod circuit_breaker;
+pub mod concurrency_limit;
+pub mod docker;
+pub mod proxy_metrics;
+pub mod request_id;
+pub mod response_cache;
+pub mod retry_budget;
+pub mod single_flight;
+pub mod sse;
+pub mod webhook_sink;
+pub mod wolf_backoff;
+mod wolf_connector;
+pub mod wolf_endpoint;
+pub mod wolf_error;
 pub mod wolf_proxy;
 
-use anyhow::Result;
+#[cfg(test)]
+mod test_util;
+
+use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures_core::Stream;
-use futures_util::stream;
-use http::Method;
+use futures_util::{stream, StreamExt};
+use http::{header, HeaderMap, HeaderValue, Method, Uri};
+use http_body_util::{BodyExt, Full};
+use serde::{Deserialize, Serialize};
 use std::pin::Pin;
 use std::sync::Arc;
 
+use wolf_proxy::{WolfProxyClient, WolfProxyConfig};
+
+/// Wolf API path listing the Moonlight clients paired with Wolf
+pub const WOLF_CLIENTS_PATH: &str = "/api/v1/clients";
+
+/// Default Wolf API path streaming Wolf's events over SSE
+pub const WOLF_EVENTS_PATH: &str = "/api/v1/events";
+
+/// A Moonlight client known to Wolf
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WolfClient {
+    pub client_id: String,
+    #[serde(default)]
+    pub app_state_folder: Option<String>,
+    /// Per-client settings; passed through as Wolf reports them
+    #[serde(default)]
+    pub settings: serde_json::Value,
+}
+
+/// Body of Wolf's clients listing
+#[derive(Debug, Deserialize)]
+struct WolfClientsResponse {
+    clients: Vec<WolfClient>,
+}
+
 /// Trait for Wolf API communication (passthrough + SSE streaming)
 #[async_trait]
 pub trait WolfApi: Send + Sync {
@@ -23,6 +67,14 @@ pub trait WolfApi: Send + Sync {
         &self,
         path: &str,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>>;
+
+    /// Clients paired with Wolf
+    async fn list_clients(&self) -> Result<Vec<WolfClient>> {
+        let body = self.send_passthrough(Method::GET, WOLF_CLIENTS_PATH, None).await?;
+        let response: WolfClientsResponse =
+            serde_json::from_slice(&body).context("invalid clients response from Wolf")?;
+        Ok(response.clients)
+    }
 }
 
 /// Mock implementation for testing and scaffolding
@@ -50,11 +102,108 @@ impl WolfApi for MockWolfApi {
             b"data: {\"type\":\"mock\"}\n\n",
         ))])))
     }
+
+    async fn list_clients(&self) -> Result<Vec<WolfClient>> {
+        Ok(["steam-deck", "living-room-tv"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| WolfClient {
+                client_id: (1000 + i).to_string(),
+                app_state_folder: Some(format!("/etc/wolf/{}", name)),
+                settings: serde_json::json!({}),
+            })
+            .collect())
+    }
 }
 
 /// Smart constructor for mock implementation
 pub fn mock_wolf() -> Arc<dyn WolfApi> {
-    Arc::new(MockWolfApi::default())
+    Arc::new(MockWolfApi)
+}
+
+/// Production implementation talking to Wolf over its Unix socket
+pub struct UnixWolfApi {
+    client: Arc<WolfProxyClient>,
+}
+
+impl UnixWolfApi {
+    /// Share `client` (and its connection pool) with the `/wolfapi` proxy
+    pub fn new(client: Arc<WolfProxyClient>) -> Self {
+        Self { client }
+    }
+
+    fn parse_path(path: &str) -> Result<Uri> {
+        path.parse::<Uri>()
+            .with_context(|| format!("invalid Wolf API path: {}", path))
+    }
+
+    fn base_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("localhost"));
+        headers
+    }
+}
+
+#[async_trait]
+impl WolfApi for UnixWolfApi {
+    async fn send_passthrough(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Bytes>,
+    ) -> Result<Bytes> {
+        let uri = Self::parse_path(path)?;
+        let mut headers = Self::base_headers();
+        if body.is_some() {
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+        }
+
+        let response = self
+            .client
+            .proxy_request(method.clone(), uri, headers, Full::new(body.unwrap_or_default()), None)
+            .await?;
+
+        let status = response.status();
+        let bytes = response.into_body().collect().await?.to_bytes();
+        if !status.is_success() {
+            return Err(anyhow!("Wolf returned {} for {} {}", status, method, path));
+        }
+        Ok(bytes)
+    }
+
+    async fn sse_stream(
+        &self,
+        path: &str,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>> {
+        let uri = Self::parse_path(path)?;
+        let mut headers = Self::base_headers();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("text/event-stream"));
+
+        let response = self
+            .client
+            .proxy_request(Method::GET, uri, headers, Full::new(Bytes::new()), None)
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(anyhow!("Wolf returned {} for SSE stream {}", status, path));
+        }
+
+        // Forward body chunks as they arrive
+        let stream = response
+            .into_body()
+            .into_data_stream()
+            .map(|chunk| chunk.map_err(anyhow::Error::from));
+        Ok(Box::pin(stream))
+    }
+}
+
+/// Smart constructor for the Unix socket implementation
+pub fn unix_wolf(config: WolfProxyConfig) -> Arc<dyn WolfApi> {
+    Arc::new(UnixWolfApi::new(Arc::new(WolfProxyClient::new(config))))
 }
 
 #[cfg(test)]
@@ -84,4 +233,102 @@ mod tests {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_mock_list_clients() -> Result<()> {
+        let clients = mock_wolf().list_clients().await?;
+        assert_eq!(clients.len(), 2);
+        assert_ne!(clients[0].client_id, clients[1].client_id);
+        Ok(())
+    }
+
+    fn unix_client(socket_path: String) -> Arc<dyn WolfApi> {
+        unix_wolf(WolfProxyConfig::new(socket_path, 1000, 1000).with_retry(1, 10))
+    }
+
+    #[tokio::test]
+    async fn test_unix_passthrough() -> Result<()> {
+        use http::Response;
+        use test_util::{full, spawn_unix_server};
+
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            assert_eq!(req.uri().path(), "/api/v1/apps");
+            Response::new(full(r#"{"apps":[]}"#))
+        });
+
+        let response = unix_client(path)
+            .send_passthrough(Method::GET, "/api/v1/apps", None)
+            .await?;
+        assert_eq!(response, Bytes::from_static(br#"{"apps":[]}"#));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unix_passthrough_error_status() {
+        use http::{Response, StatusCode};
+        use test_util::{full, spawn_unix_server};
+
+        let (_dir, path) = spawn_unix_server(|_req| async move {
+            let mut response = Response::new(full("boom"));
+            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            response
+        });
+
+        let err = unix_client(path)
+            .send_passthrough(Method::GET, "/api/v1/apps", None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("500"));
+    }
+
+    #[tokio::test]
+    async fn test_unix_list_clients() -> Result<()> {
+        use http::Response;
+        use test_util::{full, spawn_unix_server};
+
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            assert_eq!(req.uri().path(), WOLF_CLIENTS_PATH);
+            Response::new(full(
+                r#"{"success":true,"clients":[{"client_id":"42","app_state_folder":"/etc/wolf/42","settings":{"run_uid":1000}}]}"#,
+            ))
+        });
+
+        let clients = unix_client(path).list_clients().await?;
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].client_id, "42");
+        assert_eq!(clients[0].settings["run_uid"], 1000);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unix_list_clients_malformed_json() {
+        use http::Response;
+        use test_util::{full, spawn_unix_server};
+
+        let (_dir, path) = spawn_unix_server(|_req| async move { Response::new(full("<html>oops</html>")) });
+
+        let err = unix_client(path).list_clients().await.unwrap_err();
+        assert!(err.to_string().contains("invalid clients response"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn test_unix_sse_stream() -> Result<()> {
+        use http::Response;
+        use std::time::Duration;
+        use test_util::{chunked, spawn_unix_server};
+
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            assert_eq!(req.headers()[header::ACCEPT], "text/event-stream");
+            Response::new(chunked(
+                vec!["data: {\"n\":1}\n\n", "data: {\"n\":2}\n\n"],
+                Duration::from_millis(10),
+            ))
+        });
+
+        let stream = unix_client(path).sse_stream("/api/v1/events").await?;
+        let chunks: Vec<Bytes> = stream.map(|c| c.unwrap()).collect().await;
+        let body: Vec<u8> = chunks.concat();
+        assert_eq!(body, b"data: {\"n\":1}\n\ndata: {\"n\":2}\n\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+//! Prometheus-style metrics for the Wolf proxy, recorded through the `metrics` facade
+
+use std::time::Duration;
+
+use http::{Method, StatusCode};
+
+pub const REQUESTS_TOTAL: &str = "wolf_proxy_requests_total";
+pub const RESPONSES_TOTAL: &str = "wolf_proxy_responses_total";
+pub const REQUEST_DURATION_SECONDS: &str = "wolf_proxy_request_duration_seconds";
+
+/// Register metric descriptions with the installed recorder
+pub fn describe() {
+    metrics::describe_counter!(REQUESTS_TOTAL, "Requests forwarded to Wolf");
+    metrics::describe_counter!(
+        RESPONSES_TOTAL,
+        "Wolf proxy outcomes by upstream status class (2xx/4xx/5xx/error)"
+    );
+    metrics::describe_histogram!(
+        REQUEST_DURATION_SECONDS,
+        metrics::Unit::Seconds,
+        "Time until Wolf answered with response headers"
+    );
+}
+
+/// Bucket a status into `1xx`..`5xx`; `None` means no response came back
+pub(crate) fn status_class(status: Option<StatusCode>) -> &'static str {
+    match status.map(|s| s.as_u16() / 100) {
+        Some(1) => "1xx",
+        Some(2) => "2xx",
+        Some(3) => "3xx",
+        Some(4) => "4xx",
+        Some(5) => "5xx",
+        _ => "error",
+    }
+}
+
+pub(crate) fn record(method: &Method, status: Option<StatusCode>, elapsed: Duration) {
+    let method = method.to_string();
+    let class = status_class(status);
+
+    metrics::counter!(REQUESTS_TOTAL, "method" => method.clone()).increment(1);
+    metrics::counter!(RESPONSES_TOTAL, "status_class" => class).increment(1);
+    metrics::histogram!(
+        REQUEST_DURATION_SECONDS,
+        "method" => method,
+        "status_class" => class
+    )
+    .record(elapsed.as_secs_f64());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_class() {
+        assert_eq!(status_class(Some(StatusCode::SWITCHING_PROTOCOLS)), "1xx");
+        assert_eq!(status_class(Some(StatusCode::OK)), "2xx");
+        assert_eq!(status_class(Some(StatusCode::NOT_FOUND)), "4xx");
+        assert_eq!(status_class(Some(StatusCode::BAD_GATEWAY)), "5xx");
+        assert_eq!(status_class(None), "error");
+    }
+}
@@ -0,0 +1,21 @@
+//! Per-request correlation ID, held in a task-local so adapters can read it
+//! without threading it through every call
+
+use std::future::Future;
+use std::sync::Arc;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: Arc<str>;
+}
+
+/// Run `f` with `id` as the current request ID
+pub async fn scope<F: Future>(id: Arc<str>, f: F) -> F::Output {
+    REQUEST_ID.scope(id, f).await
+}
+
+/// The request ID for the current task, if running inside [`scope`]
+pub fn current() -> Option<Arc<str>> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
@@ -0,0 +1,195 @@
+//! Short-lived cache of Wolf answers to GETs on a configured set of paths
+
+use bytes::Bytes;
+use http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A response as stored: everything but the connection it came in on
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+#[derive(Debug)]
+struct Entry {
+    response: CachedResponse,
+    expires: Instant,
+}
+
+/// Path and query, and the request's `Accept-Encoding`: a body Wolf compressed for one
+/// client is only replayed to clients that asked for the same encodings
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey(String, Option<HeaderValue>);
+
+impl CacheKey {
+    pub fn new(uri: &Uri, request_headers: &HeaderMap) -> Self {
+        Self(
+            uri.path_and_query().map_or_else(|| uri.path().to_owned(), |p| p.as_str().to_owned()),
+            request_headers.get(header::ACCEPT_ENCODING).cloned(),
+        )
+    }
+}
+
+/// Whether Wolf lets `response` be replayed to other clients: not with `Vary` (the key
+/// can't tell those clients apart), `Set-Cookie`, or `Cache-Control: no-store`/`private`
+fn storable(response: &CachedResponse) -> bool {
+    let headers = &response.headers;
+    let forbids = headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|directive| directive.split('=').next().unwrap_or_default().trim())
+        .any(|name| name.eq_ignore_ascii_case("no-store") || name.eq_ignore_ascii_case("private"));
+    response.status.is_success()
+        && !forbids
+        && !headers.contains_key(header::VARY)
+        && !headers.contains_key(header::SET_COOKIE)
+}
+
+/// Keeps successful GET responses for `ttl`, keyed by [`CacheKey`]. Only the listed
+/// `paths` (exact matches) are cached, so anything left off keeps going to Wolf every time.
+#[derive(Debug)]
+pub struct ResponseCache {
+    paths: Vec<String>,
+    ttl: Duration,
+    entries: Mutex<HashMap<CacheKey, Entry>>,
+}
+
+impl ResponseCache {
+    /// No `paths` or a zero `ttl` disables the cache
+    pub fn new(paths: Vec<String>, ttl: Duration) -> Self {
+        Self {
+            paths,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether a `method` request for `uri` may be answered from, and stored in, the cache
+    pub fn covers(&self, method: &Method, uri: &Uri) -> bool {
+        !self.ttl.is_zero() && *method == Method::GET && self.paths.iter().any(|p| p == uri.path())
+    }
+
+    /// The stored response for `key`, unless it has expired
+    pub fn get(&self, key: &CacheKey) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.expires > Instant::now() => Some(entry.response.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store `response` under `key` if it is a success Wolf allows to be shared; anything
+    /// else is never cached
+    pub fn put(&self, key: CacheKey, response: CachedResponse) {
+        if !storable(&response) {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        // Drop what has expired so queries that are never repeated don't pile up
+        entries.retain(|_, entry| entry.expires > now);
+        entries.insert(
+            key,
+            Entry {
+                response,
+                expires: now + self.ttl,
+            },
+        );
+    }
+
+    /// Entries currently stored, expired ones included until they are next touched
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: StatusCode) -> CachedResponse {
+        CachedResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(b"{}"),
+        }
+    }
+
+    #[test]
+    fn test_covers_only_listed_get_paths() {
+        let cache = ResponseCache::new(vec!["/api/v1/version".into()], Duration::from_secs(5));
+        assert!(cache.covers(&Method::GET, &"/api/v1/version?x=1".parse().unwrap()));
+        assert!(!cache.covers(&Method::POST, &"/api/v1/version".parse().unwrap()));
+        assert!(!cache.covers(&Method::GET, &"/api/v1/version/extra".parse().unwrap()));
+
+        let disabled = ResponseCache::new(vec!["/api/v1/version".into()], Duration::ZERO);
+        assert!(!disabled.covers(&Method::GET, &"/api/v1/version".parse().unwrap()));
+    }
+
+    fn key(uri: &str) -> CacheKey {
+        CacheKey::new(&uri.parse().unwrap(), &HeaderMap::new())
+    }
+
+    #[test]
+    fn test_entries_expire_and_errors_are_not_stored() {
+        let cache = ResponseCache::new(vec!["/v".into()], Duration::from_millis(20));
+        cache.put(key("/v"), response(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(cache.get(&key("/v")).is_none());
+
+        cache.put(key("/v"), response(StatusCode::OK));
+        assert_eq!(cache.get(&key("/v")).unwrap().status, StatusCode::OK);
+        assert!(cache.get(&key("/v?other")).is_none(), "the query is part of the key");
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.get(&key("/v")).is_none());
+        assert!(cache.is_empty(), "the expired entry was dropped");
+    }
+
+    #[test]
+    fn test_accept_encoding_is_part_of_the_key() {
+        let cache = ResponseCache::new(vec!["/v".into()], Duration::from_secs(5));
+        let uri: Uri = "/v".parse().unwrap();
+        let mut gzip = HeaderMap::new();
+        gzip.insert(header::ACCEPT_ENCODING, HeaderValue::from_static("gzip"));
+        cache.put(CacheKey::new(&uri, &gzip), response(StatusCode::OK));
+
+        assert!(cache.get(&CacheKey::new(&uri, &gzip)).is_some());
+        assert!(cache.get(&CacheKey::new(&uri, &HeaderMap::new())).is_none());
+    }
+
+    #[test]
+    fn test_private_and_varying_responses_are_not_stored() {
+        let cache = ResponseCache::new(vec!["/v".into()], Duration::from_secs(5));
+        for (name, value) in [
+            (header::CACHE_CONTROL, "no-store"),
+            (header::CACHE_CONTROL, "max-age=60, Private"),
+            (header::CACHE_CONTROL, "private=\"set-cookie\""),
+            (header::VARY, "Accept-Language"),
+            (header::SET_COOKIE, "session=abc"),
+        ] {
+            let mut response = response(StatusCode::OK);
+            response.headers.insert(name.clone(), HeaderValue::from_static(value));
+            cache.put(key("/v"), response);
+            assert!(cache.is_empty(), "{name}: {value}");
+        }
+
+        let mut response = response(StatusCode::OK);
+        response.headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("public, max-age=60"));
+        cache.put(key("/v"), response);
+        assert_eq!(cache.len(), 1);
+    }
+}
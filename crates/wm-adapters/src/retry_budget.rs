@@ -0,0 +1,97 @@
+//! Token bucket limiting how many retries all Wolf requests may make together
+
+use std::sync::Mutex;
+use std::time::Instant;
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Each retry spends one token; tokens come back at `refill_per_sec` up to `capacity`.
+/// While Wolf keeps failing the bucket drains, and every request after that gets a single
+/// attempt instead of the full `retry_attempts`, so an outage isn't multiplied by retries.
+#[derive(Debug)]
+pub struct RetryBudget {
+    /// `None` when retries are unbudgeted
+    bucket: Option<Mutex<Bucket>>,
+    capacity: u32,
+    refill_per_sec: u32,
+}
+
+impl RetryBudget {
+    /// A `capacity` of 0 disables the budget: every request may retry in full
+    pub fn new(capacity: u32, refill_per_sec: u32) -> Self {
+        Self {
+            bucket: (capacity > 0).then(|| {
+                Mutex::new(Bucket {
+                    tokens: f64::from(capacity),
+                    refilled_at: Instant::now(),
+                })
+            }),
+            capacity,
+            refill_per_sec,
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let earned = now.duration_since(bucket.refilled_at).as_secs_f64() * f64::from(self.refill_per_sec);
+        bucket.tokens = (bucket.tokens + earned).min(f64::from(self.capacity));
+        bucket.refilled_at = now;
+    }
+
+    /// Spend a token on one retry; `false` means the caller should give up instead
+    pub fn try_spend(&self) -> bool {
+        let Some(bucket) = &self.bucket else {
+            return true;
+        };
+        let mut bucket = bucket.lock().unwrap();
+        self.refill(&mut bucket);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whole retries currently available; `None` when unbudgeted
+    pub fn remaining(&self) -> Option<u32> {
+        let bucket = self.bucket.as_ref()?;
+        let mut bucket = bucket.lock().unwrap();
+        self.refill(&mut bucket);
+        Some(bucket.tokens as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_disabled_budget_always_allows() {
+        let budget = RetryBudget::new(0, 0);
+        assert!((0..1000).all(|_| budget.try_spend()));
+        assert_eq!(budget.remaining(), None);
+    }
+
+    #[test]
+    fn test_spends_down_then_refills() {
+        let budget = RetryBudget::new(3, 100);
+        assert!((0..3).all(|_| budget.try_spend()));
+        assert!(!budget.try_spend(), "bucket is empty");
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(budget.try_spend(), "a token came back");
+    }
+
+    #[test]
+    fn test_refill_is_capped_at_capacity() {
+        let budget = RetryBudget::new(2, 1000);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(budget.remaining(), Some(2));
+    }
+}
@@ -0,0 +1,131 @@
+//! Lets concurrent identical requests share one upstream call
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+/// Calls in flight, by key. The first caller for a key leads and does the work; callers
+/// arriving while it runs follow and get a clone of whatever it finishes with.
+#[derive(Debug)]
+pub struct SingleFlight<K, V> {
+    flights: Arc<Mutex<HashMap<K, watch::Receiver<Option<V>>>>>,
+}
+
+impl<K, V> Default for SingleFlight<K, V> {
+    fn default() -> Self {
+        Self {
+            flights: Arc::default(),
+        }
+    }
+}
+
+/// Which part a caller plays in its key's flight
+pub enum Joined<K: Hash + Eq, V> {
+    Leader(Leader<K, V>),
+    Follower(Follower<V>),
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> SingleFlight<K, V> {
+    /// Lead a new flight for `key`, or follow the one already running
+    pub fn join(&self, key: K) -> Joined<K, V> {
+        let mut flights = self.flights.lock().unwrap();
+        if let Some(rx) = flights.get(&key) {
+            return Joined::Follower(Follower(rx.clone()));
+        }
+        let (tx, rx) = watch::channel(None);
+        flights.insert(key.clone(), rx);
+        Joined::Leader(Leader {
+            flights: self.flights.clone(),
+            key,
+            tx,
+        })
+    }
+
+    /// Flights currently running
+    pub fn len(&self) -> usize {
+        self.flights.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The caller doing the work. Dropping it without `finish` (the caller was cancelled)
+/// lets the followers know, so they can make the call themselves.
+pub struct Leader<K: Hash + Eq, V> {
+    flights: Arc<Mutex<HashMap<K, watch::Receiver<Option<V>>>>>,
+    key: K,
+    tx: watch::Sender<Option<V>>,
+}
+
+impl<K: Hash + Eq, V> Leader<K, V> {
+    /// Whether anyone is waiting on this flight yet
+    pub fn has_followers(&self) -> bool {
+        // The map keeps one receiver of its own
+        self.tx.receiver_count() > 1
+    }
+
+    /// Hand `value` to every follower. Callers arriving from now on start a new flight.
+    pub fn finish(self, value: V) {
+        self.tx.send_replace(Some(value));
+    }
+}
+
+impl<K: Hash + Eq, V> Drop for Leader<K, V> {
+    fn drop(&mut self) {
+        self.flights.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// A caller waiting on someone else's flight
+pub struct Follower<V>(watch::Receiver<Option<V>>);
+
+impl<V: Clone> Follower<V> {
+    /// The leader's value; `None` if the leader gave up without one
+    pub async fn wait(mut self) -> Option<V> {
+        self.0.wait_for(Option::is_some).await.ok().and_then(|value| value.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_followers_get_the_leaders_value() {
+        let flights = SingleFlight::<&str, u32>::default();
+        let Joined::Leader(leader) = flights.join("/apps") else {
+            panic!("the first caller leads");
+        };
+        assert!(!leader.has_followers());
+        let followers: Vec<_> = (0..3)
+            .map(|_| match flights.join("/apps") {
+                Joined::Follower(follower) => tokio::spawn(follower.wait()),
+                Joined::Leader(_) => panic!("a flight is already running"),
+            })
+            .collect();
+        assert!(leader.has_followers());
+        assert!(matches!(flights.join("/other"), Joined::Leader(_)), "keys fly separately");
+
+        leader.finish(7);
+        for follower in followers {
+            assert_eq!(follower.await.unwrap(), Some(7));
+        }
+        assert!(flights.is_empty());
+        assert!(matches!(flights.join("/apps"), Joined::Leader(_)), "a finished flight isn't joined");
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_flight_releases_followers() {
+        let flights = SingleFlight::<&str, u32>::default();
+        let leader = flights.join("/apps");
+        let Joined::Follower(follower) = flights.join("/apps") else {
+            panic!("a flight is already running");
+        };
+        drop(leader);
+        assert_eq!(follower.wait().await, None);
+        assert!(flights.is_empty());
+    }
+}
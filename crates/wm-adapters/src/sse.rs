@@ -0,0 +1,159 @@
+//! Incremental decoder for Server-Sent Events, such as Wolf's event stream
+
+/// One dispatched SSE event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The `event:` field; `None` means the default `message` type
+    pub event: Option<String>,
+    /// `data:` lines joined with `\n`
+    pub data: String,
+    /// The `id:` field, if the event set one
+    pub id: Option<String>,
+}
+
+/// Turns a stream's chunks into whole events, however the chunks split them. Lines may end
+/// in `\n`, `\r\n` or `\r`; a blank line ends an event. Comments (`:` lines), `retry:` and
+/// unknown fields are dropped, as are events without any `data:` line.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    /// The unfinished line at the end of the last chunk
+    line: Vec<u8>,
+    /// The last chunk ended in `\r`, so a `\n` opening the next one belongs to that line end
+    skip_lf: bool,
+    event: Option<String>,
+    data: Option<String>,
+    id: Option<String>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the next chunk; returns the events it completes, in order
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            match byte {
+                b'\n' if std::mem::take(&mut self.skip_lf) => {}
+                b'\r' | b'\n' => {
+                    self.skip_lf = byte == b'\r';
+                    let line = std::mem::take(&mut self.line);
+                    events.extend(self.end_line(&line));
+                }
+                _ => {
+                    self.skip_lf = false;
+                    self.line.push(byte);
+                }
+            }
+        }
+        events
+    }
+
+    fn end_line(&mut self, line: &[u8]) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        let line = String::from_utf8_lossy(line);
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (&*line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_owned()),
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_owned()),
+            },
+            "id" => self.id = Some(value.to_owned()),
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        let id = self.id.take();
+        Some(SseEvent {
+            event,
+            data: self.data.take()?,
+            id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(data: &str) -> SseEvent {
+        SseEvent {
+            event: None,
+            data: data.into(),
+            id: None,
+        }
+    }
+
+    /// Every event from `chunks`, fed one after another to a single decoder
+    fn decode(chunks: &[&[u8]]) -> Vec<SseEvent> {
+        let mut decoder = SseDecoder::new();
+        chunks.iter().flat_map(|chunk| decoder.push(chunk)).collect()
+    }
+
+    #[test]
+    fn test_fields_and_multi_line_data() {
+        let events = decode(&[b"event: session\nid: 42\ndata: {\"a\":1,\ndata:\"b\":2}\nretry: 3000\n\n"]);
+        assert_eq!(
+            events,
+            [SseEvent {
+                event: Some("session".into()),
+                data: "{\"a\":1,\n\"b\":2}".into(),
+                id: Some("42".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_events_split_anywhere_reassemble() {
+        let stream: &[u8] = b": ping\n\ndata: first\n\nevent: second\r\ndata: two\r\ndata: lines\r\n\r\ndata: third\r\rdata: caf\xc3\xa9\n\n";
+        let expected = vec![
+            message("first"),
+            SseEvent {
+                event: Some("second".into()),
+                data: "two\nlines".into(),
+                id: None,
+            },
+            message("third"),
+            message("café"),
+        ];
+        assert_eq!(decode(&[stream]), expected);
+        // Every split point, including between `\r` and `\n` and inside `é`
+        for at in 0..=stream.len() {
+            let (head, tail) = stream.split_at(at);
+            assert_eq!(decode(&[head, tail]), expected, "split at {at}");
+        }
+        let bytes: Vec<&[u8]> = stream.chunks(1).collect();
+        assert_eq!(decode(&bytes), expected);
+    }
+
+    #[test]
+    fn test_partial_event_waits_for_its_blank_line() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(b"data: {\"type\":").is_empty());
+        assert!(decoder.push(b"\"pong\"}\n").is_empty());
+        assert_eq!(decoder.push(b"\ndata: next"), [message("{\"type\":\"pong\"}")]);
+    }
+
+    #[test]
+    fn test_events_without_data_are_dropped() {
+        assert!(decode(&[b": keepalive\n\nevent: ping\nid: 7\n\n"]).is_empty());
+        // The dropped event's fields don't leak into the next one
+        assert_eq!(decode(&[b"event: ping\n\ndata:\n\n"]), [message("")]);
+    }
+}
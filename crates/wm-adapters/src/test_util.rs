@@ -0,0 +1,192 @@
+//! Helpers for tests that need a fake Wolf listening on a Unix socket or TCP port
+
+use bytes::Bytes;
+use futures_util::stream;
+use http::{Request, Response};
+use http_body_util::{combinators::BoxBody, BodyExt, Full, StreamBody};
+use hyper::body::{Frame, Incoming};
+use hyper::service::service_fn;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, UnixListener};
+
+pub(crate) type TestBody = BoxBody<Bytes, Infallible>;
+
+/// Fixed body response
+pub(crate) fn full(body: impl Into<Bytes>) -> TestBody {
+    Full::new(body.into()).boxed()
+}
+
+/// Body that yields each chunk after `delay`
+pub(crate) fn chunked(chunks: Vec<&'static str>, delay: Duration) -> TestBody {
+    let frames = stream::unfold(chunks.into_iter(), move |mut chunks| async move {
+        let chunk = chunks.next()?;
+        tokio::time::sleep(delay).await;
+        Some((
+            Ok::<_, Infallible>(Frame::data(Bytes::from_static(chunk.as_bytes()))),
+            chunks,
+        ))
+    });
+    StreamBody::new(frames).boxed()
+}
+
+/// Serve `handler` over HTTP/1 on a fresh Unix socket.
+/// Keep the returned `TempDir` alive for as long as the socket is needed.
+pub(crate) fn spawn_unix_server<F, Fut>(handler: F) -> (tempfile::TempDir, String)
+where
+    F: Fn(Request<Incoming>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<TestBody>> + Send + 'static,
+{
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wolf.sock");
+    spawn_unix_server_at(&path, handler);
+    (dir, path.to_string_lossy().into_owned())
+}
+
+/// Serve `handler` over HTTP/1 on a Unix socket at `path`
+pub(crate) fn spawn_unix_server_at<F, Fut>(path: &Path, handler: F)
+where
+    F: Fn(Request<Incoming>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<TestBody>> + Send + 'static,
+{
+    let listener = UnixListener::bind(path).unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_connection(stream, handler.clone()));
+        }
+    });
+}
+
+/// Serve `handler` over HTTP/1 on an ephemeral loopback TCP port
+pub(crate) async fn spawn_tcp_server<F, Fut>(handler: F) -> SocketAddr
+where
+    F: Fn(Request<Incoming>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<TestBody>> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_connection(stream, handler.clone()));
+        }
+    });
+    addr
+}
+
+/// Serve `handler` over HTTP/1 and TLS on an ephemeral loopback TCP port, with the
+/// `testdata` server certificate, accepting only clients with a certificate from the test CA
+pub(crate) async fn spawn_mtls_tcp_server<F, Fut>(handler: F) -> SocketAddr
+where
+    F: Fn(Request<Incoming>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<TestBody>> + Send + 'static,
+{
+    use rustls::pki_types::pem::PemObject;
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+    use std::sync::Arc;
+    use tokio_rustls::rustls;
+
+    let testdata = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata");
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(CertificateDer::from_pem_file(testdata.join("ca.pem")).unwrap()).unwrap();
+    let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .unwrap();
+    let config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            vec![CertificateDer::from_pem_file(testdata.join("server.pem")).unwrap()],
+            PrivateKeyDer::from_pem_file(testdata.join("server.key")).unwrap(),
+        )
+        .unwrap();
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let (acceptor, handler) = (acceptor.clone(), handler.clone());
+            tokio::spawn(async move {
+                if let Ok(stream) = acceptor.accept(stream).await {
+                    serve_connection(stream, handler).await;
+                }
+            });
+        }
+    });
+    addr
+}
+
+/// Serve `handler` over HTTP/2 with prior knowledge (h2c) on an ephemeral loopback TCP port
+pub(crate) async fn spawn_h2_tcp_server<F, Fut>(handler: F) -> SocketAddr
+where
+    F: Fn(Request<Incoming>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<TestBody>> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_h2_connection(stream, handler.clone()));
+        }
+    });
+    addr
+}
+
+/// Serve `handler` over HTTP/2 with prior knowledge on a fresh Unix socket
+pub(crate) fn spawn_h2_unix_server<F, Fut>(handler: F) -> (tempfile::TempDir, String)
+where
+    F: Fn(Request<Incoming>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<TestBody>> + Send + 'static,
+{
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wolf.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_h2_connection(stream, handler.clone()));
+        }
+    });
+    (dir, path.to_string_lossy().into_owned())
+}
+
+async fn serve_h2_connection<S, F, Fut>(stream: S, handler: F)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    F: Fn(Request<Incoming>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<TestBody>> + Send + 'static,
+{
+    let service = service_fn(move |req| {
+        let fut = handler(req);
+        async move { Ok::<_, Infallible>(fut.await) }
+    });
+    let _ = hyper::server::conn::http2::Builder::new(TokioExecutor::new())
+        .serve_connection(TokioIo::new(stream), service)
+        .await;
+}
+
+async fn serve_connection<S, F, Fut>(stream: S, handler: F)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    F: Fn(Request<Incoming>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<TestBody>> + Send + 'static,
+{
+    let service = service_fn(move |req| {
+        let fut = handler(req);
+        async move { Ok::<_, Infallible>(fut.await) }
+    });
+    let _ = hyper::server::conn::http1::Builder::new()
+        .serve_connection(TokioIo::new(stream), service)
+        .with_upgrades()
+        .await;
+}
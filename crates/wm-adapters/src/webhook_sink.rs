@@ -0,0 +1,264 @@
+//! Event sink POSTing each event as JSON to an external URL
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt::Write as _;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::warn;
+use wm_core::{Event, EventSink};
+
+use crate::wolf_backoff::Backoff;
+
+/// Header carrying the body's HMAC-SHA256 when a secret is configured
+pub const SIGNATURE_HEADER: &str = "x-wm-signature";
+
+/// Events waiting for delivery; past this, new events are dropped until the webhook catches up
+pub const WEBHOOK_QUEUE_CAPACITY: usize = 1024;
+
+/// `sha256=` and the lowercase hex HMAC-SHA256 of `body` keyed with `secret`, GitHub style
+pub fn signature(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let mut value = String::with_capacity(7 + digest.len() * 2);
+    value.push_str("sha256=");
+    for byte in digest {
+        let _ = write!(value, "{:02x}", byte);
+    }
+    value
+}
+
+/// One webhook endpoint and how hard to try reaching it
+struct Delivery {
+    client: reqwest::Client,
+    url: String,
+    secret: Option<Vec<u8>>,
+    attempts: u32,
+    backoff: Backoff,
+}
+
+impl Delivery {
+    async fn post(&self, body: &[u8]) -> Result<()> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, body));
+        }
+        let response = request.body(body.to_vec()).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(anyhow!("webhook answered {}", status));
+        }
+        Ok(())
+    }
+
+    /// POST `body`, retrying with `backoff` until it succeeds or the attempts run out
+    async fn deliver(&self, body: &[u8]) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match self.post(body).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= self.attempts => {
+                    return Err(e.context(format!("gave up after {} attempts", attempt)));
+                }
+                Err(e) => {
+                    warn!(attempt, max_attempts = self.attempts, "Webhook delivery failed, retrying: {:#}", e);
+                    tokio::time::sleep(self.backoff.delay(attempt)).await;
+                }
+            }
+        }
+    }
+}
+
+/// Delivers events to a webhook from a background task, in order, retrying failed deliveries
+/// with `backoff`. `emit` only queues the event, so a slow or failing webhook never holds up
+/// ingestion; a delivery that still fails after its retries is logged and dropped.
+/// A delivery fails on a connection error, a timeout, or any non-2xx answer.
+pub struct WebhookSink {
+    queue: mpsc::Sender<Vec<u8>>,
+}
+
+impl WebhookSink {
+    /// `attempts` counts the first try; each one is bounded by `timeout`. With a `secret`
+    /// every POST carries its signature in `X-WM-Signature`. Must be called within a Tokio
+    /// runtime; the delivery task ends when the sink is dropped.
+    pub fn new(
+        url: String,
+        secret: Option<String>,
+        timeout: Duration,
+        attempts: u32,
+        backoff: Backoff,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent(crate::wolf_proxy::DEFAULT_USER_AGENT)
+            .build()
+            .context("failed to build webhook client")?;
+        let delivery = Delivery {
+            client,
+            url,
+            secret: secret.map(String::into_bytes),
+            attempts: attempts.max(1),
+            backoff,
+        };
+
+        let (queue, mut pending) = mpsc::channel::<Vec<u8>>(WEBHOOK_QUEUE_CAPACITY);
+        tokio::spawn(async move {
+            while let Some(body) = pending.recv().await {
+                if let Err(e) = delivery.deliver(&body).await {
+                    warn!("Dropping webhook delivery: {:#}", e);
+                }
+            }
+        });
+        Ok(Self { queue })
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn emit(&self, event: &Event) -> Result<()> {
+        let body = serde_json::to_vec(event)?;
+        self.queue.try_send(body).map_err(|e| match e {
+            TrySendError::Full(_) => anyhow!("{} webhook deliveries already queued; event dropped", WEBHOOK_QUEUE_CAPACITY),
+            TrySendError::Closed(_) => anyhow!("webhook delivery task has stopped; event dropped"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{full, spawn_tcp_server};
+    use crate::wolf_backoff::BackoffStrategy;
+    use http::{Response, StatusCode};
+    use http_body_util::BodyExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use time::OffsetDateTime;
+    use wm_core::SessionId;
+
+    const SECRET: &str = "hook-secret";
+
+    fn backoff() -> Backoff {
+        Backoff {
+            strategy: BackoffStrategy::Fixed,
+            base: Duration::from_millis(5),
+            max: Duration::from_millis(5),
+            jitter: false,
+        }
+    }
+
+    fn event() -> Event {
+        Event::SessionStarted {
+            session_id: SessionId(uuid::Uuid::nil()),
+            at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    /// What the webhook received: the body and `X-WM-Signature` of each POST
+    type Received = Arc<Mutex<Vec<(Vec<u8>, Option<String>)>>>;
+
+    /// Answers 500 to the first `failures` POSTs and 204 after that, keeping every request
+    async fn webhook(failures: usize) -> (String, Arc<AtomicUsize>, Received) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let received = Received::default();
+        let (counter, seen) = (hits.clone(), received.clone());
+        let addr = spawn_tcp_server(move |req| {
+            let (counter, seen) = (counter.clone(), seen.clone());
+            async move {
+                assert_eq!(req.headers()[http::header::CONTENT_TYPE], "application/json");
+                let signature = req.headers().get(SIGNATURE_HEADER).map(|v| v.to_str().unwrap().to_owned());
+                let body = req.into_body().collect().await.unwrap().to_bytes();
+                seen.lock().unwrap().push((body.to_vec(), signature));
+                let mut response = Response::new(full(""));
+                *response.status_mut() = if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                } else {
+                    StatusCode::NO_CONTENT
+                };
+                response
+            }
+        })
+        .await;
+        (format!("http://{}/hooks/wolf", addr), hits, received)
+    }
+
+    async fn wait_for_hits(hits: &AtomicUsize, expected: usize) {
+        for _ in 0..200 {
+            if hits.load(Ordering::SeqCst) >= expected {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // Let any extra, unexpected attempt land before the caller counts
+        tokio::time::sleep(Duration::from_millis(30)).await;
+    }
+
+    #[test]
+    fn test_signature_matches_known_hmac() {
+        assert_eq!(
+            signature(b"key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_posts_signed_event_json_and_retries_500() {
+        let (url, hits, received) = webhook(1).await;
+        let sink = WebhookSink::new(url, Some(SECRET.into()), Duration::from_secs(2), 3, backoff()).unwrap();
+        sink.emit(&event()).await.unwrap();
+        wait_for_hits(&hits, 2).await;
+
+        assert_eq!(hits.load(Ordering::SeqCst), 2, "the 500 was retried once");
+        let received = received.lock().unwrap();
+        assert_eq!(received[0], received[1], "the retry resends the same event");
+        let (body, signature) = &received[0];
+        assert_eq!(signature.as_deref(), Some(super::signature(SECRET.as_bytes(), body).as_str()));
+        let json: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(json["type"], "SessionStarted");
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_without_secret_and_dropped_after_attempts() {
+        let (url, hits, received) = webhook(usize::MAX).await;
+        let sink = WebhookSink::new(url, None, Duration::from_secs(2), 2, backoff()).unwrap();
+        sink.emit(&event()).await.unwrap();
+        wait_for_hits(&hits, 2).await;
+
+        assert_eq!(hits.load(Ordering::SeqCst), 2, "no attempts past the limit");
+        assert!(received.lock().unwrap().iter().all(|(_, signature)| signature.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_emit_does_not_wait_for_delivery() {
+        // Accepts connections and never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+
+        let url = format!("http://{}/", addr);
+        let sink = WebhookSink::new(url, None, Duration::from_secs(30), 1, backoff()).unwrap();
+        let queued = tokio::time::timeout(Duration::from_millis(200), async {
+            for _ in 0..10 {
+                sink.emit(&event()).await.unwrap();
+            }
+        })
+        .await;
+        assert!(queued.is_ok(), "emit waited on the webhook");
+    }
+}
@@ -0,0 +1,97 @@
+//! Delays between retried connection attempts and transient-error retries
+
+use std::time::Duration;
+
+/// Default ceiling on a single retry delay
+pub const DEFAULT_MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// How the delay grows from one retry to the next
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackoffStrategy {
+    /// `base` before every retry
+    Fixed,
+    /// `base * attempt`
+    #[default]
+    Linear,
+    /// `base * 2^(attempt - 1)`
+    Exponential,
+}
+
+/// Retry delay policy; `attempt` counts the attempts already made, starting at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    pub strategy: BackoffStrategy,
+    pub base: Duration,
+    /// No delay exceeds this, jittered or not
+    pub max: Duration,
+    /// Full jitter: pick uniformly in `[0, delay]` so clients don't reconnect in lockstep
+    pub jitter: bool,
+}
+
+impl Backoff {
+    /// Capped delay before the next attempt, without jitter
+    pub fn nominal(&self, attempt: u32) -> Duration {
+        let attempt = attempt.max(1);
+        let delay = match self.strategy {
+            BackoffStrategy::Fixed => Some(self.base),
+            BackoffStrategy::Linear => self.base.checked_mul(attempt),
+            BackoffStrategy::Exponential => 2u32
+                .checked_pow(attempt - 1)
+                .and_then(|factor| self.base.checked_mul(factor)),
+        };
+        delay.map_or(self.max, |d| d.min(self.max))
+    }
+
+    /// Delay to sleep before the next attempt
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let nominal = self.nominal(attempt);
+        if !self.jitter || nominal.is_zero() {
+            return nominal;
+        }
+        let nanos = u64::try_from(nominal.as_nanos()).unwrap_or(u64::MAX);
+        Duration::from_nanos(fastrand::u64(0..=nanos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backoff(strategy: BackoffStrategy, jitter: bool) -> Backoff {
+        Backoff {
+            strategy,
+            base: Duration::from_millis(100),
+            max: Duration::from_millis(500),
+            jitter,
+        }
+    }
+
+    fn sequence(backoff: Backoff) -> Vec<u128> {
+        (1..=5).map(|attempt| backoff.delay(attempt).as_millis()).collect()
+    }
+
+    #[test]
+    fn test_delay_sequences_are_capped() {
+        assert_eq!(sequence(backoff(BackoffStrategy::Fixed, false)), [100, 100, 100, 100, 100]);
+        assert_eq!(sequence(backoff(BackoffStrategy::Linear, false)), [100, 200, 300, 400, 500]);
+        assert_eq!(sequence(backoff(BackoffStrategy::Exponential, false)), [100, 200, 400, 500, 500]);
+        // Overflowing growth saturates at the cap rather than panicking
+        assert_eq!(
+            backoff(BackoffStrategy::Exponential, false).delay(200),
+            Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        for strategy in [BackoffStrategy::Fixed, BackoffStrategy::Linear, BackoffStrategy::Exponential] {
+            let jittered = backoff(strategy, true);
+            for attempt in 1..=6 {
+                let nominal = jittered.nominal(attempt);
+                let delays: Vec<_> = (0..200).map(|_| jittered.delay(attempt)).collect();
+                assert!(delays.iter().all(|d| *d <= nominal), "{strategy:?} attempt {attempt}");
+                assert!(delays.iter().any(|d| *d < nominal), "{strategy:?} attempt {attempt} never jittered");
+            }
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! Connector backing the pooled Wolf HTTP client (Unix socket, TCP, or TLS)
+
+use http::Uri;
+use hyper_util::client::legacy::connect::{Connected, Connection};
+use hyper_util::rt::TokioIo;
+use std::error::Error as StdError;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpStream, UnixStream};
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::TlsConnector;
+use tower_service::Service;
+use tracing::warn;
+
+use crate::retry_budget::RetryBudget;
+use crate::wolf_backoff::Backoff;
+use crate::wolf_endpoint::WolfEndpoint;
+use crate::wolf_error::WolfProxyError;
+
+/// Connection counters shared by the connector and every connection it opens
+#[derive(Debug, Default)]
+pub(crate) struct ConnStats {
+    opened: AtomicU64,
+    live: AtomicUsize,
+}
+
+impl ConnStats {
+    pub(crate) fn opened(&self) -> u64 {
+        self.opened.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn live(&self) -> usize {
+        self.live.load(Ordering::Relaxed)
+    }
+}
+
+/// A connected transport to Wolf
+pub(crate) enum WolfStream {
+    Unix(UnixStream),
+    Tcp(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+/// Opens new connections to Wolf, retrying with backoff while it is unavailable
+#[derive(Clone)]
+pub(crate) struct WolfConnector {
+    pub(crate) endpoint: Arc<WolfEndpoint>,
+    /// TLS settings for TLS endpoints, or why they could not be built
+    pub(crate) tls: Result<Option<(TlsConnector, ServerName<'static>)>, Arc<str>>,
+    pub(crate) connect_timeout: Duration,
+    pub(crate) retry_attempts: u32,
+    pub(crate) backoff: Backoff,
+    pub(crate) budget: Arc<RetryBudget>,
+    pub(crate) stats: Arc<ConnStats>,
+}
+
+/// Sort a failed connect into a permission problem, which retrying won't fix, or anything else
+pub(crate) fn connect_error(target: String, e: io::Error) -> WolfProxyError {
+    let reason = e.to_string();
+    if e.kind() == io::ErrorKind::PermissionDenied {
+        WolfProxyError::PermissionDenied { target, reason }
+    } else {
+        WolfProxyError::ConnectFailed { target, reason }
+    }
+}
+
+impl WolfConnector {
+    /// Human-readable target for error messages
+    pub(crate) fn target(&self) -> String {
+        match &*self.endpoint {
+            WolfEndpoint::Unix(_) => "wolf.sock".to_string(),
+            endpoint => format!("Wolf at {}", endpoint),
+        }
+    }
+
+    /// Open one transport connection (with TLS handshake when configured)
+    async fn open(&self) -> Result<WolfStream, WolfProxyError> {
+        let connect_failed = |e: io::Error| connect_error(self.target(), e);
+        let handshake_failed = |reason: String| WolfProxyError::Handshake {
+            target: self.target(),
+            reason,
+        };
+
+        match &*self.endpoint {
+            WolfEndpoint::Unix(path) => UnixStream::connect(path)
+                .await
+                .map(WolfStream::Unix)
+                .map_err(connect_failed),
+            WolfEndpoint::Tcp { host, port, .. } => {
+                // Never fall back to plaintext when TLS was asked for but is misconfigured
+                let tls = self.tls.as_ref().map_err(|e| handshake_failed(e.to_string()))?;
+                let tcp = TcpStream::connect((host.as_str(), *port))
+                    .await
+                    .map_err(connect_failed)?;
+                let _ = tcp.set_nodelay(true);
+                match tls {
+                    Some((connector, server_name)) => {
+                        let tls = connector
+                            .connect(server_name.clone(), tcp)
+                            .await
+                            .map_err(|e| handshake_failed(e.to_string()))?;
+                        Ok(WolfStream::Tls(Box::new(tls)))
+                    }
+                    None => Ok(WolfStream::Tcp(tcp)),
+                }
+            }
+        }
+    }
+
+    /// Connect once without retrying; used by readiness checks
+    pub(crate) async fn probe(&self) -> Result<(), WolfProxyError> {
+        tokio::time::timeout(self.connect_timeout, self.open())
+            .await
+            .map_err(|_| WolfProxyError::ConnectTimeout { attempts: 1 })??;
+        Ok(())
+    }
+
+    /// Spend a retry from the shared budget, if any is left
+    fn retry_allowed(&self) -> bool {
+        let allowed = self.budget.try_spend();
+        if !allowed {
+            warn!("Wolf connection failed; retry budget exhausted, not retrying");
+        }
+        allowed
+    }
+
+    async fn connect(&self) -> Result<WolfStream, WolfProxyError> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            match tokio::time::timeout(self.connect_timeout, self.open()).await {
+                Ok(Ok(stream)) => return Ok(stream),
+                Ok(Err(e @ WolfProxyError::PermissionDenied { .. })) => return Err(e),
+                Ok(Err(e)) => {
+                    if attempt >= self.retry_attempts || !self.retry_allowed() {
+                        return Err(e);
+                    }
+                    warn!(
+                        attempt = attempt,
+                        max_attempts = self.retry_attempts,
+                        "Wolf connection failed, retrying..."
+                    );
+                    tokio::time::sleep(self.backoff.delay(attempt)).await;
+                }
+                Err(_) => {
+                    if attempt >= self.retry_attempts || !self.retry_allowed() {
+                        return Err(WolfProxyError::ConnectTimeout { attempts: attempt });
+                    }
+                    warn!(
+                        attempt = attempt,
+                        max_attempts = self.retry_attempts,
+                        "Wolf connection timeout, retrying..."
+                    );
+                    tokio::time::sleep(self.backoff.delay(attempt)).await;
+                }
+            }
+        }
+    }
+}
+
+impl Service<Uri> for WolfConnector {
+    type Response = WolfConnection;
+    type Error = Box<dyn StdError + Send + Sync>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _uri: Uri) -> Self::Future {
+        let connector = self.clone();
+        Box::pin(async move {
+            let stream = connector.connect().await?;
+            // Only a TLS endpoint offering h2 can negotiate it; the pooled client then speaks h2
+            let negotiated_h2 = matches!(&stream, WolfStream::Tls(tls) if tls.get_ref().1.alpn_protocol() == Some(b"h2"));
+            connector.stats.opened.fetch_add(1, Ordering::Relaxed);
+            connector.stats.live.fetch_add(1, Ordering::Relaxed);
+            Ok(WolfConnection {
+                io: TokioIo::new(stream),
+                stats: connector.stats,
+                negotiated_h2,
+            })
+        })
+    }
+}
+
+/// A Wolf connection that updates `ConnStats` when it closes
+pub(crate) struct WolfConnection {
+    io: TokioIo<WolfStream>,
+    stats: Arc<ConnStats>,
+    negotiated_h2: bool,
+}
+
+impl Drop for WolfConnection {
+    fn drop(&mut self) {
+        self.stats.live.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Connection for WolfConnection {
+    fn connected(&self) -> Connected {
+        let connected = Connected::new();
+        if self.negotiated_h2 {
+            connected.negotiated_h2()
+        } else {
+            connected
+        }
+    }
+}
+
+impl hyper::rt::Read for WolfConnection {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: hyper::rt::ReadBufCursor<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl hyper::rt::Write for WolfConnection {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write_vectored(cx, bufs)
+    }
+}
+
+macro_rules! delegate {
+    ($self:ident, $s:ident => $e:expr) => {
+        match $self.get_mut() {
+            WolfStream::Unix($s) => {
+                let $s = Pin::new($s);
+                $e
+            }
+            WolfStream::Tcp($s) => {
+                let $s = Pin::new($s);
+                $e
+            }
+            WolfStream::Tls($s) => {
+                let $s = Pin::new($s.as_mut());
+                $e
+            }
+        }
+    };
+}
+
+impl AsyncRead for WolfStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        delegate!(self, s => s.poll_read(cx, buf))
+    }
+}
+
+impl AsyncWrite for WolfStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        delegate!(self, s => s.poll_write(cx, buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        delegate!(self, s => s.poll_flush(cx))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        delegate!(self, s => s.poll_shutdown(cx))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        delegate!(self, s => s.poll_write_vectored(cx, bufs))
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            WolfStream::Unix(s) => s.is_write_vectored(),
+            WolfStream::Tcp(s) => s.is_write_vectored(),
+            WolfStream::Tls(s) => s.is_write_vectored(),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Where the Wolf API lives: a local Unix socket or a TCP host, optionally over TLS
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use tokio_rustls::rustls::{self, ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+/// Trust and identity for TLS endpoints: the CAs a Wolf server certificate must chain
+/// to, and the client certificate presented when Wolf requires mutual TLS
+#[derive(Debug, Clone)]
+pub struct WolfTls {
+    client: Arc<ClientConfig>,
+}
+
+impl Default for WolfTls {
+    /// The bundled Mozilla roots, no client certificate
+    fn default() -> Self {
+        Self::from_pem(None, None).expect("the bundled roots always build a client config")
+    }
+}
+
+impl WolfTls {
+    /// Trust the PEM `ca` bundle instead of the bundled Mozilla roots, and present the PEM
+    /// `identity` (certificate chain, private key) to servers asking for one
+    pub fn from_pem(ca: Option<&[u8]>, identity: Option<(&[u8], &[u8])>) -> Result<Self> {
+        let roots = match ca {
+            Some(pem) => {
+                let mut roots = RootCertStore::empty();
+                for cert in certificates(pem).context("invalid CA bundle")? {
+                    roots.add(cert).context("invalid CA certificate")?;
+                }
+                roots
+            }
+            None => RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            },
+        };
+        let builder = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots);
+        let client = match identity {
+            Some((cert, key)) => {
+                let chain = certificates(cert).context("invalid client certificate")?;
+                let key = PrivateKeyDer::from_pem_slice(key)
+                    .map_err(|e| anyhow!("invalid client key: {}", e))?;
+                builder
+                    .with_client_auth_cert(chain, key)
+                    .context("client certificate and key don't form a usable pair")?
+            }
+            None => builder.with_no_client_auth(),
+        };
+        Ok(Self {
+            client: Arc::new(client),
+        })
+    }
+
+    /// `from_pem` reading each part from a PEM file
+    pub fn load(ca: Option<&Path>, cert: Option<&Path>, key: Option<&Path>) -> Result<Self> {
+        let read = |path: &Path| std::fs::read(path).with_context(|| format!("failed to read {}", path.display()));
+        let ca = ca.map(read).transpose()?;
+        let identity = match (cert, key) {
+            (Some(cert), Some(key)) => Some((read(cert)?, read(key)?)),
+            (None, None) => None,
+            _ => bail!("a client certificate needs both the certificate and its key"),
+        };
+        Self::from_pem(ca.as_deref(), identity.as_ref().map(|(cert, key)| (&cert[..], &key[..])))
+    }
+
+    /// Whether a client certificate is presented
+    pub fn has_client_auth(&self) -> bool {
+        self.client.client_auth_cert_resolver.has_certs()
+    }
+}
+
+/// Every certificate in a PEM bundle; an empty or unreadable one is an error
+fn certificates(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_slice_iter(pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow!("{}", e))?;
+    if certs.is_empty() {
+        bail!("no PEM certificates found");
+    }
+    Ok(certs)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WolfEndpoint {
+    /// Path to wolf.sock (the default deployment)
+    Unix(String),
+    /// Remote Wolf reachable over TCP
+    Tcp { host: String, port: u16, tls: bool },
+}
+
+impl WolfEndpoint {
+    /// Parse `WM_WOLF_ENDPOINT`.
+    ///
+    /// Accepted forms:
+    /// - `/path/to/wolf.sock` or `unix:/path/to/wolf.sock`
+    /// - `host:port` or `tcp://host:port` (TLS when `tls` is set)
+    /// - `https://host:port` (always TLS)
+    pub fn parse(endpoint: &str, tls: bool) -> Result<Self> {
+        let endpoint = endpoint.trim();
+        if endpoint.is_empty() {
+            bail!("Wolf endpoint is empty");
+        }
+
+        if let Some(path) = endpoint.strip_prefix("unix:") {
+            let path = path.trim_start_matches("//");
+            if path.is_empty() {
+                bail!("Wolf endpoint {:?} has no socket path", endpoint);
+            }
+            if tls {
+                bail!("TLS is not supported for Unix socket endpoint {:?}", endpoint);
+            }
+            return Ok(Self::Unix(path.to_string()));
+        }
+        if endpoint.starts_with('/') || endpoint.starts_with('.') {
+            if tls {
+                bail!("TLS is not supported for Unix socket endpoint {:?}", endpoint);
+            }
+            return Ok(Self::Unix(endpoint.to_string()));
+        }
+
+        let (address, tls) = if let Some(rest) = endpoint.strip_prefix("https://") {
+            (rest, true)
+        } else if let Some(rest) = endpoint
+            .strip_prefix("tcp://")
+            .or_else(|| endpoint.strip_prefix("http://"))
+        {
+            (rest, tls)
+        } else if endpoint.contains("://") {
+            bail!("Wolf endpoint {:?} has an unsupported scheme", endpoint);
+        } else {
+            (endpoint, tls)
+        };
+
+        let address = address.trim_end_matches('/');
+        let (host, port) = address
+            .rsplit_once(':')
+            .ok_or_else(|| anyhow!("Wolf endpoint {:?} must be host:port", endpoint))?;
+        let port: u16 = port
+            .parse()
+            .with_context(|| format!("Wolf endpoint {:?} has an invalid port", endpoint))?;
+        // Bracketed IPv6 literals keep their brackets out of the stored host
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() {
+            bail!("Wolf endpoint {:?} has no host", endpoint);
+        }
+
+        Ok(Self::Tcp {
+            host: host.to_string(),
+            port,
+            tls,
+        })
+    }
+
+    pub fn is_tls(&self) -> bool {
+        matches!(self, Self::Tcp { tls: true, .. })
+    }
+
+    /// Authority used in upstream request URIs
+    pub(crate) fn authority(&self) -> String {
+        match self {
+            Self::Unix(_) => "localhost".to_string(),
+            Self::Tcp { host, port, .. } if host.contains(':') => format!("[{}]:{}", host, port),
+            Self::Tcp { host, port, .. } => format!("{}:{}", host, port),
+        }
+    }
+
+    /// TLS connector and server name for TLS endpoints; `None` for plaintext.
+    /// With `offer_h2` the handshake advertises `h2` ahead of `http/1.1` via ALPN.
+    pub(crate) fn tls_connector(
+        &self,
+        tls: &WolfTls,
+        offer_h2: bool,
+    ) -> Result<Option<(TlsConnector, ServerName<'static>)>> {
+        let Self::Tcp { host, tls: true, .. } = self else {
+            return Ok(None);
+        };
+
+        let mut config = (*tls.client).clone();
+        if offer_h2 {
+            config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        }
+        let server_name = ServerName::try_from(host.clone())
+            .with_context(|| format!("invalid TLS server name {:?}", host))?;
+
+        Ok(Some((TlsConnector::from(Arc::new(config)), server_name)))
+    }
+}
+
+impl fmt::Display for WolfEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "unix:{}", path),
+            Self::Tcp { tls: true, .. } => write!(f, "https://{}", self.authority()),
+            Self::Tcp { tls: false, .. } => write!(f, "tcp://{}", self.authority()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tcp(host: &str, port: u16, tls: bool) -> WolfEndpoint {
+        WolfEndpoint::Tcp {
+            host: host.into(),
+            port,
+            tls,
+        }
+    }
+
+    #[test]
+    fn test_parse_unix() {
+        let expected = WolfEndpoint::Unix("/var/run/wolf/wolf.sock".into());
+        assert_eq!(WolfEndpoint::parse("/var/run/wolf/wolf.sock", false).unwrap(), expected);
+        assert_eq!(WolfEndpoint::parse("unix:/var/run/wolf/wolf.sock", false).unwrap(), expected);
+        assert_eq!(WolfEndpoint::parse("unix:///var/run/wolf/wolf.sock", false).unwrap(), expected);
+        assert!(WolfEndpoint::parse("/var/run/wolf/wolf.sock", true).is_err());
+    }
+
+    #[test]
+    fn test_parse_tcp() {
+        assert_eq!(WolfEndpoint::parse("wolf.lan:8443", false).unwrap(), tcp("wolf.lan", 8443, false));
+        assert_eq!(WolfEndpoint::parse("tcp://10.0.0.5:80", false).unwrap(), tcp("10.0.0.5", 80, false));
+        assert_eq!(WolfEndpoint::parse("[::1]:8080", false).unwrap(), tcp("::1", 8080, false));
+        assert!(WolfEndpoint::parse("wolf.lan", false).is_err());
+        assert!(WolfEndpoint::parse("wolf.lan:http", false).is_err());
+        assert!(WolfEndpoint::parse("ftp://wolf.lan:21", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_tls() {
+        assert_eq!(WolfEndpoint::parse("wolf.lan:8443", true).unwrap(), tcp("wolf.lan", 8443, true));
+        assert_eq!(WolfEndpoint::parse("https://wolf.lan:8443", false).unwrap(), tcp("wolf.lan", 8443, true));
+    }
+
+    #[test]
+    fn test_tls_connector_selection() {
+        let tls = WolfTls::default();
+        assert!(WolfEndpoint::Unix("/tmp/wolf.sock".into()).tls_connector(&tls, true).unwrap().is_none());
+        assert!(tcp("wolf.lan", 80, false).tls_connector(&tls, true).unwrap().is_none());
+        assert!(tcp("wolf.lan", 443, true).tls_connector(&tls, false).unwrap().is_some());
+        assert!(tcp("wolf.lan", 443, true).tls_connector(&tls, true).unwrap().is_some());
+    }
+
+    fn testdata(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata").join(name)
+    }
+
+    #[test]
+    fn test_load_client_identity() {
+        assert!(!WolfTls::default().has_client_auth());
+        let tls = WolfTls::load(
+            Some(&testdata("ca.pem")),
+            Some(&testdata("client.pem")),
+            Some(&testdata("client.key")),
+        )
+        .unwrap();
+        assert!(tls.has_client_auth());
+        let ca_only = WolfTls::load(Some(&testdata("ca.pem")), None, None).unwrap();
+        assert!(!ca_only.has_client_auth());
+    }
+
+    #[test]
+    fn test_load_rejects_bad_identities() {
+        let err = |ca: Option<&str>, cert: Option<&str>, key: Option<&str>| {
+            let (ca, cert, key) = (ca.map(testdata), cert.map(testdata), key.map(testdata));
+            format!("{:#}", WolfTls::load(ca.as_deref(), cert.as_deref(), key.as_deref()).unwrap_err())
+        };
+
+        let mismatched = err(None, Some("client.pem"), Some("other.key"));
+        assert!(mismatched.contains("don't form a usable pair"), "{mismatched}");
+        // A certificate where the key should be
+        let not_a_key = err(None, Some("client.pem"), Some("client.pem"));
+        assert!(not_a_key.contains("invalid client key"), "{not_a_key}");
+        let not_a_cert = err(None, Some("client.key"), Some("client.key"));
+        assert!(not_a_cert.contains("invalid client certificate"), "{not_a_cert}");
+        let bad_ca = err(Some("client.key"), None, None);
+        assert!(bad_ca.contains("invalid CA bundle"), "{bad_ca}");
+        let missing = err(None, Some("client.pem"), Some("missing.key"));
+        assert!(missing.contains("missing.key"), "{missing}");
+        let half = err(None, Some("client.pem"), None);
+        assert!(half.contains("both the certificate and its key"), "{half}");
+    }
+
+    #[test]
+    fn test_display_and_authority() {
+        assert_eq!(WolfEndpoint::Unix("/tmp/wolf.sock".into()).to_string(), "unix:/tmp/wolf.sock");
+        assert_eq!(tcp("::1", 8080, false).to_string(), "tcp://[::1]:8080");
+        assert_eq!(tcp("wolf.lan", 443, true).authority(), "wolf.lan:443");
+    }
+}
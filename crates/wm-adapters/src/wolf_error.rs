@@ -0,0 +1,68 @@
+//! Typed failures of the Wolf proxy, so callers can pick a status without parsing messages
+
+use std::error::Error as StdError;
+use std::time::Duration;
+
+/// Why a proxied request to Wolf failed
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WolfProxyError {
+    /// Every connection attempt ran past `connect_timeout`
+    #[error("connection timeout after {attempts} attempts")]
+    ConnectTimeout { attempts: u32 },
+    /// Wolf's socket or port refused the connection (or doesn't exist)
+    #[error("failed to connect to {target}: {reason}")]
+    ConnectFailed { target: String, reason: String },
+    /// The OS refused access to Wolf's socket (EACCES): wrong owner, group or mode
+    #[error("permission denied connecting to {target}: {reason}; check that the socket's owner, group and mode let WolfManager's user connect")]
+    PermissionDenied { target: String, reason: String },
+    /// Connected, but TLS could not be set up with Wolf
+    #[error("failed to connect to {target}: TLS handshake failed: {reason}")]
+    Handshake { target: String, reason: String },
+    /// Wolf accepted the request but didn't send response headers within `read_timeout`
+    #[error("read timeout after {}ms", .0.as_millis())]
+    ReadTimeout(Duration),
+    /// Too many recent connection failures; retry after the remaining cooldown
+    #[error("circuit breaker open: skipping connection to Wolf for {}ms", .0.as_millis())]
+    CircuitOpen(Duration),
+    /// `limit` requests were already in flight and the wait queue was full
+    #[error("too many concurrent Wolf requests: {limit} in flight and the queue is full")]
+    QueueFull { limit: usize },
+    /// Queued behind the concurrency limit for longer than the acquire timeout
+    #[error("no Wolf request slot freed up within {}ms", .0.as_millis())]
+    QueueTimeout(Duration),
+    #[error("request body exceeds limit of {0} bytes")]
+    BodyTooLarge(usize),
+    /// A request header, or all of them together, exceeded the configured size limits
+    #[error("request headers too large: {0}")]
+    HeadersTooLarge(String),
+    /// The path and query, after `/wolfapi` is stripped, exceeded the configured limit
+    #[error("request URI is {len} bytes, limit is {limit}")]
+    UriTooLong { len: usize, limit: usize },
+    /// The client's request body failed while being read
+    #[error("failed to read request body: {0}")]
+    RequestBody(String),
+    /// The upstream request could not be built
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+    /// Any other failure talking to Wolf once connected
+    #[error("{0}")]
+    Upstream(String),
+}
+
+impl From<http::Error> for WolfProxyError {
+    fn from(e: http::Error) -> Self {
+        Self::InvalidRequest(e.to_string())
+    }
+}
+
+/// `e` and its sources joined with `: `, like anyhow's `{:#}`
+pub(crate) fn error_chain(e: &(dyn StdError + 'static)) -> String {
+    let mut message = e.to_string();
+    let mut source = e.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
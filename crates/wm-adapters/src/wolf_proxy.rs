@@ -1,24 +1,146 @@
-use anyhow::{anyhow, Context, Result};
-use bytes::Bytes;
-use http::{header, HeaderMap, HeaderName, Method, Request, Response, StatusCode};
-use http_body_util::{BodyExt, Full};
-use hyper::body::Incoming;
-use hyper_util::rt::TokioIo;
+use anyhow::{anyhow, Result};
+use bytes::{Bytes, BytesMut};
+use http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode};
+use http_body_util::combinators::UnsyncBoxBody;
+use futures_util::StreamExt as _;
+use http_body_util::{BodyExt, BodyStream, Empty, Full, LengthLimitError, Limited, StreamBody};
+use hyper::body::{Body, Frame, Incoming};
+use hyper::upgrade::OnUpgrade;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use ipnet::IpNet;
+use std::error::Error as StdError;
+use std::net::IpAddr;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::net::UnixStream;
-use tracing::{info, warn};
+use tokio::io::AsyncWriteExt;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn, Instrument};
+
+use crate::circuit_breaker::CircuitBreaker;
+use crate::concurrency_limit::ConcurrencyLimit;
+use crate::proxy_metrics;
+use crate::request_id::{self, REQUEST_ID_HEADER};
+use crate::response_cache::{CacheKey, CachedResponse, ResponseCache};
+use crate::retry_budget::RetryBudget;
+use crate::single_flight::{Joined, Leader, SingleFlight};
+use crate::wolf_connector::{ConnStats, WolfConnector};
+pub use crate::wolf_backoff::{Backoff, BackoffStrategy, DEFAULT_MAX_RETRY_DELAY};
+pub use crate::wolf_endpoint::{WolfEndpoint, WolfTls};
+pub use crate::wolf_error::WolfProxyError;
+use crate::wolf_error::error_chain;
 
 /// Configuration for the Wolf proxy client
 #[derive(Debug, Clone)]
 pub struct WolfProxyConfig {
-    pub socket_path: String,
+    /// Unix socket (default) or TCP/TLS address of the Wolf API
+    pub endpoint: WolfEndpoint,
+    /// Trusted CAs and client certificate for a TLS `endpoint`
+    pub tls: WolfTls,
     pub connect_timeout: Duration,
     pub read_timeout: Duration,
     pub retry_attempts: u32,
+    /// Base delay between retries; `backoff` decides how it grows
     pub retry_delay: Duration,
+    pub backoff: BackoffStrategy,
+    /// Ceiling on any single retry delay
+    pub retry_max_delay: Duration,
+    /// Randomize each retry delay within `[0, delay]` (full jitter)
+    pub retry_jitter: bool,
+    /// Largest request body forwarded to Wolf; bodies are streamed, never buffered whole
+    pub max_body_bytes: usize,
+    /// Idle keep-alive connections kept for reuse (0 disables pooling)
+    pub pool_max_idle: usize,
+    /// How long an idle pooled connection is kept before being closed
+    pub pool_idle_timeout: Duration,
+    /// Retry idempotent requests when Wolf answers 502/503/504
+    pub retry_on_5xx: bool,
+    /// Retries all requests may make in a burst before they fail after one attempt
+    /// (0 = no budget, every request retries in full)
+    pub retry_budget: u32,
+    /// Retries returned to the budget per second
+    pub retry_budget_refill_per_sec: u32,
+    /// Consecutive connection failures before the circuit breaker opens (0 disables it)
+    pub breaker_failure_threshold: u32,
+    /// How long the breaker stays open before letting a probe through
+    pub breaker_cooldown: Duration,
+    /// Peer ranges whose inbound `X-Forwarded-For`/`Forwarded` chain is kept and extended
+    pub trusted_proxies: Vec<IpNet>,
+    /// Headers stripped in both directions on top of the RFC 7230 hop-by-hop set
+    pub strip_headers: Vec<HeaderName>,
+    /// Largest total request header size, counted as on the wire (`name: value\r\n`)
+    pub max_headers_bytes: usize,
+    /// Largest single request header name or value
+    pub max_header_bytes: usize,
+    /// Longest path plus query forwarded to Wolf
+    pub max_uri_bytes: usize,
+    /// HTTP version spoken to Wolf; WebSocket upgrades always use HTTP/1.1
+    pub http_version: UpstreamHttpVersion,
+    /// `User-Agent` sent when the client supplied none (`None` forwards requests without one)
+    pub user_agent: Option<HeaderValue>,
+    /// Host sent to Wolf in place of the client's (`Host`, or `:authority` over h2); the
+    /// client's still travels as `X-Forwarded-Host`. `None` forwards the client's
+    pub host_override: Option<http::uri::Authority>,
+    /// `Content-Type` added to Wolf responses that carry a body but name no type, so browsers
+    /// don't sniff one (`None` passes them through untyped)
+    pub default_content_type: Option<HeaderValue>,
+    /// Rewrite Wolf's 4xx/5xx bodies into our `ErrorBody` envelope, the original under
+    /// `upstream`; off passes them through as Wolf sent them
+    pub normalize_errors: bool,
+    /// Most requests sent to Wolf at once (0 = unlimited); a request holds its slot until
+    /// Wolf's response headers arrive
+    pub max_concurrency: usize,
+    /// Requests allowed to wait for a slot; more than that are rejected straight away
+    pub concurrency_queue: usize,
+    /// Longest a queued request waits for a slot
+    pub concurrency_timeout: Duration,
+    /// GET paths whose successful responses are kept for `cache_ttl` and served without
+    /// asking Wolf again (exact matches; empty disables the cache)
+    pub cache_paths: Vec<String>,
+    pub cache_ttl: Duration,
+    /// Largest response body read whole to be cached or shared; bigger ones stream as usual
+    pub max_buffered_bytes: usize,
+    /// Concurrent identical GET/HEAD requests share one upstream call and its response
+    pub coalesce: bool,
+}
+
+/// HTTP version used on connections to Wolf
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpstreamHttpVersion {
+    #[default]
+    Http1,
+    /// HTTP/2, multiplexing concurrent requests over one connection. TLS endpoints offer it
+    /// via ALPN and fall back to HTTP/1.1 if Wolf declines; Unix sockets and plaintext TCP
+    /// use prior knowledge, so Wolf must accept h2c there.
+    Http2,
 }
 
+/// Default request body limit for proxied requests (100 MiB)
+pub const DEFAULT_MAX_BODY_BYTES: usize = 100 * 1024 * 1024;
+
+/// Default limit on a response body held in memory for the cache or coalescing (1 MiB)
+pub const DEFAULT_MAX_BUFFERED_BYTES: usize = 1024 * 1024;
+
+/// Default limit on all request headers together (64 KiB)
+pub const DEFAULT_MAX_HEADERS_BYTES: usize = 64 * 1024;
+
+/// Default limit on one request header name or value (8 KiB)
+pub const DEFAULT_MAX_HEADER_BYTES: usize = 8 * 1024;
+
+/// Default limit on a proxied path plus query (16 KiB)
+pub const DEFAULT_MAX_URI_BYTES: usize = 16 * 1024;
+
+/// Largest Wolf error body rewritten into our envelope; bigger ones pass through untouched
+pub const MAX_NORMALIZED_ERROR_BYTES: usize = 64 * 1024;
+
+/// Default `User-Agent` for proxied requests that arrive without one
+pub const DEFAULT_USER_AGENT: &str = concat!("wolfmanager/", env!("CARGO_PKG_VERSION"));
+pub const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Our `Via` entry (RFC 9110 §7.6.3), appended to any the client sent
+const VIA: &str = "1.1 wolfmanager";
+
 impl WolfProxyConfig {
     pub fn new(
         socket_path: String,
@@ -26,24 +148,309 @@ impl WolfProxyConfig {
         read_timeout_ms: u64,
     ) -> Self {
         Self {
-            socket_path,
+            endpoint: WolfEndpoint::Unix(socket_path),
+            tls: WolfTls::default(),
             connect_timeout: Duration::from_millis(connect_timeout_ms),
             read_timeout: Duration::from_millis(read_timeout_ms),
             retry_attempts: 3,
             retry_delay: Duration::from_millis(500),
+            backoff: BackoffStrategy::Linear,
+            retry_max_delay: DEFAULT_MAX_RETRY_DELAY,
+            retry_jitter: false,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            pool_max_idle: 8,
+            pool_idle_timeout: Duration::from_secs(30),
+            retry_on_5xx: true,
+            retry_budget: 100,
+            retry_budget_refill_per_sec: 10,
+            breaker_failure_threshold: 5,
+            breaker_cooldown: Duration::from_secs(10),
+            trusted_proxies: Vec::new(),
+            strip_headers: Vec::new(),
+            max_headers_bytes: DEFAULT_MAX_HEADERS_BYTES,
+            max_header_bytes: DEFAULT_MAX_HEADER_BYTES,
+            max_uri_bytes: DEFAULT_MAX_URI_BYTES,
+            http_version: UpstreamHttpVersion::Http1,
+            user_agent: Some(HeaderValue::from_static(DEFAULT_USER_AGENT)),
+            host_override: None,
+            default_content_type: Some(HeaderValue::from_static(DEFAULT_CONTENT_TYPE)),
+            normalize_errors: false,
+            max_concurrency: 0,
+            concurrency_queue: 0,
+            concurrency_timeout: Duration::from_secs(5),
+            cache_paths: Vec::new(),
+            cache_ttl: Duration::from_secs(30),
+            max_buffered_bytes: DEFAULT_MAX_BUFFERED_BYTES,
+            coalesce: false,
         }
     }
 
+    pub fn with_endpoint(mut self, endpoint: WolfEndpoint) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    pub fn with_tls(mut self, tls: WolfTls) -> Self {
+        self.tls = tls;
+        self
+    }
+
     pub fn with_retry(mut self, attempts: u32, delay_ms: u64) -> Self {
         self.retry_attempts = attempts;
         self.retry_delay = Duration::from_millis(delay_ms);
         self
     }
+
+    pub fn with_backoff(mut self, strategy: BackoffStrategy, max_delay_ms: u64, jitter: bool) -> Self {
+        self.backoff = strategy;
+        self.retry_max_delay = Duration::from_millis(max_delay_ms);
+        self.retry_jitter = jitter;
+        self
+    }
+
+    /// Delay policy shared by connection retries and 5xx retries
+    pub fn retry_backoff(&self) -> Backoff {
+        Backoff {
+            strategy: self.backoff,
+            base: self.retry_delay,
+            max: self.retry_max_delay,
+            jitter: self.retry_jitter,
+        }
+    }
+
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    pub fn with_pool(mut self, max_idle: usize, idle_timeout_ms: u64) -> Self {
+        self.pool_max_idle = max_idle;
+        self.pool_idle_timeout = Duration::from_millis(idle_timeout_ms);
+        self
+    }
+
+    pub fn with_retry_on_5xx(mut self, enabled: bool) -> Self {
+        self.retry_on_5xx = enabled;
+        self
+    }
+
+    pub fn with_retry_budget(mut self, capacity: u32, refill_per_sec: u32) -> Self {
+        self.retry_budget = capacity;
+        self.retry_budget_refill_per_sec = refill_per_sec;
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, failure_threshold: u32, cooldown_ms: u64) -> Self {
+        self.breaker_failure_threshold = failure_threshold;
+        self.breaker_cooldown = Duration::from_millis(cooldown_ms);
+        self
+    }
+
+    pub fn with_trusted_proxies(mut self, trusted_proxies: Vec<IpNet>) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+
+    pub fn with_strip_headers(mut self, strip_headers: Vec<HeaderName>) -> Self {
+        self.strip_headers = strip_headers;
+        self
+    }
+
+    pub fn with_header_limits(mut self, max_headers_bytes: usize, max_header_bytes: usize) -> Self {
+        self.max_headers_bytes = max_headers_bytes;
+        self.max_header_bytes = max_header_bytes;
+        self
+    }
+
+    pub fn with_max_uri_bytes(mut self, max_uri_bytes: usize) -> Self {
+        self.max_uri_bytes = max_uri_bytes;
+        self
+    }
+
+    pub fn with_http_version(mut self, http_version: UpstreamHttpVersion) -> Self {
+        self.http_version = http_version;
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: Option<HeaderValue>) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    pub fn with_host_override(mut self, host: Option<http::uri::Authority>) -> Self {
+        self.host_override = host;
+        self
+    }
+
+    pub fn with_normalized_errors(mut self, normalize_errors: bool) -> Self {
+        self.normalize_errors = normalize_errors;
+        self
+    }
+
+    pub fn with_default_content_type(mut self, content_type: Option<HeaderValue>) -> Self {
+        self.default_content_type = content_type;
+        self
+    }
+
+    pub fn with_concurrency_limit(mut self, max: usize, queue: usize, timeout_ms: u64) -> Self {
+        self.max_concurrency = max;
+        self.concurrency_queue = queue;
+        self.concurrency_timeout = Duration::from_millis(timeout_ms);
+        self
+    }
+
+    pub fn with_response_cache(mut self, paths: Vec<String>, ttl_ms: u64) -> Self {
+        self.cache_paths = paths;
+        self.cache_ttl = Duration::from_millis(ttl_ms);
+        self
+    }
+
+    pub fn with_max_buffered_bytes(mut self, max_buffered_bytes: usize) -> Self {
+        self.max_buffered_bytes = max_buffered_bytes;
+        self
+    }
+
+    pub fn with_coalescing(mut self, enabled: bool) -> Self {
+        self.coalesce = enabled;
+        self
+    }
+}
+
+/// Methods that are safe to resend without risking duplicate side effects
+fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Upstream statuses that usually mean Wolf is restarting or briefly overloaded
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Body type sent upstream; every request body is boxed so pooled connections share one type
+type ProxyBody = UnsyncBoxBody<Bytes, Box<dyn StdError + Send + Sync>>;
+
+/// Body of a Wolf response: streamed from the connection, or replayed from the cache
+pub type WolfBody = UnsyncBoxBody<Bytes, hyper::Error>;
+
+fn streamed(response: Response<Incoming>) -> Response<WolfBody> {
+    response.map(BodyExt::boxed_unsync)
+}
+
+fn replayed(cached: CachedResponse) -> Response<WolfBody> {
+    let mut response = Response::new(Full::new(cached.body).map_err(|never| match never {}).boxed_unsync());
+    *response.status_mut() = cached.status;
+    *response.headers_mut() = cached.headers;
+    response
+}
+
+fn is_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"))
+}
+
+/// Method, path and query, and `Accept-Encoding`: requests agreeing on all of them get
+/// interchangeable answers from Wolf
+type FlightKey = (Method, String, Option<HeaderValue>);
+
+/// What a coalesced request's leader hands its followers
+type Flight = Result<CachedResponse, WolfProxyError>;
+
+/// The key to coalesce a request under, if it can be: a bodiless GET or HEAD that isn't
+/// asking for a range or an event stream
+fn flight_key<B: Body>(method: &Method, uri: &http::Uri, headers: &HeaderMap, body: &B) -> Option<FlightKey> {
+    let wants_stream = headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.contains("text/event-stream"));
+    if !matches!(*method, Method::GET | Method::HEAD)
+        || !body.is_end_stream()
+        || headers.contains_key(header::RANGE)
+        || wants_stream
+    {
+        return None;
+    }
+    let target = uri.path_and_query().map_or_else(|| uri.path().to_owned(), |p| p.as_str().to_owned());
+    Some((method.clone(), target, headers.get(header::ACCEPT_ENCODING).cloned()))
+}
+
+/// Which of the proxy's shortcuts a request goes through
+#[derive(Debug, Clone, Copy)]
+struct Route {
+    /// Waits for a slot under the concurrency limit
+    limited: bool,
+    /// May be answered by the response cache or an identical in-flight request
+    shared: bool,
+}
+
+impl Route {
+    const PROXIED: Self = Self {
+        limited: true,
+        shared: true,
+    };
+    const HEALTH_CHECK: Self = Self {
+        limited: false,
+        shared: false,
+    };
+}
+
+/// A Wolf response read whole, so it can be replayed, or still streaming from the connection
+enum Fetched {
+    Buffered(CachedResponse),
+    Streamed(Response<WolfBody>),
+}
+
+/// The first error of type `E` in `err`'s source chain, including `err` itself
+fn find_cause<'a, E: StdError + 'static>(err: &'a (dyn StdError + 'static)) -> Option<&'a E> {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if let Some(found) = e.downcast_ref::<E>() {
+            return Some(found);
+        }
+        current = e.source();
+    }
+    None
+}
+
+/// Check whether an error was caused by the request body exceeding `max_body_bytes`
+fn is_body_limit_error(err: &(dyn StdError + 'static)) -> bool {
+    find_cause::<LengthLimitError>(err).is_some()
+}
+
+/// Marks a failure reading the client's body, so it isn't blamed on Wolf
+#[derive(Debug)]
+struct ClientBodyError(Box<dyn StdError + Send + Sync>);
+
+impl std::fmt::Display for ClientBodyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl StdError for ClientBodyError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.0.source()
+    }
+}
+
+/// Tag client body errors other than the length limit as `ClientBodyError`
+fn tag_body_error(e: Box<dyn StdError + Send + Sync>) -> Box<dyn StdError + Send + Sync> {
+    if is_body_limit_error(&*e) {
+        e
+    } else {
+        Box::new(ClientBodyError(e))
+    }
 }
 
-/// Hop-by-hop headers that should not be forwarded
-fn hop_by_hop_headers() -> Vec<HeaderName> {
-    vec![
+/// Headers that should not be forwarded for this message: the RFC 7230 hop-by-hop set,
+/// the configured `extra` ones, and any the message itself lists in `Connection`
+fn hop_by_hop_headers(headers: &HeaderMap, extra: &[HeaderName]) -> Vec<HeaderName> {
+    let mut hop_headers = vec![
         header::CONNECTION,
         header::PROXY_AUTHENTICATE,
         header::PROXY_AUTHORIZATION,
@@ -52,180 +459,2731 @@ fn hop_by_hop_headers() -> Vec<HeaderName> {
         header::TRANSFER_ENCODING,
         header::UPGRADE,
         HeaderName::from_static("keep-alive"),
-    ]
+    ];
+    hop_headers.extend_from_slice(extra);
+    hop_headers.extend(
+        headers
+            .get_all(header::CONNECTION)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .filter_map(|token| HeaderName::from_bytes(token.trim().as_bytes()).ok()),
+    );
+    hop_headers
+}
+
+/// Copy `headers` without hop-by-hop fields, keeping repeated values
+fn without_hop_by_hop(headers: &HeaderMap, extra: &[HeaderName]) -> HeaderMap {
+    let hop_headers = hop_by_hop_headers(headers, extra);
+    let mut filtered = HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers.iter() {
+        if !hop_headers.contains(name) {
+            filtered.append(name.clone(), value.clone());
+        }
+    }
+    filtered
+}
+
+/// Client-supplied forwarding headers; rebuilt per request rather than copied
+fn is_forwarding_header(name: &HeaderName) -> bool {
+    name == header::FORWARDED || name.as_str() == "x-forwarded-for"
+}
+
+/// Every value of `name` joined into one comma-separated list
+fn joined_values(headers: &HeaderMap, name: impl header::AsHeaderName) -> Option<String> {
+    let values: Vec<&str> = headers
+        .get_all(name)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .collect();
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+/// RFC 7239 `Forwarded` element for one hop; IPv6 nodes and hosts with a port are quoted
+fn forwarded_element(client: IpAddr, host: Option<&str>) -> String {
+    let mut element = match client {
+        IpAddr::V4(ip) => format!("for={}", ip),
+        IpAddr::V6(ip) => format!("for=\"[{}]\"", ip),
+    };
+    element.push_str(";proto=http");
+    if let Some(host) = host {
+        if host.bytes().all(|b| b.is_ascii_alphanumeric() || b"-._".contains(&b)) {
+            element.push_str(&format!(";host={}", host));
+        } else {
+            element.push_str(&format!(";host=\"{}\"", host.replace(['\\', '"'], "")));
+        }
+    }
+    element
 }
 
-/// Wolf API reverse proxy client over Unix Domain Socket
+/// Reject request headers over `max_total` bytes together, or with a name or value
+/// over `max_field` bytes, before they cost a connection
+fn check_header_sizes(
+    headers: &HeaderMap,
+    max_total: usize,
+    max_field: usize,
+) -> Result<(), WolfProxyError> {
+    let mut total = 0;
+    for (name, value) in headers {
+        let longest = name.as_str().len().max(value.len());
+        if longest > max_field {
+            return Err(WolfProxyError::HeadersTooLarge(format!(
+                "header {} is {} bytes, limit is {}",
+                name, longest, max_field
+            )));
+        }
+        // `name: value\r\n`
+        total += name.as_str().len() + value.len() + 4;
+    }
+    if total > max_total {
+        return Err(WolfProxyError::HeadersTooLarge(format!(
+            "{} bytes of headers, limit is {}",
+            total, max_total
+        )));
+    }
+    Ok(())
+}
+
+/// Reject a path and query longer than `max` bytes before it reaches Wolf or the logs
+fn check_uri_length(uri: &http::Uri, max: usize) -> Result<(), WolfProxyError> {
+    let len = uri.path_and_query().map_or(uri.path().len(), |p| p.as_str().len());
+    if len > max {
+        return Err(WolfProxyError::UriTooLong { len, limit: max });
+    }
+    Ok(())
+}
+
+/// Whether a client's `TE` header says it accepts trailers
+fn accepts_trailers(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::TE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|token| token.trim().eq_ignore_ascii_case("trailers"))
+}
+
+/// Wolf API reverse proxy client over a Unix socket, TCP, or TLS
 pub struct WolfProxyClient {
     config: WolfProxyConfig,
+    connector: WolfConnector,
+    http: Client<WolfConnector, ProxyBody>,
+    /// HTTP/1.1 client for WebSocket upgrades; the same pool as `http` unless that speaks h2
+    upgrades: Client<WolfConnector, ProxyBody>,
+    stats: Arc<ConnStats>,
+    breaker: CircuitBreaker,
+    limit: ConcurrencyLimit,
+    /// Shared by connection retries and 5xx retries alike
+    budget: Arc<RetryBudget>,
+    cache: ResponseCache,
+    flights: SingleFlight<FlightKey, Flight>,
 }
 
 impl WolfProxyClient {
     pub fn new(config: WolfProxyConfig) -> Self {
-        Self { config }
-    }
+        let stats = Arc::new(ConnStats::default());
+        let budget = Arc::new(RetryBudget::new(config.retry_budget, config.retry_budget_refill_per_sec));
+        let http2 = config.http_version == UpstreamHttpVersion::Http2;
+        let connector_offering = |offer_h2: bool| WolfConnector {
+            endpoint: Arc::new(config.endpoint.clone()),
+            tls: config
+                .endpoint
+                .tls_connector(&config.tls, offer_h2)
+                .map_err(|e| Arc::from(format!("TLS setup failed: {:#}", e))),
+            connect_timeout: config.connect_timeout,
+            retry_attempts: config.retry_attempts,
+            backoff: config.retry_backoff(),
+            budget: budget.clone(),
+            stats: stats.clone(),
+        };
+        let client = |connector: WolfConnector, http2_only: bool| {
+            Client::builder(TokioExecutor::new())
+                .pool_max_idle_per_host(config.pool_max_idle)
+                .pool_idle_timeout(config.pool_idle_timeout)
+                .pool_timer(TokioTimer::new())
+                .http2_only(http2_only)
+                .build(connector)
+        };
 
-    /// Check if Wolf socket is available and connectable
-    pub async fn check_readiness(&self) -> Result<()> {
-        let path = Path::new(&self.config.socket_path);
-        if !path.exists() {
-            return Err(anyhow!("wolf.sock not found at {}", self.config.socket_path));
+        let connector = connector_offering(http2);
+        // TLS connections switch to h2 only when ALPN agrees; other transports have no negotiation
+        let http = client(connector.clone(), http2 && !config.endpoint.is_tls());
+        let upgrades = if http2 {
+            client(connector_offering(false), false)
+        } else {
+            http.clone()
+        };
+
+        let breaker = CircuitBreaker::new(config.breaker_failure_threshold, config.breaker_cooldown);
+        let limit = ConcurrencyLimit::new(
+            config.max_concurrency,
+            config.concurrency_queue,
+            config.concurrency_timeout,
+        );
+
+        let cache = ResponseCache::new(config.cache_paths.clone(), config.cache_ttl);
+
+        Self {
+            config,
+            connector,
+            http,
+            upgrades,
+            stats,
+            breaker,
+            limit,
+            budget,
+            cache,
+            flights: SingleFlight::default(),
         }
+    }
 
-        // Try to connect
-        tokio::time::timeout(
-            self.config.connect_timeout,
-            UnixStream::connect(&self.config.socket_path),
-        )
-        .await
-        .context("connection timeout")?
-        .context("failed to connect to wolf.sock")?;
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
 
-        Ok(())
+    pub fn concurrency_limit(&self) -> &ConcurrencyLimit {
+        &self.limit
+    }
+
+    pub fn retry_budget(&self) -> &RetryBudget {
+        &self.budget
+    }
+
+    pub fn response_cache(&self) -> &ResponseCache {
+        &self.cache
+    }
+
+    /// Coalesced requests currently waiting on Wolf
+    pub fn flights_in_progress(&self) -> usize {
+        self.flights.len()
+    }
+
+    pub fn endpoint(&self) -> &WolfEndpoint {
+        &self.config.endpoint
+    }
+
+    /// Whether ordinary requests may go out over HTTP/2
+    fn speaks_h2(&self) -> bool {
+        self.config.http_version == UpstreamHttpVersion::Http2
+    }
+
+    /// Number of open Wolf connections (idle in the pool or in use)
+    pub fn pool_size(&self) -> usize {
+        self.stats.live()
+    }
+
+    /// Total connections (and therefore HTTP handshakes) opened so far
+    pub fn connections_opened(&self) -> u64 {
+        self.stats.opened()
+    }
+
+    /// Send a request over a pooled connection, translating client errors.
+    /// Fails fast without connecting while the circuit breaker is open.
+    async fn send(&self, req: Request<ProxyBody>) -> Result<Response<Incoming>, WolfProxyError> {
+        self.send_via(&self.http, req).await
     }
 
-    /// Proxy an HTTP request to Wolf over the Unix socket
-    pub async fn proxy_request(
+    async fn send_via(
         &self,
-        method: Method,
-        uri: http::Uri,
-        headers: HeaderMap,
-        body: Bytes,
-        client_ip: Option<String>,
-    ) -> Result<Response<Incoming>> {
-        let start = std::time::Instant::now();
+        http: &Client<WolfConnector, ProxyBody>,
+        req: Request<ProxyBody>,
+    ) -> Result<Response<Incoming>, WolfProxyError> {
+        let attempt = self.breaker.try_acquire().map_err(WolfProxyError::CircuitOpen)?;
 
-        // Retry connection with exponential backoff
-        let stream = {
-            let mut attempt = 0;
-            loop {
-                attempt += 1;
+        let max_body = self.config.max_body_bytes;
+        let Ok(result) = tokio::time::timeout(self.config.read_timeout, http.request(req)).await else {
+            attempt.failed();
+            return Err(WolfProxyError::ReadTimeout(self.config.read_timeout));
+        };
 
-                match tokio::time::timeout(
-                    self.config.connect_timeout,
-                    UnixStream::connect(&self.config.socket_path),
-                )
-                .await
-                {
-                    Ok(Ok(stream)) => break stream,
-                    Ok(Err(e)) => {
-                        if attempt >= self.config.retry_attempts {
-                            return Err(anyhow::Error::from(e).context("failed to connect to wolf.sock after retries"));
-                        }
-                        warn!(
-                            attempt = attempt,
-                            max_attempts = self.config.retry_attempts,
-                            "Wolf connection failed, retrying..."
-                        );
-                        tokio::time::sleep(self.config.retry_delay * attempt).await;
-                    }
-                    Err(_) => {
-                        if attempt >= self.config.retry_attempts {
-                            return Err(anyhow!("connection timeout after {} attempts", attempt));
-                        }
-                        warn!(
-                            attempt = attempt,
-                            max_attempts = self.config.retry_attempts,
-                            "Wolf connection timeout, retrying..."
-                        );
-                        tokio::time::sleep(self.config.retry_delay * attempt).await;
-                    }
+        match result {
+            Ok(response) => {
+                attempt.succeeded();
+                Ok(response)
+            }
+            Err(e) if e.is_connect() => {
+                attempt.failed();
+                // Surface the connector's own error (connect failure, timeout, or handshake)
+                Err(e
+                    .source()
+                    .and_then(|cause| cause.downcast_ref::<WolfProxyError>())
+                    .cloned()
+                    .unwrap_or_else(|| WolfProxyError::ConnectFailed {
+                        target: self.connector.target(),
+                        reason: error_chain(&e),
+                    }))
+            }
+            Err(e) => {
+                // Wolf was reachable, so the breaker is not to blame
+                attempt.succeeded();
+                if is_body_limit_error(&e) {
+                    Err(WolfProxyError::BodyTooLarge(max_body))
+                } else if let Some(body_error) = find_cause::<ClientBodyError>(&e) {
+                    Err(WolfProxyError::RequestBody(error_chain(body_error)))
+                } else {
+                    Err(WolfProxyError::Upstream(error_chain(&e)))
                 }
             }
-        };
+        }
+    }
+
+    /// Check if Wolf is available and connectable
+    pub async fn check_readiness(&self) -> Result<()> {
+        if let WolfEndpoint::Unix(socket_path) = &self.config.endpoint {
+            if !Path::new(socket_path).exists() {
+                return Err(anyhow!("wolf.sock not found at {}", socket_path));
+            }
+        }
+
+        Ok(self.connector.probe().await?)
+    }
 
-        let io = TokioIo::new(stream);
+    /// GET `path` from Wolf and require a 2xx answer within the read timeout
+    pub async fn check_endpoint(&self, path: &str) -> Result<()> {
+        let uri = path
+            .parse::<http::Uri>()
+            .map_err(|e| anyhow!("invalid readiness path {:?}: {}", path, e))?;
+        // Health checks skip the concurrency limit, so a busy Wolf isn't reported as down, and
+        // the cache and coalescing, so a stale or borrowed answer can't report a down Wolf as up
+        let response = self
+            .request(Method::GET, uri, HeaderMap::new(), Empty::<Bytes>::new(), None, Route::HEALTH_CHECK)
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Wolf returned {} for GET {}", response.status(), path));
+        }
+        Ok(())
+    }
 
-        // Build the request
+    /// Start an upstream request carrying the client's headers minus hop-by-hop ones.
+    /// With `http2` the client's `Host` is left out: h2 carries the authority in the
+    /// `:authority` pseudo-header, built from the Wolf endpoint like the rest of the URI,
+    /// and the original host still travels as `X-Forwarded-Host`.
+    fn forward_request_builder(
+        &self,
+        method: &Method,
+        uri: &http::Uri,
+        headers: &HeaderMap,
+        client_ip: Option<IpAddr>,
+        http2: bool,
+    ) -> http::request::Builder {
+        // The pooled client needs an absolute URI; the connector decides where it actually goes.
+        // Without a client Host, hyper sends this authority, so an override goes here too.
+        let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+        let host_override = self.config.host_override.as_ref();
+        let authority = host_override.map_or_else(|| self.config.endpoint.authority(), |host| host.to_string());
         let mut req_builder = Request::builder()
             .method(method.clone())
-            .uri(&uri);
+            .uri(format!("http://{}{}", authority, path));
 
         // Copy headers, filtering hop-by-hop headers
-        let hop_headers = hop_by_hop_headers();
+        let hop_headers = hop_by_hop_headers(headers, &self.config.strip_headers);
+        let request_id = request_id::current();
         for (name, value) in headers.iter() {
-            if !hop_headers.contains(name) {
-                req_builder = req_builder.header(name, value);
+            if hop_headers.contains(name)
+                || is_forwarding_header(name)
+                || name == header::VIA
+                || ((http2 || host_override.is_some()) && name == header::HOST)
+                || (request_id.is_some() && name == REQUEST_ID_HEADER)
+            {
+                continue;
+            }
+            req_builder = req_builder.header(name, value);
+        }
+
+        // TE is hop-by-hop, but "trailers" is the client's end-to-end consent to receive them
+        if accepts_trailers(headers) {
+            req_builder = req_builder.header(header::TE, "trailers");
+        }
+
+        // Let Wolf-side logs be correlated with ours
+        if let Some(id) = request_id {
+            req_builder = req_builder.header(REQUEST_ID_HEADER, &*id);
+        }
+
+        // Mark the request as proxied, so Wolf can tell it from direct access
+        let via = match joined_values(headers, header::VIA) {
+            Some(chain) => format!("{}, {}", chain, VIA),
+            None => VIA.to_owned(),
+        };
+        req_builder = req_builder.header(header::VIA, via);
+        if !headers.contains_key(header::USER_AGENT) {
+            if let Some(user_agent) = &self.config.user_agent {
+                req_builder = req_builder.header(header::USER_AGENT, user_agent);
             }
         }
 
-        // Add X-Forwarded-* headers
+        // Extend the forwarding chain only when the peer is a proxy we trust to have built it;
+        // otherwise a client could claim any origin
         if let Some(ip) = client_ip {
-            req_builder = req_builder.header("x-forwarded-for", ip);
+            let peer = ip.to_canonical();
+            let trusted = self.config.trusted_proxies.iter().any(|net| net.contains(&peer));
+            let inbound = |name| trusted.then(|| joined_values(headers, name)).flatten();
+            let host = headers.get(header::HOST).and_then(|h| h.to_str().ok());
+
+            let hop = ip.to_string();
+            let xff = match inbound(HeaderName::from_static("x-forwarded-for")) {
+                Some(chain) => format!("{}, {}", chain, hop),
+                None => hop,
+            };
+            let element = forwarded_element(ip, host);
+            let forwarded = match inbound(header::FORWARDED) {
+                Some(chain) => format!("{}, {}", chain, element),
+                None => element,
+            };
+            req_builder = req_builder
+                .header("x-forwarded-for", xff)
+                .header(header::FORWARDED, forwarded);
         }
         req_builder = req_builder.header("x-forwarded-proto", "http");
         if let Some(host) = headers.get(header::HOST) {
             req_builder = req_builder.header("x-forwarded-host", host);
         }
 
-        let req = req_builder.body(Full::new(body))?;
+        req_builder
+    }
 
-        // Send request and get response
-        let (mut sender, conn) = hyper::client::conn::http1::handshake(io).await?;
+    /// Proxy an HTTP request to Wolf over the Unix socket.
+    /// The body is streamed upstream as it is read and capped at `max_body_bytes`.
+    /// Waits for a slot when `max_concurrency` requests are already in flight.
+    /// GETs on `cache_paths` are answered from the cache while a fresh copy is held.
+    /// With `coalesce`, a GET or HEAD identical to one already waiting on Wolf gets a copy
+    /// of that one's response (or error) instead of a call of its own.
+    pub async fn proxy_request<B>(
+        &self,
+        method: Method,
+        uri: http::Uri,
+        headers: HeaderMap,
+        body: B,
+        client_ip: Option<IpAddr>,
+    ) -> Result<Response<WolfBody>, WolfProxyError>
+    where
+        B: Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        self.request(method, uri, headers, body, client_ip, Route::PROXIED).await
+    }
 
-        // Spawn connection handler
-        tokio::spawn(async move {
-            if let Err(e) = conn.await {
-                warn!("Wolf proxy connection error: {}", e);
-            }
-        });
+    async fn request<B>(
+        &self,
+        method: Method,
+        uri: http::Uri,
+        headers: HeaderMap,
+        body: B,
+        client_ip: Option<IpAddr>,
+        route: Route,
+    ) -> Result<Response<WolfBody>, WolfProxyError>
+    where
+        B: Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        let start = std::time::Instant::now();
+        // Before the cache and coalescing, so their answers are held to the same limits
+        if let Err(e) = check_uri_length(&uri, self.config.max_uri_bytes)
+            .and_then(|()| check_header_sizes(&headers, self.config.max_headers_bytes, self.config.max_header_bytes))
+        {
+            proxy_metrics::record(&method, None, start.elapsed());
+            return Err(e);
+        }
+        let cache_key = (route.shared && self.cache.covers(&method, &uri)).then(|| CacheKey::new(&uri, &headers));
+        if let Some(hit) = cache_key.as_ref().and_then(|key| self.cache.get(key)) {
+            proxy_metrics::record(&method, Some(hit.status), start.elapsed());
+            debug!(method = %method, uri = %uri, status = %hit.status, "Wolf proxy request served from cache");
+            return Ok(replayed(hit));
+        }
 
-        let response = tokio::time::timeout(
-            self.config.read_timeout,
-            sender.send_request(req),
-        )
-        .await
-        .context("read timeout")??;
+        let mut leader: Option<Leader<FlightKey, Flight>> = None;
+        if let Some(key) = (route.shared && self.config.coalesce).then(|| flight_key(&method, &uri, &headers, &body)).flatten() {
+            match self.flights.join(key) {
+                Joined::Follower(follower) => {
+                    // A leader that gave up, or got an event stream, leaves us to go ourselves
+                    if let Some(flight) = follower.wait().await {
+                        let result = flight.map(replayed);
+                        proxy_metrics::record(&method, result.as_ref().ok().map(|r| r.status()), start.elapsed());
+                        debug!(method = %method, uri = %uri, "Wolf proxy request coalesced with an identical one");
+                        return result;
+                    }
+                }
+                Joined::Leader(flight) => leader = Some(flight),
+            }
+        }
 
-        let status = response.status();
+        let fetched: Result<Fetched, WolfProxyError> = async {
+            let _slot = if route.limited { self.limit.acquire().await? } else { None };
+            let response = self.forward(&method, &uri, headers, body, client_ip).await?;
+            // Only buffer for followers if some turned up; otherwise the response streams as usual
+            let share = leader.as_ref().is_some_and(Leader::has_followers);
+            let keep = cache_key.is_some() && response.status().is_success();
+            if (share || keep) && !is_event_stream(response.headers()) {
+                let fetched = self.buffer(response).await?;
+                if let (Fetched::Buffered(buffered), Some(key)) = (&fetched, cache_key) {
+                    self.cache.put(key, buffered.clone());
+                }
+                Ok(fetched)
+            } else {
+                Ok(Fetched::Streamed(streamed(response)))
+            }
+        }
+        .await;
+        if let Some(leader) = leader {
+            match &fetched {
+                Ok(Fetched::Buffered(response)) => leader.finish(Ok(response.clone())),
+                // An event stream or a body past `max_buffered_bytes` isn't shared: dropping
+                // the flight unfinished sends the followers to Wolf on their own
+                Ok(Fetched::Streamed(_)) => drop(leader),
+                Err(e) => leader.finish(Err(e.clone())),
+            }
+        }
+        let result = fetched.map(|fetched| match fetched {
+            Fetched::Buffered(response) => replayed(response),
+            Fetched::Streamed(response) => response,
+        });
         let elapsed = start.elapsed();
+        proxy_metrics::record(&method, result.as_ref().ok().map(|r| r.status()), elapsed);
 
-        info!(
-            method = %method,
-            uri = %uri,
-            status = %status,
-            duration_ms = elapsed.as_millis(),
-            "Wolf proxy request completed"
-        );
+        if let Ok(response) = &result {
+            info!(
+                method = %method,
+                uri = %uri,
+                status = %response.status(),
+                duration_ms = elapsed.as_millis(),
+                "Wolf proxy request completed"
+            );
+        }
 
-        Ok(response)
+        result
     }
 
-    /// Convert hyper Response to axum Response
-    pub async fn response_to_axum(response: Response<Incoming>) -> Result<Response<axum::body::Body>> {
-        let (parts, body) = response.into_parts();
+    /// Read a whole response within `read_timeout`, to cache or share it. A body past
+    /// `max_buffered_bytes` streams on instead: at once when `Content-Length` gives it away,
+    /// otherwise once it outgrows the limit, starting with what was already read.
+    async fn buffer(&self, response: Response<Incoming>) -> Result<Fetched, WolfProxyError> {
+        let limit = self.config.max_buffered_bytes;
+        let declared_len = response
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        if declared_len.is_some_and(|len| len > limit as u64) {
+            return Ok(Fetched::Streamed(streamed(response)));
+        }
 
-        // Filter hop-by-hop headers from response
-        let hop_headers = hop_by_hop_headers();
-        let mut filtered_headers = HeaderMap::new();
-        for (name, value) in parts.headers.iter() {
-            if !hop_headers.contains(name) {
-                filtered_headers.insert(name.clone(), value.clone());
+        let (parts, mut body) = response.into_parts();
+        let mut read = BytesMut::new();
+        let overflowed = tokio::time::timeout(self.config.read_timeout, async {
+            while let Some(frame) = body.frame().await {
+                let frame = frame.map_err(|e| WolfProxyError::Upstream(error_chain(&e)))?;
+                if let Ok(data) = frame.into_data() {
+                    read.extend_from_slice(&data);
+                    if read.len() > limit {
+                        return Ok(true);
+                    }
+                }
             }
+            Ok::<_, WolfProxyError>(false)
+        })
+        .await
+        .map_err(|_| WolfProxyError::ReadTimeout(self.config.read_timeout))??;
+
+        if overflowed {
+            let head = futures_util::stream::once(std::future::ready(Ok(Frame::data(read.freeze()))));
+            let body = StreamBody::new(head.chain(BodyStream::new(body))).boxed_unsync();
+            return Ok(Fetched::Streamed(Response::from_parts(parts, body)));
         }
+        Ok(Fetched::Buffered(CachedResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body: read.freeze(),
+        }))
+    }
 
-        // Convert body
-        let bytes = body.collect().await?.to_bytes();
+    async fn forward<B>(
+        &self,
+        method: &Method,
+        uri: &http::Uri,
+        headers: HeaderMap,
+        body: B,
+        client_ip: Option<IpAddr>,
+    ) -> Result<Response<Incoming>, WolfProxyError>
+    where
+        B: Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        let max_body = self.config.max_body_bytes;
 
-        let mut response = Response::new(axum::body::Body::from(bytes));
-        *response.status_mut() = parts.status;
-        *response.headers_mut() = filtered_headers;
-        *response.version_mut() = parts.version;
+        // Reject declared oversized bodies before paying for a connection
+        let declared_len = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        if declared_len.is_some_and(|len| len > max_body as u64) {
+            return Err(WolfProxyError::BodyTooLarge(max_body));
+        }
 
-        Ok(response)
-    }
-}
+        let body = Limited::new(body, max_body);
+        let retry_5xx = self.config.retry_on_5xx && is_idempotent(method);
 
-/// Build error response with JSON payload
-pub fn error_response(status: StatusCode, error: &str, detail: &str) -> Response<axum::body::Body> {
-    let body = serde_json::json!({
-        "error": error,
-        "detail": detail,
-    });
+        let response = if retry_5xx {
+            // Idempotent bodies are normally empty; buffer them so they can be replayed
+            let body = body
+                .collect()
+                .await
+                .map_err(|e| {
+                    if is_body_limit_error(&*e) {
+                        WolfProxyError::BodyTooLarge(max_body)
+                    } else {
+                        WolfProxyError::RequestBody(error_chain(&*e))
+                    }
+                })?
+                .to_bytes();
 
-    Response::builder()
+            let mut attempt = 0;
+            loop {
+                attempt += 1;
+                let req = self
+                    .forward_request_builder(method, uri, &headers, client_ip, self.speaks_h2())
+                    .body(Full::new(body.clone()).map_err(|never| match never {}).boxed_unsync())?;
+                let response = self.send(req).await?;
+
+                let status = response.status();
+                if !is_retryable_status(status) || attempt >= self.config.retry_attempts {
+                    break response;
+                }
+                if !self.budget.try_spend() {
+                    warn!(status = %status, "Wolf returned a transient error; retry budget exhausted, not retrying");
+                    break response;
+                }
+                warn!(
+                    attempt = attempt,
+                    max_attempts = self.config.retry_attempts,
+                    status = %status,
+                    "Wolf returned a transient error, retrying..."
+                );
+                tokio::time::sleep(self.connector.backoff.delay(attempt)).await;
+            }
+        } else {
+            let req = self
+                .forward_request_builder(method, uri, &headers, client_ip, self.speaks_h2())
+                .body(body.map_err(tag_body_error).boxed_unsync())?;
+            self.send(req).await?
+        };
+
+        Ok(response)
+    }
+
+    /// Forward a WebSocket handshake to Wolf.
+    /// On `101 Switching Protocols` the upstream connection is handed back for [`spawn_tunnel`].
+    pub async fn proxy_upgrade(
+        &self,
+        uri: http::Uri,
+        headers: HeaderMap,
+        client_ip: Option<IpAddr>,
+    ) -> Result<UpgradeOutcome, WolfProxyError> {
+        check_uri_length(&uri, self.config.max_uri_bytes)?;
+        check_header_sizes(&headers, self.config.max_headers_bytes, self.config.max_header_bytes)?;
+        // Hop-by-hop filtering drops Connection/Upgrade, but the handshake needs them
+        let upgrade = headers
+            .get(header::UPGRADE)
+            .cloned()
+            .unwrap_or_else(|| HeaderValue::from_static("websocket"));
+        let req = self
+            .forward_request_builder(&Method::GET, &uri, &headers, client_ip, false)
+            .header(header::CONNECTION, "upgrade")
+            .header(header::UPGRADE, upgrade)
+            .body(Empty::<Bytes>::new().map_err(|never| match never {}).boxed_unsync())?;
+
+        let start = std::time::Instant::now();
+        // The slot covers the handshake only; an established tunnel doesn't count against it
+        let result = async {
+            let _slot = self.limit.acquire().await?;
+            self.send_via(&self.upgrades, req).await
+        }
+        .await;
+        proxy_metrics::record(
+            &Method::GET,
+            result.as_ref().ok().map(|r| r.status()),
+            start.elapsed(),
+        );
+        let mut response = result?;
+
+        if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+            return Ok(UpgradeOutcome::Rejected(streamed(response)));
+        }
+
+        // Echo Wolf's handshake (Sec-WebSocket-Accept/-Protocol, Upgrade, Connection) to the client
+        let hop_headers = hop_by_hop_headers(response.headers(), &self.config.strip_headers);
+        let mut client_response = Response::new(axum::body::Body::empty());
+        *client_response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+        for (name, value) in response.headers().iter() {
+            if name == header::CONNECTION || name == header::UPGRADE || !hop_headers.contains(name) {
+                client_response.headers_mut().append(name.clone(), value.clone());
+            }
+        }
+
+        info!(uri = %uri, "Wolf WebSocket upgrade accepted");
+
+        Ok(UpgradeOutcome::Switched {
+            response: client_response,
+            upstream: hyper::upgrade::on(&mut response),
+        })
+    }
+
+    /// Convert hyper Response to axum Response, streaming the body through
+    pub async fn response_to_axum(
+        &self,
+        response: Response<WolfBody>,
+    ) -> Result<Response<axum::body::Body>> {
+        if self.config.normalize_errors && should_normalize(&response) {
+            return self.normalize_error(response).await;
+        }
+        let (parts, body) = response.into_parts();
+        let strip = self.config.strip_headers.clone();
+
+        // Filter hop-by-hop headers from response
+        let mut filtered_headers = without_hop_by_hop(&parts.headers, &strip);
+
+        // hyper only sends trailers declared in a Trailer header, so keep Wolf's declaration
+        for value in parts.headers.get_all(header::TRAILER) {
+            filtered_headers.append(header::TRAILER, value.clone());
+        }
+
+        // 204, 304 and 1xx never have a body, so there is nothing to type
+        let has_body = !(parts.status.is_informational()
+            || parts.status == StatusCode::NO_CONTENT
+            || parts.status == StatusCode::NOT_MODIFIED);
+        if let Some(content_type) = &self.config.default_content_type {
+            if has_body && !filtered_headers.contains_key(header::CONTENT_TYPE) {
+                filtered_headers.insert(header::CONTENT_TYPE, content_type.clone());
+            }
+        }
+
+        // Stream the body as it arrives (SSE, large downloads), filtering any trailers
+        // (e.g. grpc-status) Wolf sends after it
+        let body = body.map_frame(move |frame| match frame.into_trailers() {
+            Ok(trailers) => Frame::trailers(without_hop_by_hop(&trailers, &strip)),
+            Err(frame) => frame,
+        });
+
+        let mut response = Response::new(axum::body::Body::new(body));
+        *response.status_mut() = parts.status;
+        *response.headers_mut() = filtered_headers;
+        // Our listener speaks HTTP/1.1; an h2 upstream says nothing about that connection
+        if parts.version != http::Version::HTTP_2 {
+            *response.version_mut() = parts.version;
+        }
+
+        Ok(response)
+    }
+}
+
+/// Whether a Wolf response is an error whose body can be rewritten: 4xx/5xx, not compressed
+/// (we'd have to decode it), and not declared larger than `MAX_NORMALIZED_ERROR_BYTES`
+fn should_normalize(response: &Response<WolfBody>) -> bool {
+    let status = response.status();
+    let declared_len = response
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    (status.is_client_error() || status.is_server_error())
+        && !response.headers().contains_key(header::CONTENT_ENCODING)
+        && declared_len.is_none_or(|len| len <= MAX_NORMALIZED_ERROR_BYTES)
+}
+
+/// `ErrorBody` fields for a Wolf error: Wolf's own message when its JSON has one, else the
+/// status; the original body goes under `upstream`, parsed when it is JSON
+fn upstream_error_body(status: StatusCode, body: &[u8]) -> (ErrorBody, serde_json::Value) {
+    let upstream = serde_json::from_slice::<serde_json::Value>(body)
+        .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(body).into_owned()));
+    let message = ["detail", "message", "error"]
+        .iter()
+        .find_map(|field| upstream.get(field).and_then(|v| v.as_str()))
+        .map(str::to_owned);
+    let detail = match message {
+        Some(message) => format!("Wolf returned {}: {}", status, message),
+        None => format!("Wolf returned {}", status),
+    };
+    let error = ErrorBody::new("WolfError", &detail);
+    let mut envelope = serde_json::json!(error);
+    envelope["upstream"] = upstream;
+    (error, envelope)
+}
+
+impl WolfProxyClient {
+    /// Answer a Wolf error in our envelope, keeping its status and end-to-end headers
+    async fn normalize_error(&self, response: Response<WolfBody>) -> Result<Response<axum::body::Body>> {
+        let (parts, body) = response.into_parts();
+        let body = tokio::time::timeout(
+            self.config.read_timeout,
+            Limited::new(body, MAX_NORMALIZED_ERROR_BYTES).collect(),
+        )
+        .await
+        .map_err(|_| anyhow!("timed out reading Wolf's error body"))?
+        .map_err(|e| anyhow!("failed to read Wolf's error body: {}", e))?
+        .to_bytes();
+        let (error, envelope) = upstream_error_body(parts.status, &body);
+
+        let mut headers = without_hop_by_hop(&parts.headers, &self.config.strip_headers);
+        // The body is ours now
+        headers.remove(header::CONTENT_LENGTH);
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let mut response = Response::new(axum::body::Body::from(envelope.to_string()));
+        *response.status_mut() = parts.status;
+        *response.headers_mut() = headers;
+        response.extensions_mut().insert(error);
+        Ok(response)
+    }
+}
+
+/// Result of forwarding a WebSocket handshake to Wolf
+pub enum UpgradeOutcome {
+    /// Wolf switched protocols; send `response` to the client and tunnel the connections
+    Switched {
+        response: Response<axum::body::Body>,
+        upstream: OnUpgrade,
+    },
+    /// Wolf refused the upgrade; forward its response as a normal reply
+    Rejected(Response<WolfBody>),
+}
+
+/// Pump bytes between an upgraded client connection and Wolf until either side closes.
+/// Frames pass through untouched, so close handshakes and subprotocols are end-to-end.
+pub fn spawn_tunnel(client: OnUpgrade, upstream: OnUpgrade) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let (client, upstream) = match tokio::try_join!(client, upstream) {
+            Ok(pair) => pair,
+            Err(e) => {
+                warn!("WebSocket upgrade failed: {}", e);
+                return;
+            }
+        };
+
+        let (mut client_rx, mut client_tx) = tokio::io::split(TokioIo::new(client));
+        let (mut wolf_rx, mut wolf_tx) = tokio::io::split(TokioIo::new(upstream));
+
+        let client_to_wolf = async {
+            let copied = tokio::io::copy(&mut client_rx, &mut wolf_tx).await;
+            let _ = wolf_tx.shutdown().await;
+            copied
+        };
+        let wolf_to_client = async {
+            let copied = tokio::io::copy(&mut wolf_rx, &mut client_tx).await;
+            let _ = client_tx.shutdown().await;
+            copied
+        };
+
+        // Whichever side finishes first ends the tunnel; dropping the halves closes the other
+        tokio::select! {
+            result = client_to_wolf => match result {
+                Ok(bytes) => info!(bytes, "WebSocket client closed"),
+                Err(e) => warn!("WebSocket client disconnected: {}", e),
+            },
+            result = wolf_to_client => match result {
+                Ok(bytes) => info!(bytes, "WebSocket closed by Wolf"),
+                Err(e) => warn!("WebSocket Wolf side disconnected: {}", e),
+            },
+        }
+    }
+    .in_current_span())
+}
+
+/// Shape of every JSON error response. Some endpoints add fields (e.g. `/wolfapi/_ready`'s `check`).
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct ErrorBody {
+    /// Machine-readable code, e.g. `UpstreamTimeout`
+    pub error: String,
+    /// Human-readable explanation
+    pub detail: String,
+    /// ID of the failed request, also sent as `X-Request-Id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ErrorBody {
+    /// Error for the current request, tagged with its ID when there is one
+    pub fn new(error: &str, detail: &str) -> Self {
+        Self {
+            error: error.to_string(),
+            detail: detail.to_string(),
+            request_id: request_id::current().map(|id| id.to_string()),
+        }
+    }
+
+    /// `error: detail` line for clients that ask for `text/plain`
+    pub fn to_text(&self) -> String {
+        format!("{}: {}\n", self.error, self.detail)
+    }
+}
+
+/// JSON error payload; carries the current request ID when there is one
+pub fn error_body(error: &str, detail: &str) -> serde_json::Value {
+    serde_json::json!(ErrorBody::new(error, detail))
+}
+
+/// Build error response with JSON payload. The `ErrorBody` also rides along as a response
+/// extension so outer layers can re-render it (e.g. as `text/plain`) without parsing JSON.
+pub fn error_response(status: StatusCode, error: &str, detail: &str) -> Response<axum::body::Body> {
+    let body = ErrorBody::new(error, detail);
+    Response::builder()
         .status(status)
         .header(header::CONTENT_TYPE, "application/json")
-        .body(axum::body::Body::from(body.to_string()))
+        .extension(body.clone())
+        .body(axum::body::Body::from(serde_json::json!(body).to_string()))
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{
+        full, spawn_h2_tcp_server, spawn_h2_unix_server, spawn_mtls_tcp_server, spawn_tcp_server, spawn_unix_server,
+    };
+    use futures_util::{stream, StreamExt};
+    use http_body_util::StreamBody;
+    use hyper::body::Frame;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const CHUNK: usize = 64 * 1024;
+
+    fn client(socket_path: String) -> WolfProxyClient {
+        WolfProxyClient::new(WolfProxyConfig::new(socket_path, 1000, 5000).with_retry(1, 10))
+    }
+
+    #[tokio::test]
+    async fn test_large_body_is_streamed() -> Result<()> {
+        let consumed = Arc::new(AtomicUsize::new(0));
+        let produced = Arc::new(AtomicUsize::new(0));
+        let peak_in_flight = Arc::new(AtomicUsize::new(0));
+
+        // Upstream counts bytes as it reads them
+        let server_consumed = consumed.clone();
+        let (_dir, path) = spawn_unix_server(move |req| {
+            let consumed = server_consumed.clone();
+            async move {
+                let mut body = req.into_body();
+                while let Some(frame) = body.frame().await {
+                    if let Ok(data) = frame.unwrap().into_data() {
+                        consumed.fetch_add(data.len(), Ordering::SeqCst);
+                    }
+                }
+                Response::new(full(consumed.load(Ordering::SeqCst).to_string()))
+            }
+        });
+
+        // 16 MiB body generated lazily, tracking how far ahead of upstream it gets
+        let total_chunks = 256;
+        let (p, c, peak) = (produced.clone(), consumed.clone(), peak_in_flight.clone());
+        let frames = stream::iter(0..total_chunks).map(move |_| {
+            let sent = p.fetch_add(CHUNK, Ordering::SeqCst) + CHUNK;
+            let in_flight = sent.saturating_sub(c.load(Ordering::SeqCst));
+            peak.fetch_max(in_flight, Ordering::SeqCst);
+            Ok::<_, Infallible>(Frame::data(Bytes::from(vec![0u8; CHUNK])))
+        });
+
+        let response = client(path)
+            .proxy_request(
+                Method::POST,
+                "/upload".parse()?,
+                HeaderMap::new(),
+                StreamBody::new(frames),
+                None,
+            )
+            .await?;
+        let body = response.into_body().collect().await?.to_bytes();
+
+        assert_eq!(body, (total_chunks * CHUNK).to_string());
+        let peak = peak_in_flight.load(Ordering::SeqCst);
+        assert!(peak < 4 * 1024 * 1024, "peak in-flight bytes too high: {peak}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_body_over_limit_rejected() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            let _ = req.into_body().collect().await;
+            Response::new(full("ok"))
+        });
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_retry(1, 10)
+                .with_max_body_bytes(1024),
+        );
+
+        // Declared length over the limit fails before connecting
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_LENGTH, "4096".parse()?);
+        let err = client
+            .proxy_request(
+                Method::POST,
+                "/upload".parse()?,
+                headers,
+                Full::new(Bytes::from(vec![0u8; 4096])),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeds limit"), "{err}");
+        assert_eq!(err, WolfProxyError::BodyTooLarge(1024));
+
+        // Undeclared length is cut off while streaming
+        let frames = stream::iter((0..4).map(|_| {
+            Ok::<_, Infallible>(Frame::data(Bytes::from(vec![0u8; 1024])))
+        }));
+        let err = client
+            .proxy_request(
+                Method::POST,
+                "/upload".parse()?,
+                HeaderMap::new(),
+                StreamBody::new(frames),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeds limit"), "{err}");
+        assert_eq!(err, WolfProxyError::BodyTooLarge(1024));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_malformed_body_is_request_body_error() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            let _ = req.into_body().collect().await;
+            Response::new(full("ok"))
+        });
+        let client = client(path);
+        let broken = || {
+            StreamBody::new(stream::iter([
+                Ok(Frame::data(Bytes::from_static(b"{\"name\":"))),
+                Err(std::io::Error::other("client went away")),
+            ]))
+        };
+
+        // Streamed (POST) and buffered (GET) paths both blame the client, not Wolf
+        for method in [Method::POST, Method::GET] {
+            let err = client
+                .proxy_request(method.clone(), "/upload".parse()?, HeaderMap::new(), broken(), None)
+                .await
+                .unwrap_err();
+            assert!(matches!(&err, WolfProxyError::RequestBody(reason) if reason.contains("client went away")),
+                "{method}: {err:?}");
+        }
+        Ok(())
+    }
+
+    async fn get_ok(client: &WolfProxyClient) -> Result<()> {
+        let response = client
+            .proxy_request(
+                Method::GET,
+                "/api/v1/apps".parse()?,
+                HeaderMap::new(),
+                Full::new(Bytes::new()),
+                None,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        response.into_body().collect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connections_are_reused() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|_req| async move { Response::new(full("{}")) });
+        let client = client(path);
+
+        for _ in 0..50 {
+            get_ok(&client).await?;
+        }
+
+        assert_eq!(client.connections_opened(), 1);
+        assert_eq!(client.pool_size(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pooling_disabled_opens_per_request() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|_req| async move { Response::new(full("{}")) });
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_retry(1, 10)
+                .with_pool(0, 1000),
+        );
+
+        for _ in 0..10 {
+            get_ok(&client).await?;
+        }
+
+        assert_eq!(client.connections_opened(), 10);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_closed_connections_are_discarded() -> Result<()> {
+        // Upstream closes every connection after responding
+        let (_dir, path) = spawn_unix_server(|_req| async move {
+            let mut response = Response::new(full("{}"));
+            response
+                .headers_mut()
+                .insert(header::CONNECTION, HeaderValue::from_static("close"));
+            response
+        });
+        let client = client(path);
+
+        for _ in 0..5 {
+            get_ok(&client).await?;
+        }
+
+        assert_eq!(client.connections_opened(), 5);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_idle_connections_expire() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|_req| async move { Response::new(full("{}")) });
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_retry(1, 10)
+                .with_pool(8, 50),
+        );
+
+        get_ok(&client).await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        get_ok(&client).await?;
+
+        assert_eq!(client.connections_opened(), 2);
+        Ok(())
+    }
+
+    /// Upstream that answers 503 for the first `failures` requests, then 200
+    fn spawn_flaky_server(failures: usize) -> (tempfile::TempDir, String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let (dir, path) = spawn_unix_server(move |_req| {
+            let hit = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let mut response = Response::new(full("{}"));
+                if hit < failures {
+                    *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                }
+                response
+            }
+        });
+        (dir, path, hits)
+    }
+
+    async fn send_empty(client: &WolfProxyClient, method: Method) -> Result<StatusCode> {
+        let response = client
+            .proxy_request(
+                method,
+                "/api/v1/apps".parse()?,
+                HeaderMap::new(),
+                Full::new(Bytes::new()),
+                None,
+            )
+            .await?;
+        Ok(response.status())
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_requests_retry_on_5xx() -> Result<()> {
+        let (_dir, path, hits) = spawn_flaky_server(2);
+        let client = WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 5000).with_retry(3, 1));
+
+        assert_eq!(send_empty(&client, Method::GET).await?, StatusCode::OK);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retries_stop_after_attempts() -> Result<()> {
+        let (_dir, path, hits) = spawn_flaky_server(10);
+        let client = WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 5000).with_retry(3, 1));
+
+        assert_eq!(send_empty(&client, Method::HEAD).await?, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_non_idempotent_requests_not_retried() -> Result<()> {
+        let (_dir, path, hits) = spawn_flaky_server(2);
+        let client = WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 5000).with_retry(3, 1));
+
+        assert_eq!(send_empty(&client, Method::POST).await?, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_5xx_can_be_disabled() -> Result<()> {
+        let (_dir, path, hits) = spawn_flaky_server(2);
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_retry(3, 1)
+                .with_retry_on_5xx(false),
+        );
+
+        assert_eq!(send_empty(&client, Method::GET).await?, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_budget_stops_retries_under_sustained_failure() -> Result<()> {
+        let (_dir, path, hits) = spawn_flaky_server(usize::MAX);
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_retry(3, 1)
+                .with_retry_budget(4, 0),
+        );
+
+        // Ten concurrent requests would retry 20 times unbudgeted; only 4 retries are allowed
+        let statuses = futures_util::future::join_all((0..10).map(|_| send_empty(&client, Method::GET))).await;
+        assert!(statuses.iter().all(|s| matches!(s, Ok(StatusCode::SERVICE_UNAVAILABLE))));
+        assert_eq!(hits.load(Ordering::SeqCst), 10 + 4);
+        assert_eq!(client.retry_budget().remaining(), Some(0));
+
+        // With the budget spent, a later request gets a single attempt
+        assert_eq!(send_empty(&client, Method::GET).await?, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(hits.load(Ordering::SeqCst), 15);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_budget_also_covers_connection_retries() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let socket = dir.path().join("wolf.sock").to_string_lossy().into_owned();
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(socket, 1000, 5000)
+                .with_retry(3, 1)
+                .with_retry_budget(2, 0)
+                .with_circuit_breaker(0, 0),
+        );
+
+        for _ in 0..3 {
+            let err = send_empty(&client, Method::POST).await.unwrap_err();
+            assert!(matches!(err.downcast_ref(), Some(WolfProxyError::ConnectFailed { .. })), "{err:?}");
+        }
+        assert_eq!(client.retry_budget().remaining(), Some(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_eacces_is_permission_denied() {
+        let denied = crate::wolf_connector::connect_error(
+            "wolf.sock".into(),
+            std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+        );
+        assert!(matches!(denied, WolfProxyError::PermissionDenied { .. }), "{denied:?}");
+        assert!(denied.to_string().contains("owner, group and mode"), "{denied}");
+
+        let refused = crate::wolf_connector::connect_error(
+            "wolf.sock".into(),
+            std::io::Error::from(std::io::ErrorKind::ConnectionRefused),
+        );
+        assert!(matches!(refused, WolfProxyError::ConnectFailed { .. }), "{refused:?}");
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_socket_is_permission_denied() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_dir, path) = spawn_unix_server(|_req| async { Response::new(full("{}")) });
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o000))?;
+        if tokio::net::UnixStream::connect(&path).await.is_ok() {
+            // Running as root: socket modes aren't enforced, so there is no EACCES to see
+            return Ok(());
+        }
+
+        let client = WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 5000).with_retry(3, 10));
+        let err = client.check_readiness().await.unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(WolfProxyError::PermissionDenied { .. })), "{err:?}");
+        let err = send_empty(&client, Method::GET).await.unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(WolfProxyError::PermissionDenied { .. })), "{err:?}");
+        assert_eq!(client.retry_budget().remaining(), Some(100), "permission errors aren't retried");
+        Ok(())
+    }
+
+    /// Counts requests and answers each with its sequence number, 503 while `failing` is set
+    fn spawn_counting_server(failing: Arc<std::sync::atomic::AtomicBool>) -> (tempfile::TempDir, String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let (dir, path) = spawn_unix_server(move |_req| {
+            let hit = counter.fetch_add(1, Ordering::SeqCst);
+            let failing = failing.load(Ordering::SeqCst);
+            async move {
+                let mut response = Response::new(full(format!("{{\"hit\":{}}}", hit)));
+                response.headers_mut().insert("x-wolf-version", HeaderValue::from_static("1.0"));
+                if failing {
+                    *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                }
+                response
+            }
+        });
+        (dir, path, hits)
+    }
+
+    async fn get_body(client: &WolfProxyClient, uri: &str) -> Result<(StatusCode, HeaderMap, Bytes)> {
+        let response = client
+            .proxy_request(Method::GET, uri.parse()?, HeaderMap::new(), Full::new(Bytes::new()), None)
+            .await?;
+        let (parts, body) = response.into_parts();
+        Ok((parts.status, parts.headers, body.collect().await?.to_bytes()))
+    }
+
+    #[tokio::test]
+    async fn test_cached_get_is_served_without_wolf_until_ttl() -> Result<()> {
+        let (_dir, path, hits) = spawn_counting_server(Default::default());
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000).with_response_cache(vec!["/api/v1/version".into()], 100),
+        );
+
+        let (status, headers, body) = get_body(&client, "/api/v1/version").await?;
+        assert_eq!((status, &body[..]), (StatusCode::OK, &br#"{"hit":0}"#[..]));
+        // Within the TTL: a hit, with Wolf's status, headers and body replayed
+        let (status, cached_headers, cached) = get_body(&client, "/api/v1/version").await?;
+        assert_eq!((status, cached), (StatusCode::OK, body));
+        assert_eq!(cached_headers["x-wolf-version"], headers["x-wolf-version"]);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // Uncached paths still go to Wolf every time
+        get_body(&client, "/api/v1/apps").await?;
+        get_body(&client, "/api/v1/apps").await?;
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // After the TTL: a miss that fetches a fresh copy
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let (_, _, body) = get_body(&client, "/api/v1/version").await?;
+        assert_eq!(&body[..], br#"{"hit":3}"#);
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_error_responses_are_not_cached() -> Result<()> {
+        let failing = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let (_dir, path, hits) = spawn_counting_server(failing.clone());
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_retry_on_5xx(false)
+                .with_response_cache(vec!["/api/v1/version".into()], 60_000),
+        );
+
+        assert_eq!(get_body(&client, "/api/v1/version").await?.0, StatusCode::SERVICE_UNAVAILABLE);
+        failing.store(false, Ordering::SeqCst);
+        assert_eq!(get_body(&client, "/api/v1/version").await?.0, StatusCode::OK);
+        assert_eq!(get_body(&client, "/api/v1/version").await?.0, StatusCode::OK);
+        assert_eq!(hits.load(Ordering::SeqCst), 2, "only the 200 was cached");
+        assert_eq!(client.response_cache().len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_responses_past_the_buffer_limit_stream_and_are_not_cached() -> Result<()> {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        // A sized answer on one path, a chunked one of unknown length on the other
+        let (_dir, path) = spawn_unix_server(move |req| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if req.uri().path() == "/api/v1/sized" {
+                    Response::new(full("x".repeat(64)))
+                } else {
+                    Response::new(crate::test_util::chunked(vec!["0123456789", "0123456789", "0123456789"], Duration::ZERO))
+                }
+            }
+        });
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_max_buffered_bytes(16)
+                .with_response_cache(vec!["/api/v1/sized".into(), "/api/v1/chunked".into()], 60_000),
+        );
+
+        for _ in 0..2 {
+            let (status, _, body) = get_body(&client, "/api/v1/sized").await?;
+            assert_eq!((status, body.len()), (StatusCode::OK, 64));
+            let (_, _, body) = get_body(&client, "/api/v1/chunked").await?;
+            assert_eq!(&body[..], "0123456789".repeat(3).as_bytes(), "the bytes read before the limit come first");
+        }
+        assert!(client.response_cache().is_empty());
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_size_limits_apply_to_cached_paths() -> Result<()> {
+        let (_dir, path, hits) = spawn_counting_server(Default::default());
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_max_uri_bytes(32)
+                .with_header_limits(1024, 64)
+                .with_response_cache(vec!["/api/v1/version".into()], 60_000),
+        );
+        let long = format!("/api/v1/version?q={}", "a".repeat(64));
+        // Cache the very request that is then too long, as if the limit had just been lowered
+        client.response_cache().put(
+            CacheKey::new(&long.parse()?, &HeaderMap::new()),
+            CachedResponse {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: Bytes::from_static(b"{}"),
+            },
+        );
+
+        let err = get_body(&client, &long).await.unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(WolfProxyError::UriTooLong { .. })), "{err:?}");
+
+        get_body(&client, "/api/v1/version").await?;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-big", HeaderValue::from_str(&"b".repeat(128))?);
+        let err = client
+            .proxy_request(Method::GET, "/api/v1/version".parse()?, headers, Full::new(Bytes::new()), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WolfProxyError::HeadersTooLarge(_)), "{err:?}");
+        assert_eq!(hits.load(Ordering::SeqCst), 1, "only the plain GET reached Wolf");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_readiness_check_bypasses_the_cache() -> Result<()> {
+        let failing = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let (_dir, path, hits) = spawn_counting_server(failing.clone());
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_retry_on_5xx(false)
+                .with_coalescing(true)
+                .with_response_cache(vec!["/api/v1/version".into()], 60_000),
+        );
+        assert_eq!(get_body(&client, "/api/v1/version").await?.0, StatusCode::OK);
+        assert_eq!(client.response_cache().len(), 1);
+
+        // Wolf starts failing: proxied GETs still get the cached copy, readiness asks Wolf
+        failing.store(true, Ordering::SeqCst);
+        assert_eq!(get_body(&client, "/api/v1/version").await?.0, StatusCode::OK);
+        let err = client.check_endpoint("/api/v1/version").await.unwrap_err();
+        assert!(err.to_string().contains("503"), "{err}");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_encoded_cache_entry_is_not_served_to_other_encodings() -> Result<()> {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        // Compresses for clients that accept gzip, as Wolf behind a compressing proxy would
+        let (_dir, path) = spawn_unix_server(move |req| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let gzip = req
+                .headers()
+                .get(header::ACCEPT_ENCODING)
+                .is_some_and(|v| v.to_str().unwrap().contains("gzip"));
+            async move {
+                if gzip {
+                    let mut response = Response::new(full("compressed"));
+                    response.headers_mut().insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+                    response
+                } else {
+                    Response::new(full("{}"))
+                }
+            }
+        });
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000).with_response_cache(vec!["/api/v1/version".into()], 60_000),
+        );
+        let get = |accept_encoding: Option<&'static str>| {
+            let mut headers = HeaderMap::new();
+            if let Some(value) = accept_encoding {
+                headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_static(value));
+            }
+            client.proxy_request(Method::GET, "/api/v1/version".parse().unwrap(), headers, Full::new(Bytes::new()), None)
+        };
+
+        let gzip = get(Some("gzip")).await?;
+        assert_eq!(gzip.headers()[header::CONTENT_ENCODING], "gzip");
+        let identity = get(None).await?;
+        assert!(identity.headers().get(header::CONTENT_ENCODING).is_none(), "gzip replayed to an identity client");
+        assert_eq!(&identity.into_body().collect().await?.to_bytes()[..], b"{}");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        // Each encoding still gets its own hits
+        assert_eq!(get(Some("gzip")).await?.headers()[header::CONTENT_ENCODING], "gzip");
+        assert!(get(None).await?.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_trips_and_recovers() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let socket = dir.path().join("wolf.sock");
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(socket.to_string_lossy().into_owned(), 1000, 5000)
+                .with_retry(1, 1)
+                .with_circuit_breaker(2, 100),
+        );
+
+        // Wolf is down: two connection failures trip the breaker
+        for _ in 0..2 {
+            let err = send_empty(&client, Method::POST).await.unwrap_err();
+            assert!(err.to_string().contains("failed to connect"), "{err}");
+            assert!(matches!(err.downcast_ref(), Some(WolfProxyError::ConnectFailed { .. })), "{err:?}");
+        }
+        assert!(client.circuit_breaker().is_open());
+
+        let err = send_empty(&client, Method::POST).await.unwrap_err();
+        assert!(err.to_string().contains("circuit breaker open"), "{err}");
+        assert!(matches!(err.downcast_ref(), Some(WolfProxyError::CircuitOpen(_))), "{err:?}");
+
+        // Wolf comes back; after the cooldown a probe closes the breaker
+        crate::test_util::spawn_unix_server_at(&socket, |_req| async move { Response::new(full("{}")) });
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        assert_eq!(send_empty(&client, Method::GET).await?, StatusCode::OK);
+        assert!(!client.circuit_breaker().is_open());
+        assert_eq!(send_empty(&client, Method::GET).await?, StatusCode::OK);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_or_dropped_probe_leaves_breaker_recoverable() -> Result<()> {
+        // Wolf hangs while `slow` is set
+        let slow = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let hanging = slow.clone();
+        let (_dir, path) = spawn_unix_server(move |_req| {
+            let hanging = hanging.clone();
+            async move {
+                if hanging.load(std::sync::atomic::Ordering::SeqCst) {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+                Response::new(full("{}"))
+            }
+        });
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 100)
+                .with_retry(1, 1)
+                .with_circuit_breaker(1, 50),
+        );
+        let breaker = client.circuit_breaker();
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, "open");
+
+        // The probe times out: that's a failure, so the breaker re-opens rather than sticking half-open
+        tokio::time::sleep(Duration::from_millis(70)).await;
+        let err = send_empty(&client, Method::GET).await.unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(WolfProxyError::ReadTimeout(_))), "{err:?}");
+        assert_eq!(breaker.status().state, "open");
+
+        // The probe's caller gives up first
+        tokio::time::sleep(Duration::from_millis(70)).await;
+        let dropped = tokio::time::timeout(Duration::from_millis(30), send_empty(&client, Method::GET)).await;
+        assert!(dropped.is_err());
+        assert_eq!(breaker.status().state, "open");
+
+        // Wolf recovers and the next probe closes the breaker
+        slow.store(false, std::sync::atomic::Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(70)).await;
+        assert_eq!(send_empty(&client, Method::GET).await?, StatusCode::OK);
+        assert_eq!(breaker.status().state, "closed");
+        Ok(())
+    }
+
+    #[test]
+    fn test_metrics_record_status_classes_and_errors() {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        metrics::with_local_recorder(&recorder, || {
+            rt.block_on(async {
+                let (_dir, path) = spawn_unix_server(|req| async move {
+                    let status = if req.uri().path() == "/missing" {
+                        StatusCode::NOT_FOUND
+                    } else {
+                        StatusCode::OK
+                    };
+                    let mut resp = Response::new(full("{}"));
+                    *resp.status_mut() = status;
+                    resp
+                });
+                let up = WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 5000));
+                let down = WolfProxyClient::new(
+                    WolfProxyConfig::new("/nonexistent/wolf.sock".into(), 100, 1000).with_retry(1, 1),
+                );
+
+                for uri in ["/ok", "/ok", "/missing"] {
+                    up.proxy_request(Method::GET, uri.parse().unwrap(), HeaderMap::new(), full(""), None)
+                        .await
+                        .unwrap();
+                }
+                // Connection failures land in the error bucket
+                assert!(down
+                    .proxy_request(Method::GET, "/ok".parse().unwrap(), HeaderMap::new(), full(""), None)
+                    .await
+                    .is_err());
+            })
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let counter = |name: &str, label: (&str, &str)| {
+            snapshot
+                .iter()
+                .find(|(key, _, _, _)| {
+                    let key = key.key();
+                    key.name() == name
+                        && key.labels().any(|l| l.key() == label.0 && l.value() == label.1)
+                })
+                .map(|(_, _, _, value)| match value {
+                    DebugValue::Counter(n) => *n,
+                    other => panic!("expected counter, got {other:?}"),
+                })
+                .unwrap_or(0)
+        };
+
+        assert_eq!(counter(proxy_metrics::REQUESTS_TOTAL, ("method", "GET")), 4);
+        assert_eq!(counter(proxy_metrics::RESPONSES_TOTAL, ("status_class", "2xx")), 2);
+        assert_eq!(counter(proxy_metrics::RESPONSES_TOTAL, ("status_class", "4xx")), 1);
+        assert_eq!(counter(proxy_metrics::RESPONSES_TOTAL, ("status_class", "error")), 1);
+        assert!(snapshot.iter().any(|(key, _, _, value)| {
+            key.key().name() == proxy_metrics::REQUEST_DURATION_SECONDS
+                && matches!(value, DebugValue::Histogram(samples) if !samples.is_empty())
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_request_id_forwarded_upstream() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            let id = req
+                .headers()
+                .get_all(REQUEST_ID_HEADER)
+                .iter()
+                .map(|v| v.to_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+                .join(",");
+            Response::new(full(id))
+        });
+        let client = WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 5000));
+
+        // The scoped ID replaces whatever the client sent
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("from-client"));
+        let resp = request_id::scope(
+            "req-123".into(),
+            client.proxy_request(Method::GET, "/api/v1/apps".parse()?, headers, full(""), None),
+        )
+        .await?;
+        assert_eq!(resp.into_body().collect().await?.to_bytes(), "req-123");
+        Ok(())
+    }
+
+    /// Wolf stand-in that echoes the forwarding headers it received
+    fn spawn_forwarded_echo() -> (tempfile::TempDir, String) {
+        spawn_unix_server(|req| async move {
+            let get = |name: &str| {
+                let values: Vec<_> = req.headers().get_all(name).iter().map(|v| v.to_str().unwrap()).collect();
+                values.join(" | ")
+            };
+            Response::new(full(format!("{}\n{}", get("x-forwarded-for"), get("forwarded"))))
+        })
+    }
+
+    async fn forwarded_upstream(
+        client: &WolfProxyClient,
+        peer: &str,
+        inbound: &[(&'static str, &'static str)],
+    ) -> Result<(String, String)> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("wolf.local"));
+        for (name, value) in inbound {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        let resp = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, headers, full(""), Some(peer.parse()?))
+            .await?;
+        let body = String::from_utf8(resp.into_body().collect().await?.to_bytes().to_vec())?;
+        let (xff, forwarded) = body.split_once('\n').unwrap();
+        Ok((xff.to_owned(), forwarded.to_owned()))
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_for_direct_client() -> Result<()> {
+        let (_dir, path) = spawn_forwarded_echo();
+        let client = WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 5000));
+
+        let (xff, forwarded) = forwarded_upstream(&client, "203.0.113.7", &[]).await?;
+        assert_eq!(xff, "203.0.113.7");
+        assert_eq!(forwarded, "for=203.0.113.7;proto=http;host=wolf.local");
+
+        let (xff, forwarded) = forwarded_upstream(&client, "2001:db8::1", &[]).await?;
+        assert_eq!(xff, "2001:db8::1");
+        assert_eq!(forwarded, r#"for="[2001:db8::1]";proto=http;host=wolf.local"#);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_for_appends_to_trusted_proxy_chain() -> Result<()> {
+        let (_dir, path) = spawn_forwarded_echo();
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000).with_trusted_proxies(vec!["10.0.0.0/24".parse()?]),
+        );
+
+        // Repeated headers are one list; ours is the last hop
+        let (xff, forwarded) = forwarded_upstream(
+            &client,
+            "10.0.0.1",
+            &[
+                ("x-forwarded-for", "198.51.100.1"),
+                ("x-forwarded-for", "192.0.2.4"),
+                ("forwarded", "for=198.51.100.1"),
+            ],
+        )
+        .await?;
+        assert_eq!(xff, "198.51.100.1, 192.0.2.4, 10.0.0.1");
+        assert_eq!(forwarded, "for=198.51.100.1, for=10.0.0.1;proto=http;host=wolf.local");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_for_from_untrusted_peer_is_replaced() -> Result<()> {
+        let (_dir, path) = spawn_forwarded_echo();
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000).with_trusted_proxies(vec!["10.0.0.0/24".parse()?]),
+        );
+
+        let (xff, forwarded) = forwarded_upstream(
+            &client,
+            "192.168.1.50",
+            &[("x-forwarded-for", "127.0.0.1"), ("forwarded", "for=127.0.0.1")],
+        )
+        .await?;
+        assert_eq!(xff, "192.168.1.50");
+        assert_eq!(forwarded, "for=192.168.1.50;proto=http;host=wolf.local");
+        Ok(())
+    }
+
+    /// The `Via` and `User-Agent` Wolf received for a request carrying `inbound`
+    async fn via_and_user_agent(
+        config: WolfProxyConfig,
+        inbound: &[(&'static str, &'static str)],
+    ) -> Result<String> {
+        let client = WolfProxyClient::new(config);
+        let mut headers = HeaderMap::new();
+        for (name, value) in inbound {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        let resp = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, headers, full(""), None)
+            .await?;
+        Ok(String::from_utf8(resp.into_body().collect().await?.to_bytes().to_vec())?)
+    }
+
+    #[tokio::test]
+    async fn test_via_is_appended_and_user_agent_defaults_when_absent() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            let get = |name| {
+                let values: Vec<_> = req.headers().get_all(name).iter().map(|v| v.to_str().unwrap()).collect();
+                values.join(" | ")
+            };
+            Response::new(full(format!("{}\n{}", get(header::VIA), get(header::USER_AGENT))))
+        });
+        let config = || WolfProxyConfig::new(path.clone(), 1000, 5000);
+
+        let seen = via_and_user_agent(config(), &[]).await?;
+        assert_eq!(seen, format!("1.1 wolfmanager\n{}", DEFAULT_USER_AGENT));
+
+        // Earlier hops stay in order, as one list; the client's own agent is kept
+        let seen = via_and_user_agent(
+            config(),
+            &[("via", "1.0 edge"), ("via", "1.1 lb"), ("user-agent", "Moonlight/6.0")],
+        )
+        .await?;
+        assert_eq!(seen, "1.0 edge, 1.1 lb, 1.1 wolfmanager\nMoonlight/6.0");
+
+        let custom = config().with_user_agent(Some(HeaderValue::from_static("ops-probe/1")));
+        assert_eq!(via_and_user_agent(custom, &[]).await?, "1.1 wolfmanager\nops-probe/1");
+        assert_eq!(via_and_user_agent(config().with_user_agent(None), &[]).await?, "1.1 wolfmanager\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wolf_errors_are_normalized_when_enabled() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            let mut resp = match req.uri().path() {
+                "/json" => {
+                    let mut resp = Response::new(full(r#"{"error":"app not found","code":42}"#));
+                    resp.headers_mut()
+                        .insert(http::header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                    resp
+                }
+                "/text" => Response::new(full("upstream exploded")),
+                _ => Response::new(full(r#"{"apps":[]}"#)),
+            };
+            if req.uri().path() != "/ok" {
+                *resp.status_mut() = if req.uri().path() == "/json" {
+                    StatusCode::NOT_FOUND
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                };
+            }
+            resp.headers_mut().insert("x-wolf-version", HeaderValue::from_static("1.0"));
+            resp
+        });
+        let fetch = |client: WolfProxyClient, uri: &'static str| async move {
+            let upstream = client
+                .proxy_request(Method::GET, uri.parse()?, HeaderMap::new(), full(""), None)
+                .await?;
+            let resp = client.response_to_axum(upstream).await?;
+            let (parts, body) = resp.into_parts();
+            Ok::<_, anyhow::Error>((parts, body.collect().await?.to_bytes()))
+        };
+        let normalizing = || WolfProxyClient::new(WolfProxyConfig::new(path.clone(), 1000, 5000).with_normalized_errors(true));
+
+        let (parts, body) = fetch(normalizing(), "/json").await?;
+        assert_eq!(parts.status, StatusCode::NOT_FOUND);
+        assert_eq!(parts.headers["x-wolf-version"], "1.0");
+        assert_eq!(parts.extensions.get::<ErrorBody>().unwrap().error, "WolfError");
+        let json: serde_json::Value = serde_json::from_slice(&body)?;
+        assert_eq!(json["error"], "WolfError");
+        assert_eq!(json["detail"], "Wolf returned 404 Not Found: app not found");
+        assert_eq!(json["upstream"], serde_json::json!({"error": "app not found", "code": 42}));
+
+        let (parts, body) = fetch(normalizing(), "/text").await?;
+        assert_eq!(parts.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(parts.headers[http::header::CONTENT_TYPE], "application/json");
+        let json: serde_json::Value = serde_json::from_slice(&body)?;
+        assert_eq!(json["detail"], "Wolf returned 500 Internal Server Error");
+        assert_eq!(json["upstream"], "upstream exploded");
+
+        // Successes are never touched, and without the option errors pass through raw
+        let (_, body) = fetch(normalizing(), "/ok").await?;
+        assert_eq!(body, r#"{"apps":[]}"#);
+        let (parts, body) = fetch(client(path.clone()), "/text").await?;
+        assert_eq!(parts.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body, "upstream exploded");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_default_content_type_only_fills_a_missing_one() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            let mut resp = Response::new(full("body"));
+            match req.uri().path() {
+                "/typed" => {
+                    resp.headers_mut()
+                        .insert(http::header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                }
+                "/empty" => *resp.status_mut() = StatusCode::NO_CONTENT,
+                _ => {}
+            }
+            resp
+        });
+        let content_type = |client: WolfProxyClient, uri: &'static str| async move {
+            let upstream = client
+                .proxy_request(Method::GET, uri.parse()?, HeaderMap::new(), full(""), None)
+                .await?;
+            let resp = client.response_to_axum(upstream).await?;
+            Ok::<_, anyhow::Error>(resp.headers().get(http::header::CONTENT_TYPE).cloned())
+        };
+
+        let untyped = content_type(client(path.clone()), "/untyped").await?;
+        assert_eq!(untyped.unwrap(), DEFAULT_CONTENT_TYPE);
+        let typed = content_type(client(path.clone()), "/typed").await?;
+        assert_eq!(typed.unwrap(), "application/json", "Wolf's own type is kept");
+        assert!(content_type(client(path.clone()), "/empty").await?.is_none());
+
+        let custom = WolfProxyClient::new(
+            WolfProxyConfig::new(path.clone(), 1000, 5000)
+                .with_default_content_type(Some(HeaderValue::from_static("text/plain"))),
+        );
+        assert_eq!(content_type(custom, "/untyped").await?.unwrap(), "text/plain");
+        let disabled = WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 5000).with_default_content_type(None));
+        assert!(content_type(disabled, "/untyped").await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_response_trailers_are_forwarded() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            // Like hyper, a well-behaved upstream only sends trailers the client accepts
+            let te = req.headers().get(http::header::TE).cloned();
+            let mut trailers = HeaderMap::new();
+            trailers.insert("grpc-status", HeaderValue::from_static("0"));
+            trailers.insert("grpc-message", HeaderValue::from_static("ok"));
+            let frames = stream::iter(vec![
+                Ok::<_, Infallible>(Frame::data(Bytes::from_static(b"payload"))),
+                Ok(Frame::trailers(trailers)),
+            ]);
+            let mut resp = Response::new(BodyExt::boxed(StreamBody::new(frames)));
+            resp.headers_mut()
+                .insert(http::header::TRAILER, HeaderValue::from_static("grpc-status, grpc-message"));
+            if let Some(te) = te {
+                resp.headers_mut().insert("x-seen-te", te);
+            }
+            resp
+        });
+
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::TE, HeaderValue::from_static("trailers"));
+        let client = client(path);
+        let upstream = client
+            .proxy_request(Method::POST, "/grpc".parse()?, headers, full(""), None)
+            .await?;
+        let resp = client.response_to_axum(upstream).await?;
+        assert_eq!(resp.headers()["x-seen-te"], "trailers");
+        assert!(resp.headers().get(http::header::TRAILER).is_some());
+
+        let collected = resp.into_body().collect().await?;
+        let trailers = collected.trailers().cloned().expect("trailers forwarded");
+        assert_eq!(trailers["grpc-status"], "0");
+        assert_eq!(trailers["grpc-message"], "ok");
+        assert_eq!(collected.to_bytes(), "payload");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_response_is_streamed_incrementally() -> Result<()> {
+        // Upstream holds the second event back until the client has seen the first
+        let release = Arc::new(tokio::sync::Notify::new());
+        let upstream_release = release.clone();
+        let (_dir, path) = spawn_unix_server(move |_req| {
+            let release = upstream_release.clone();
+            async move {
+                let frames = stream::unfold(0, move |n| {
+                    let release = release.clone();
+                    async move {
+                        let event = match n {
+                            0 => "data: first\n\n",
+                            1 => {
+                                release.notified().await;
+                                "data: second\n\n"
+                            }
+                            _ => return None,
+                        };
+                        Some((Ok::<_, Infallible>(Frame::data(Bytes::from_static(event.as_bytes()))), n + 1))
+                    }
+                });
+                let mut resp = Response::new(BodyExt::boxed(StreamBody::new(frames)));
+                resp.headers_mut()
+                    .insert(http::header::CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+                resp
+            }
+        });
+
+        let client = client(path);
+        let upstream = client
+            .proxy_request(Method::GET, "/api/v1/events".parse()?, HeaderMap::new(), full(""), None)
+            .await?;
+        let resp = client.response_to_axum(upstream).await?;
+        assert_eq!(resp.headers()[http::header::CONTENT_TYPE], "text/event-stream");
+
+        let mut body = resp.into_body();
+        let first = tokio::time::timeout(Duration::from_secs(2), body.frame())
+            .await
+            .expect("first event arrives before the stream ends")
+            .unwrap()?;
+        assert_eq!(first.into_data().unwrap(), "data: first\n\n");
+
+        release.notify_one();
+        let rest = body.collect().await?.to_bytes();
+        assert_eq!(rest, "data: second\n\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_header_size_limits() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|_req| async { Response::new(full("ok")) });
+        let limited = |path: String| {
+            WolfProxyClient::new(
+                WolfProxyConfig::new(path, 1000, 5000)
+                    .with_retry(1, 10)
+                    .with_header_limits(256, 64),
+            )
+        };
+        let with_value = |len: usize| -> Result<HeaderMap> {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-token", HeaderValue::from_str(&"a".repeat(len))?);
+            Ok(headers)
+        };
+
+        // Just under both limits goes through
+        let resp = limited(path)
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, with_value(64)?, full(""), None)
+            .await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Over either limit fails before connecting (the socket doesn't even exist)
+        let client = limited("/nonexistent/wolf.sock".into());
+        let err = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, with_value(65)?, full(""), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, WolfProxyError::HeadersTooLarge(m) if m.contains("x-token")), "{err}");
+
+        let mut headers = HeaderMap::new();
+        for i in 0..6 {
+            headers.insert(HeaderName::from_bytes(format!("x-h{i}").as_bytes())?, HeaderValue::from_str(&"b".repeat(40))?);
+        }
+        let err = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, headers, full(""), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, WolfProxyError::HeadersTooLarge(m) if m.contains("limit is 256")), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_uri_length_limit() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move { Response::new(full(req.uri().to_string())) });
+        let limited = |path: String| {
+            WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 5000).with_retry(1, 10).with_max_uri_bytes(64))
+        };
+        // `/api/v1/apps?q=` is 15 bytes; the query counts towards the limit
+        let uri = |len: usize| format!("/api/v1/apps?q={}", "a".repeat(len - 15)).parse::<http::Uri>();
+
+        let resp = limited(path).proxy_request(Method::GET, uri(64)?, HeaderMap::new(), full(""), None).await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // One byte over fails before connecting (the socket doesn't even exist)
+        let client = limited("/nonexistent/wolf.sock".into());
+        let err = client
+            .proxy_request(Method::GET, uri(65)?, HeaderMap::new(), full(""), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, WolfProxyError::UriTooLong { len: 65, limit: 64 });
+        assert!(matches!(
+            client.proxy_upgrade(uri(65)?, HeaderMap::new(), None).await,
+            Err(WolfProxyError::UriTooLong { .. })
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_configured_and_connection_listed_headers_are_stripped() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            let mut seen: Vec<_> = req.headers().keys().map(|k| k.as_str().to_owned()).collect();
+            seen.sort();
+            let mut resp = Response::new(full(seen.join(",")));
+            let headers = resp.headers_mut();
+            headers.insert(header::CONNECTION, HeaderValue::from_static("x-wolf-hop"));
+            headers.insert("x-wolf-hop", HeaderValue::from_static("1"));
+            headers.insert("x-internal-auth", HeaderValue::from_static("wolf-secret"));
+            headers.insert("x-wolf-kept", HeaderValue::from_static("1"));
+            resp
+        });
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_strip_headers(vec![HeaderName::from_static("x-internal-auth")]),
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONNECTION, HeaderValue::from_static("keep-alive, X-Client-Hop"));
+        headers.insert("x-client-hop", HeaderValue::from_static("1"));
+        headers.insert("x-internal-auth", HeaderValue::from_static("manager-secret"));
+        headers.insert("x-client-kept", HeaderValue::from_static("1"));
+        let upstream = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, headers, full(""), None)
+            .await?;
+        let resp = client.response_to_axum(upstream).await?;
+
+        for name in ["x-wolf-hop", "x-internal-auth", "connection"] {
+            assert!(resp.headers().get(name).is_none(), "{name} reached the client");
+        }
+        assert_eq!(resp.headers()["x-wolf-kept"], "1");
+
+        let body = resp.into_body().collect().await?.to_bytes();
+        let seen: Vec<_> = std::str::from_utf8(&body)?.split(',').map(str::to_owned).collect();
+        assert!(seen.contains(&"x-client-kept".to_owned()), "{seen:?}");
+        for name in ["x-client-hop", "x-internal-auth"] {
+            assert!(!seen.contains(&name.to_owned()), "{name} reached Wolf: {seen:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_without_hop_by_hop_keeps_repeated_values() {
+        let mut headers = HeaderMap::new();
+        headers.append(http::header::SET_COOKIE, HeaderValue::from_static("a=1"));
+        headers.append(http::header::SET_COOKIE, HeaderValue::from_static("b=2"));
+        headers.insert(http::header::CONNECTION, HeaderValue::from_static("close"));
+        headers.insert(http::header::TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+
+        let filtered = without_hop_by_hop(&headers, &[]);
+        assert_eq!(filtered.get_all(http::header::SET_COOKIE).iter().count(), 2);
+        assert!(filtered.get(http::header::CONNECTION).is_none());
+        assert!(filtered.get(http::header::TRANSFER_ENCODING).is_none());
+    }
+
+    fn tcp_client(addr: std::net::SocketAddr, tls: bool) -> WolfProxyClient {
+        let endpoint = WolfEndpoint::parse(&addr.to_string(), tls).unwrap();
+        WolfProxyClient::new(
+            WolfProxyConfig::new(String::new(), 1000, 5000)
+                .with_retry(1, 10)
+                .with_endpoint(endpoint),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_tcp_endpoint() -> Result<()> {
+        let addr = spawn_tcp_server(|req| async move {
+            let host = req.headers()[http::header::HOST].to_str().unwrap().to_owned();
+            Response::new(full(host))
+        })
+        .await;
+        let client = tcp_client(addr, false);
+
+        client.check_readiness().await?;
+        let resp = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, HeaderMap::new(), full(""), None)
+            .await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.into_body().collect().await?.to_bytes(), addr.to_string());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tls_endpoint_performs_handshake() -> Result<()> {
+        // A plaintext server cannot complete the handshake, so the TLS path must fail
+        let addr = spawn_tcp_server(|_req| async move { Response::new(full("{}")) }).await;
+        let client = tcp_client(addr, true);
+
+        assert!(client.endpoint().is_tls());
+        assert!(client.check_readiness().await.is_err());
+        let err = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, HeaderMap::new(), full(""), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("connect"), "unexpected error: {:#}", err);
+        assert!(matches!(err, WolfProxyError::Handshake { .. }), "{err:?}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tls_endpoint_presents_client_certificate() -> Result<()> {
+        let addr = spawn_mtls_tcp_server(|_req| async move { Response::new(full(r#"{"apps":[]}"#)) }).await;
+        let testdata = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata");
+        let connect = |tls: WolfTls| {
+            WolfProxyClient::new(
+                WolfProxyConfig::new(String::new(), 1000, 5000)
+                    .with_retry(1, 10)
+                    .with_endpoint(WolfEndpoint::parse(&addr.to_string(), true).unwrap())
+                    .with_tls(tls),
+            )
+        };
+
+        let client = connect(WolfTls::load(
+            Some(&testdata.join("ca.pem")),
+            Some(&testdata.join("client.pem")),
+            Some(&testdata.join("client.key")),
+        )?);
+        let resp = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, HeaderMap::new(), full(""), None)
+            .await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.into_body().collect().await?.to_bytes(), r#"{"apps":[]}"#);
+
+        // Trusting the server isn't enough when it wants a client certificate
+        let anonymous = connect(WolfTls::load(Some(&testdata.join("ca.pem")), None, None)?);
+        let resp = anonymous
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, HeaderMap::new(), full(""), None)
+            .await;
+        assert!(resp.is_err(), "the server accepted a client without a certificate");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_host_override_keeps_original_as_forwarded_host() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            let header = |name: &str| {
+                let values: Vec<_> = req.headers().get_all(name).iter().map(|v| v.to_str().unwrap()).collect();
+                values.join("|")
+            };
+            Response::new(full(format!("{} {}", header("host"), header("x-forwarded-host"))))
+        });
+        let seen = |config: WolfProxyConfig| async move {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::HOST, HeaderValue::from_static("localhost:8080"));
+            let client = WolfProxyClient::new(config);
+            let resp = client
+                .proxy_request(Method::GET, "/api/v1/apps".parse()?, headers, full(""), None)
+                .await?;
+            Ok::<_, anyhow::Error>(String::from_utf8(resp.into_body().collect().await?.to_bytes().to_vec())?)
+        };
+        let config = || WolfProxyConfig::new(path.clone(), 1000, 5000).with_retry(1, 10);
+
+        assert_eq!(seen(config()).await?, "localhost:8080 localhost:8080", "no override forwards the client's Host");
+        let overridden = config().with_host_override(Some("wolf.local".parse()?));
+        assert_eq!(seen(overridden).await?, "wolf.local localhost:8080");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_host_override_sets_h2_authority() -> Result<()> {
+        let addr = spawn_h2_tcp_server(describe_h2_request).await;
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(String::new(), 1000, 5000)
+                .with_retry(1, 10)
+                .with_endpoint(WolfEndpoint::parse(&addr.to_string(), false).unwrap())
+                .with_http_version(UpstreamHttpVersion::Http2)
+                .with_host_override(Some("wolf.local:47989".parse()?)),
+        );
+        let resp = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, HeaderMap::new(), full(""), None)
+            .await?;
+        assert_eq!(resp.into_body().collect().await?.to_bytes(), "HTTP/2.0 wolf.local:47989 host=false");
+        Ok(())
+    }
+
+    /// What an h2 server saw: version, `:authority`, and whether a `host` header came along
+    async fn describe_h2_request(req: Request<Incoming>) -> Response<crate::test_util::TestBody> {
+        let seen = format!(
+            "{:?} {} host={}",
+            req.version(),
+            req.uri().authority().map(|a| a.as_str()).unwrap_or("-"),
+            req.headers().contains_key(http::header::HOST)
+        );
+        Response::new(full(seen))
+    }
+
+    #[tokio::test]
+    async fn test_http2_prior_knowledge_over_tcp() -> Result<()> {
+        let addr = spawn_h2_tcp_server(describe_h2_request).await;
+        let endpoint = WolfEndpoint::parse(&addr.to_string(), false).unwrap();
+        let client = Arc::new(WolfProxyClient::new(
+            WolfProxyConfig::new(String::new(), 1000, 5000)
+                .with_retry(1, 10)
+                .with_endpoint(endpoint)
+                .with_http_version(UpstreamHttpVersion::Http2),
+        ));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::HOST, HeaderValue::from_static("wolf.example"));
+        headers.insert(http::header::CONNECTION, HeaderValue::from_static("keep-alive"));
+        let requests = (0..4).map(|_| {
+            let (client, headers) = (client.clone(), headers.clone());
+            async move {
+                let resp = client
+                    .proxy_request(Method::GET, "/api/v1/apps".parse().unwrap(), headers, full(""), None)
+                    .await
+                    .unwrap();
+                assert_eq!(resp.status(), StatusCode::OK);
+                let resp = client.response_to_axum(resp).await.unwrap();
+                assert_eq!(resp.version(), http::Version::HTTP_11);
+                resp.into_body().collect().await.unwrap().to_bytes()
+            }
+        });
+        for body in futures_util::future::join_all(requests).await {
+            // Client's Host is not forwarded alongside :authority; connection headers are dropped
+            assert_eq!(body, format!("HTTP/2.0 {} host=false", addr));
+        }
+        // Concurrent requests share one multiplexed connection
+        assert_eq!(client.connections_opened(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_http2_prior_knowledge_over_unix_socket() -> Result<()> {
+        let (_dir, socket) = spawn_h2_unix_server(describe_h2_request);
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(socket, 1000, 5000)
+                .with_retry(1, 10)
+                .with_http_version(UpstreamHttpVersion::Http2),
+        );
+
+        let resp = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, HeaderMap::new(), full(""), None)
+            .await?;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.into_body().collect().await?.to_bytes(), "HTTP/2.0 localhost host=false");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slow_upstream_is_read_timeout() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|_req| async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            Response::new(full("{}"))
+        });
+        let client = WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 50).with_retry(1, 10));
+
+        let err = client
+            .proxy_request(Method::POST, "/api/v1/apps".parse()?, HeaderMap::new(), full(""), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, WolfProxyError::ReadTimeout(Duration::from_millis(50)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_queues_then_fails_fast() -> Result<()> {
+        let (release, gate) = tokio::sync::watch::channel(false);
+        let (_dir, path) = spawn_unix_server(move |req| {
+            let mut gate = gate.clone();
+            async move {
+                if req.uri().path() != "/api/v1/version" {
+                    gate.wait_for(|open| *open).await.unwrap();
+                }
+                Response::new(full("{}"))
+            }
+        });
+        let client = Arc::new(WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_retry(1, 10)
+                .with_concurrency_limit(2, 1, 5000),
+        ));
+        let request = |client: Arc<WolfProxyClient>| {
+            tokio::spawn(async move {
+                client
+                    .proxy_request(Method::GET, "/api/v1/apps".parse().unwrap(), HeaderMap::new(), full(""), None)
+                    .await
+                    .map(|r| r.status())
+            })
+        };
+
+        let mut pending: Vec<_> = (0..2).map(|_| request(client.clone())).collect();
+        while client.concurrency_limit().in_flight() < 2 {
+            tokio::task::yield_now().await;
+        }
+        pending.push(request(client.clone()));
+        while client.concurrency_limit().queued() < 1 {
+            tokio::task::yield_now().await;
+        }
+
+        let err = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, HeaderMap::new(), full(""), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, WolfProxyError::QueueFull { limit: 2 });
+        // Health checks don't wait behind proxied traffic
+        client.check_endpoint("/api/v1/version").await?;
+
+        release.send(true)?;
+        for task in pending {
+            assert_eq!(task.await?, Ok(StatusCode::OK));
+        }
+        assert_eq!(client.concurrency_limit().in_flight(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_identical_concurrent_gets_share_one_upstream_call() -> Result<()> {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let (_dir, path) = spawn_unix_server(move |req| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                // Long enough for every request to join the first one's flight
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                let mut resp = Response::new(full(format!(r#"{{"path":"{}"}}"#, req.uri())));
+                resp.headers_mut().insert("x-wolf-version", HeaderValue::from_static("1.0"));
+                resp
+            }
+        });
+        let client = Arc::new(WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000).with_retry(1, 10).with_coalescing(true),
+        ));
+        let get = |uri: &'static str| {
+            let client = client.clone();
+            async move {
+                let resp = client
+                    .proxy_request(Method::GET, uri.parse().unwrap(), HeaderMap::new(), full(""), None)
+                    .await
+                    .unwrap();
+                let version = resp.headers()["x-wolf-version"].clone();
+                (resp.status(), version, resp.into_body().collect().await.unwrap().to_bytes())
+            }
+        };
+
+        let responses = futures_util::future::join_all((0..20).map(|_| get("/api/v1/apps"))).await;
+        for (status, version, body) in responses {
+            assert_eq!((status, version), (StatusCode::OK, HeaderValue::from_static("1.0")));
+            assert_eq!(body, r#"{"path":"/api/v1/apps"}"#);
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert_eq!(client.connections_opened(), 1);
+        assert_eq!(client.flights_in_progress(), 0);
+
+        // Another query is another request
+        let (first, second) = tokio::join!(get("/api/v1/apps?page=1"), get("/api/v1/apps?page=2"));
+        assert_eq!((first.2, second.2), (r#"{"path":"/api/v1/apps?page=1"}"#.into(), r#"{"path":"/api/v1/apps?page=2"}"#.into()));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bodies_past_the_buffer_limit_are_not_shared() -> Result<()> {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let (_dir, path) = spawn_unix_server(move |_req| {
+            let counter = counter.clone();
+            async move {
+                // Only the first request is slow, so the others join its flight
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                }
+                Response::new(full("x".repeat(64)))
+            }
+        });
+        let client = Arc::new(WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_retry(1, 10)
+                .with_coalescing(true)
+                .with_max_buffered_bytes(16),
+        ));
+        let get = || {
+            let client = client.clone();
+            async move {
+                let resp = client
+                    .proxy_request(Method::GET, "/api/v1/apps".parse().unwrap(), HeaderMap::new(), full(""), None)
+                    .await
+                    .unwrap();
+                resp.into_body().collect().await.unwrap().to_bytes()
+            }
+        };
+
+        let bodies = futures_util::future::join_all((0..5).map(|_| get())).await;
+        assert!(bodies.iter().all(|body| body.len() == 64));
+        assert_eq!(hits.load(Ordering::SeqCst), 5, "the followers went to Wolf themselves");
+        assert_eq!(client.flights_in_progress(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_requests_all_get_the_error() -> Result<()> {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let (_dir, path) = spawn_unix_server(move |_req| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                Response::new(full("{}"))
+            }
+        });
+        let client = WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 100).with_retry(1, 10).with_coalescing(true),
+        );
+
+        let results = futures_util::future::join_all((0..5).map(|_| {
+            client.proxy_request(Method::GET, "/api/v1/apps".parse().unwrap(), HeaderMap::new(), full(""), None)
+        }))
+        .await;
+        for result in results {
+            assert_eq!(result.unwrap_err(), WolfProxyError::ReadTimeout(Duration::from_millis(100)));
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_acquire_timeout() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|_req| async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Response::new(full("{}"))
+        });
+        let client = Arc::new(WolfProxyClient::new(
+            WolfProxyConfig::new(path, 1000, 5000)
+                .with_retry(1, 10)
+                .with_concurrency_limit(1, 8, 50),
+        ));
+        let slow = tokio::spawn({
+            let client = client.clone();
+            async move {
+                client
+                    .proxy_request(Method::GET, "/api/v1/apps".parse().unwrap(), HeaderMap::new(), full(""), None)
+                    .await
+                    .map(|r| r.status())
+            }
+        });
+        while client.concurrency_limit().in_flight() < 1 {
+            tokio::task::yield_now().await;
+        }
+
+        let err = client
+            .proxy_request(Method::GET, "/api/v1/apps".parse()?, HeaderMap::new(), full(""), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, WolfProxyError::QueueTimeout(Duration::from_millis(50)));
+        assert_eq!(slow.await?, Ok(StatusCode::OK));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_endpoint_requires_success() -> Result<()> {
+        let (_dir, path) = spawn_unix_server(|req| async move {
+            let mut resp = Response::new(full("{}"));
+            if req.uri().path() != "/api/v1/version" {
+                *resp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            }
+            resp
+        });
+        let client = client(path);
+
+        client.check_endpoint("/api/v1/version").await?;
+        let err = client.check_endpoint("/api/v1/apps").await.unwrap_err();
+        assert!(err.to_string().contains("500"), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unix_readiness_reports_missing_socket() {
+        let client = client("/nonexistent/wolf.sock".into());
+        let err = client.check_readiness().await.unwrap_err();
+        assert!(err.to_string().contains("wolf.sock not found"));
+    }
+
+    #[tokio::test]
+    async fn test_error_response_includes_request_id() -> Result<()> {
+        let resp = request_id::scope("req-456".into(), async {
+            error_response(StatusCode::BAD_GATEWAY, "UpstreamError", "boom")
+        })
+        .await;
+        let body: serde_json::Value = serde_json::from_slice(&resp.into_body().collect().await?.to_bytes())?;
+        assert_eq!(body["request_id"], "req-456");
+
+        let resp = error_response(StatusCode::BAD_GATEWAY, "UpstreamError", "boom");
+        let body: serde_json::Value = serde_json::from_slice(&resp.into_body().collect().await?.to_bytes())?;
+        assert!(body.get("request_id").is_none());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod websocket_tests {
+    use super::*;
+    use crate::test_util::{full, spawn_unix_server, TestBody};
+    use base64::Engine;
+    use sha1::{Digest, Sha1};
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc;
+
+    const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+    fn accept_key(key: &str) -> String {
+        let digest = Sha1::digest(format!("{}{}", key, WS_GUID).as_bytes());
+        base64::engine::general_purpose::STANDARD.encode(digest)
+    }
+
+    /// Encode a small frame; clients must mask, servers must not
+    fn encode_frame(opcode: u8, payload: &[u8], masked: bool) -> Vec<u8> {
+        let mut frame = vec![0x80 | opcode];
+        if masked {
+            let mask = [0x12, 0x34, 0x56, 0x78];
+            frame.push(0x80 | payload.len() as u8);
+            frame.extend_from_slice(&mask);
+            frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        } else {
+            frame.push(payload.len() as u8);
+            frame.extend_from_slice(payload);
+        }
+        frame
+    }
+
+    /// Decode a small frame into (opcode, unmasked payload)
+    async fn read_frame<R: AsyncRead + Unpin>(r: &mut R) -> std::io::Result<(u8, Vec<u8>)> {
+        let mut head = [0u8; 2];
+        r.read_exact(&mut head).await?;
+        let masked = head[1] & 0x80 != 0;
+        let len = (head[1] & 0x7f) as usize;
+        let mut mask = [0u8; 4];
+        if masked {
+            r.read_exact(&mut mask).await?;
+        }
+        let mut payload = vec![0u8; len];
+        r.read_exact(&mut payload).await?;
+        if masked {
+            for (i, b) in payload.iter_mut().enumerate() {
+                *b ^= mask[i % 4];
+            }
+        }
+        Ok((head[0] & 0x0f, payload))
+    }
+
+    /// Fake Wolf: echoes frames back; with `hang_up` it closes right after the handshake
+    fn spawn_echo_wolf(hang_up: bool) -> (tempfile::TempDir, String) {
+        spawn_unix_server(move |mut req: Request<Incoming>| async move {
+            let key = req.headers()["sec-websocket-key"].to_str().unwrap().to_string();
+            let protocol = req.headers().get("sec-websocket-protocol").cloned();
+            let on_upgrade = hyper::upgrade::on(&mut req);
+            tokio::spawn(async move {
+                let mut io = TokioIo::new(on_upgrade.await.unwrap());
+                if hang_up {
+                    return;
+                }
+                while let Ok((opcode, payload)) = read_frame(&mut io).await {
+                    io.write_all(&encode_frame(opcode, &payload, false)).await.unwrap();
+                    if opcode == 0x8 {
+                        break;
+                    }
+                }
+            });
+
+            let mut response = Response::new(full(""));
+            *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+            let headers = response.headers_mut();
+            headers.insert(header::CONNECTION, "Upgrade".parse().unwrap());
+            headers.insert(header::UPGRADE, "websocket".parse().unwrap());
+            headers.insert("sec-websocket-accept", accept_key(&key).parse().unwrap());
+            if let Some(protocol) = protocol {
+                headers.insert("sec-websocket-protocol", protocol);
+            }
+            response
+        })
+    }
+
+    /// Front the fake Wolf with a TCP server running the proxy's upgrade path.
+    /// Each tunnel task handle is sent on the returned channel.
+    async fn spawn_front(socket_path: String) -> (SocketAddr, mpsc::UnboundedReceiver<JoinHandle<()>>) {
+        let client = Arc::new(WolfProxyClient::new(
+            WolfProxyConfig::new(socket_path, 1000, 1000).with_retry(1, 10),
+        ));
+        let (tx, rx) = mpsc::unbounded_channel();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (client, tx) = (client.clone(), tx.clone());
+                let service = hyper::service::service_fn(move |mut req: Request<Incoming>| {
+                    let (client, tx) = (client.clone(), tx.clone());
+                    async move {
+                        let client_upgrade = hyper::upgrade::on(&mut req);
+                        let outcome = client
+                            .proxy_upgrade(req.uri().clone(), req.headers().clone(), None)
+                            .await
+                            .unwrap();
+                        let response = match outcome {
+                            UpgradeOutcome::Switched { response, upstream } => {
+                                tx.send(spawn_tunnel(client_upgrade, upstream)).unwrap();
+                                response
+                            }
+                            UpgradeOutcome::Rejected(_) => panic!("upgrade rejected"),
+                        };
+                        Ok::<_, Infallible>(response)
+                    }
+                });
+                tokio::spawn(
+                    hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .with_upgrades(),
+                );
+            }
+        });
+
+        (addr, rx)
+    }
+
+    /// Perform the client handshake and return the raw response head
+    async fn handshake(addr: SocketAddr) -> (TcpStream, String) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                b"GET /api/v1/ws HTTP/1.1\r\n\
+                  Host: localhost\r\n\
+                  Connection: Upgrade\r\n\
+                  Upgrade: websocket\r\n\
+                  Sec-WebSocket-Version: 13\r\n\
+                  Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                  Sec-WebSocket-Protocol: wolf.v1\r\n\r\n",
+            )
+            .await
+            .unwrap();
+
+        let mut head = Vec::new();
+        let mut byte = [0u8; 1];
+        while !head.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).await.unwrap();
+            head.push(byte[0]);
+        }
+        (stream, String::from_utf8(head).unwrap().to_ascii_lowercase())
+    }
+
+    async fn assert_tunnel_finishes(rx: &mut mpsc::UnboundedReceiver<JoinHandle<()>>) {
+        let handle = rx.recv().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(2), handle)
+            .await
+            .expect("tunnel task leaked")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_echo_and_clean_close() {
+        let (_dir, path) = spawn_echo_wolf(false);
+        let (addr, mut tunnels) = spawn_front(path).await;
+
+        let (mut stream, head) = handshake(addr).await;
+        assert!(head.starts_with("http/1.1 101"), "{head}");
+        assert!(head.contains("sec-websocket-protocol: wolf.v1"), "{head}");
+        assert!(head.contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo="), "{head}");
+
+        stream.write_all(&encode_frame(0x1, b"hello", true)).await.unwrap();
+        assert_eq!(read_frame(&mut stream).await.unwrap(), (0x1, b"hello".to_vec()));
+
+        stream.write_all(&encode_frame(0x8, b"", true)).await.unwrap();
+        assert_eq!(read_frame(&mut stream).await.unwrap().0, 0x8);
+        drop(stream);
+
+        assert_tunnel_finishes(&mut tunnels).await;
+    }
+
+    #[tokio::test]
+    async fn test_websocket_client_disconnect() {
+        let (_dir, path) = spawn_echo_wolf(false);
+        let (addr, mut tunnels) = spawn_front(path).await;
+
+        let (stream, head) = handshake(addr).await;
+        assert!(head.starts_with("http/1.1 101"), "{head}");
+        drop(stream);
+
+        assert_tunnel_finishes(&mut tunnels).await;
+    }
+
+    #[tokio::test]
+    async fn test_websocket_wolf_disconnect() {
+        let (_dir, path) = spawn_echo_wolf(true);
+        let (addr, mut tunnels) = spawn_front(path).await;
+
+        let (mut stream, head) = handshake(addr).await;
+        assert!(head.starts_with("http/1.1 101"), "{head}");
+
+        // Wolf hung up, so the client sees EOF
+        let mut buf = [0u8; 1];
+        let read = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(read, 0);
+
+        assert_tunnel_finishes(&mut tunnels).await;
+    }
+
+    #[tokio::test]
+    async fn test_websocket_rejected_by_wolf() {
+        let (_dir, path) = spawn_unix_server(|_req| async move {
+            let mut response: Response<TestBody> = Response::new(full("no"));
+            *response.status_mut() = StatusCode::FORBIDDEN;
+            response
+        });
+        let client = WolfProxyClient::new(WolfProxyConfig::new(path, 1000, 1000).with_retry(1, 10));
+
+        let outcome = client
+            .proxy_upgrade("/api/v1/ws".parse().unwrap(), HeaderMap::new(), None)
+            .await
+            .unwrap();
+        match outcome {
+            UpgradeOutcome::Rejected(response) => assert_eq!(response.status(), StatusCode::FORBIDDEN),
+            UpgradeOutcome::Switched { .. } => panic!("expected rejection"),
+        }
+    }
+}
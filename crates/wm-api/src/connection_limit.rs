@@ -0,0 +1,175 @@
+use axum::serve::Listener;
+use std::io::{self, IoSlice};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{info, warn};
+
+/// Caps how many connections are open at once. Past the cap a new connection is
+/// accepted and closed straight away: leaving it in the kernel backlog would keep the
+/// client hanging, and its descriptor would still be spent.
+pub struct LimitedListener<L> {
+    inner: L,
+    /// `None` when there is no limit
+    permits: Option<Arc<Semaphore>>,
+    max: usize,
+    /// Whether the last connection was refused, so the limit is logged once per episode
+    saturated: bool,
+}
+
+impl<L> LimitedListener<L> {
+    /// `max` of `None` accepts every connection
+    pub fn new(inner: L, max: Option<usize>) -> Self {
+        Self {
+            inner,
+            permits: max.map(|max| Arc::new(Semaphore::new(max))),
+            max: max.unwrap_or(0),
+            saturated: false,
+        }
+    }
+}
+
+impl<L: Listener> Listener for LimitedListener<L> {
+    type Io = LimitedIo<L::Io>;
+    type Addr = L::Addr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            let (io, addr) = self.inner.accept().await;
+            let Some(permits) = &self.permits else {
+                return (LimitedIo { io, _permit: None }, addr);
+            };
+            match permits.clone().try_acquire_owned() {
+                Ok(permit) => {
+                    if std::mem::take(&mut self.saturated) {
+                        info!(max_connections = self.max, "Accepting connections again");
+                    }
+                    return (LimitedIo { io, _permit: Some(permit) }, addr);
+                }
+                Err(_) => {
+                    if !self.saturated {
+                        warn!(max_connections = self.max, "Connection limit reached, refusing new connections");
+                        self.saturated = true;
+                    }
+                    drop(io);
+                }
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        self.inner.local_addr()
+    }
+}
+
+/// A connection holding its slot under the limit until it is dropped
+pub struct LimitedIo<I> {
+    io: I,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<I: AsyncRead + Unpin> AsyncRead for LimitedIo<I> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl<I: AsyncWrite + Unpin> AsyncWrite for LimitedIo<I> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::listener::ApiListener;
+    use crate::shutdown::{serve, InFlight, Shutdown};
+    use axum::{routing::get, Router};
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Open a keep-alive connection and wait for its first answer, so it is surely held
+    async fn held_connection(addr: SocketAddr) -> TcpStream {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut buf = [0u8; 256];
+        let n = stream.read(&mut buf).await.unwrap();
+        assert!(buf[..n].starts_with(b"HTTP/1.1 200"), "{}", String::from_utf8_lossy(&buf[..n]));
+        stream
+    }
+
+    /// Whether the server closed `addr`'s next connection without answering it
+    async fn refused(addr: SocketAddr) -> bool {
+        let Ok(mut stream) = TcpStream::connect(addr).await else {
+            return true;
+        };
+        let _ = stream
+            .write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await;
+        let mut buf = [0u8; 256];
+        let read = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buf))
+            .await
+            .expect("the excess connection was left hanging");
+        matches!(read, Ok(0) | Err(_))
+    }
+
+    #[tokio::test]
+    async fn test_connections_past_the_limit_are_refused() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/healthz", get(|| async { "ok" }));
+        tokio::spawn(serve(
+            ApiListener::Tcp(listener),
+            app,
+            std::future::pending(),
+            Shutdown::new(),
+            InFlight::default(),
+            Duration::from_secs(5),
+            Some(2),
+        ));
+
+        let first = held_connection(addr).await;
+        let _second = held_connection(addr).await;
+        assert!(refused(addr).await);
+        assert!(refused(addr).await);
+
+        // Closing one frees its slot
+        drop(first);
+        let mut accepted = false;
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            if !refused(addr).await {
+                accepted = true;
+                break;
+            }
+        }
+        assert!(accepted, "the freed slot was never reused");
+    }
+}
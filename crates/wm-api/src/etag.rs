@@ -0,0 +1,80 @@
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+
+/// A JSON document fixed for the life of the process, served with an `ETag` so clients
+/// can revalidate it with `If-None-Match` instead of downloading it again
+#[derive(Debug, Clone)]
+pub struct CachedJson {
+    body: Bytes,
+    etag: HeaderValue,
+}
+
+impl CachedJson {
+    pub fn new<T: Serialize>(value: &T) -> Self {
+        let body = Bytes::from(serde_json::to_vec(value).unwrap_or_default());
+        let etag = format!("\"{:x}\"", Sha1::digest(&body));
+        Self {
+            body,
+            etag: HeaderValue::from_str(&etag).expect("hex digest is a valid header value"),
+        }
+    }
+
+    /// 304 when `If-None-Match` names our tag, the full document otherwise
+    pub fn respond(&self, headers: &HeaderMap) -> Response {
+        let fresh = headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| matches(v, self.etag.to_str().unwrap_or_default()));
+        if fresh {
+            return (StatusCode::NOT_MODIFIED, [(header::ETAG, self.etag.clone())]).into_response();
+        }
+        (
+            [
+                (header::CONTENT_TYPE, HeaderValue::from_static("application/json")),
+                (header::ETAG, self.etag.clone()),
+            ],
+            Body::from(self.body.clone()),
+        )
+            .into_response()
+    }
+}
+
+/// Whether an `If-None-Match` value names `etag`, using the weak comparison RFC 9110
+/// requires for it (a `W/` prefix is ignored)
+fn matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_if_none_match_forms() {
+        let etag = "\"abc\"";
+        assert!(matches("\"abc\"", etag));
+        assert!(matches("W/\"abc\"", etag));
+        assert!(matches("\"xyz\", \"abc\"", etag));
+        assert!(matches("*", etag));
+        assert!(!matches("\"abcd\"", etag));
+        assert!(!matches("abc", etag), "tags are quoted");
+    }
+
+    #[test]
+    fn test_etag_tracks_content() {
+        let a = CachedJson::new(&serde_json::json!({"a": 1}));
+        let b = CachedJson::new(&serde_json::json!({"a": 2}));
+        assert_ne!(a.etag, b.etag);
+        assert_eq!(a.etag, CachedJson::new(&serde_json::json!({"a": 1})).etag);
+        assert!(a.etag.to_str().unwrap().starts_with('"'));
+    }
+}
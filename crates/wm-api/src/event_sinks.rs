@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+use wm_core::dedup::EventDedup;
+use wm_core::{Event, EventSink};
+
+use crate::AppState;
+
+/// Stores each event and broadcasts it to `/api/v1/events/stream` subscribers
+pub struct BroadcastSink {
+    state: AppState,
+}
+
+impl BroadcastSink {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl EventSink for BroadcastSink {
+    fn name(&self) -> &str {
+        "broadcast"
+    }
+
+    async fn emit(&self, event: &Event) -> anyhow::Result<()> {
+        self.state.store_and_publish(event.clone()).await?;
+        Ok(())
+    }
+}
+
+/// Hands each ingested event to every sink once it has passed the dedup window
+#[derive(Clone)]
+pub struct EventFanout {
+    dedup: Arc<Mutex<EventDedup>>,
+    sinks: Arc<[Arc<dyn EventSink>]>,
+}
+
+impl EventFanout {
+    pub fn new(dedup: Arc<Mutex<EventDedup>>, sinks: Vec<Arc<dyn EventSink>>) -> Self {
+        Self {
+            dedup,
+            sinks: sinks.into(),
+        }
+    }
+
+    /// Emit `event` to all sinks at once; a failure is logged against its sink and
+    /// doesn't stop the others
+    pub async fn deliver(&self, event: Event) {
+        if !self.dedup.lock().unwrap_or_else(|e| e.into_inner()).admit(&event) {
+            debug!(kind = event.kind(), "Dropped duplicate event");
+            return;
+        }
+        let results = join_all(self.sinks.iter().map(|sink| sink.emit(&event))).await;
+        for (sink, result) in self.sinks.iter().zip(results) {
+            if let Err(e) = result {
+                warn!(sink = sink.name(), kind = event.kind(), "Event sink failed: {:#}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::time::Duration;
+    use wm_core::{EventBuilder, FixedClock, SessionId};
+    use wm_storage::{list_events_after, migrate, new_pool};
+
+    /// Keeps every event it is given
+    #[derive(Default)]
+    struct CapturingSink(Mutex<Vec<Event>>);
+
+    #[async_trait]
+    impl EventSink for CapturingSink {
+        fn name(&self) -> &str {
+            "capture"
+        }
+
+        async fn emit(&self, event: &Event) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    struct FailingSink;
+
+    #[async_trait]
+    impl EventSink for FailingSink {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        async fn emit(&self, _: &Event) -> anyhow::Result<()> {
+            Err(anyhow!("destination unreachable"))
+        }
+    }
+
+    fn started(at_secs: i64) -> Event {
+        let at = time::OffsetDateTime::from_unix_timestamp(at_secs).unwrap();
+        EventBuilder::new(FixedClock(at)).session_started(SessionId(uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_failing_sink_does_not_stop_the_others() {
+        let before = Arc::new(CapturingSink::default());
+        let after = Arc::new(CapturingSink::default());
+        let fanout = EventFanout::new(
+            Arc::new(Mutex::new(EventDedup::new(Duration::ZERO))),
+            vec![before.clone(), Arc::new(FailingSink), after.clone()],
+        );
+
+        fanout.deliver(started(1_700_000_000)).await;
+        fanout.deliver(started(1_700_000_001)).await;
+        assert_eq!(before.0.lock().unwrap().len(), 2);
+        assert_eq!(after.0.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_reach_no_sink() {
+        let capture = Arc::new(CapturingSink::default());
+        let fanout = EventFanout::new(
+            Arc::new(Mutex::new(EventDedup::new(Duration::from_secs(2)))),
+            vec![capture.clone()],
+        );
+        let event = started(1_700_000_000);
+
+        fanout.deliver(event.clone()).await;
+        fanout.deliver(event).await;
+        assert_eq!(capture.0.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_sink_stores_and_publishes() {
+        let pool = new_pool("sqlite::memory:").await.unwrap();
+        migrate(&pool).await.unwrap();
+        let state = AppState::new(pool, &wm_config::Config::default());
+        let mut rx = state.events.subscribe();
+
+        BroadcastSink::new(state.clone()).emit(&started(1_700_000_000)).await.unwrap();
+        assert!(matches!(rx.try_recv().unwrap().event, Event::SessionStarted { .. }));
+        assert_eq!(list_events_after(&state.pool, 0, 10).await.unwrap().len(), 1);
+    }
+}
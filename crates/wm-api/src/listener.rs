@@ -0,0 +1,101 @@
+use anyhow::{Context, Result};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::Path;
+use tokio::net::{TcpListener, UnixListener};
+use tracing::info;
+
+/// Peer address handed to handlers for Unix socket connections, which carry no IP.
+/// Such a client is on this host, so it counts as loopback (for rate limiting and
+/// `WM_TRUSTED_PROXIES` alike).
+pub const UNIX_PEER: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+
+/// Where the API server accepts connections
+pub enum ApiListener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+impl ApiListener {
+    /// Bind `addr`: `unix:/path/to.sock` for a Unix socket, anything else as `host:port`
+    pub async fn bind(addr: &str) -> Result<Self> {
+        match addr.strip_prefix("unix:") {
+            Some(path) => {
+                remove_stale_socket(Path::new(path))?;
+                let listener =
+                    UnixListener::bind(path).with_context(|| format!("failed to bind Unix socket {}", path))?;
+                Ok(Self::Unix(listener))
+            }
+            None => {
+                let listener = TcpListener::bind(addr)
+                    .await
+                    .with_context(|| format!("failed to bind {}", addr))?;
+                Ok(Self::Tcp(listener))
+            }
+        }
+    }
+}
+
+/// A socket file left by a previous run would make `bind` fail; take it over unless
+/// something is still accepting on it
+fn remove_stale_socket(path: &Path) -> Result<()> {
+    if !path.exists() || std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Ok(());
+    }
+    info!(path = %path.display(), "Removing stale Unix socket");
+    std::fs::remove_file(path).with_context(|| format!("failed to remove stale Unix socket {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shutdown::{serve, InFlight, Shutdown};
+    use axum::{extract::ConnectInfo, routing::get, Router};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn test_serves_healthz_over_unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wm-api.sock");
+        // Left behind by an earlier run that didn't clean up
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let listener = ApiListener::bind(&format!("unix:{}", path.display())).await.unwrap();
+
+        let app = Router::new().route(
+            "/healthz",
+            get(|ConnectInfo(peer): ConnectInfo<SocketAddr>| async move { peer.to_string() }),
+        );
+        let (tx, rx) = oneshot::channel::<()>();
+        let signal = async move {
+            let _ = rx.await;
+        };
+        let server = tokio::spawn(serve(listener, app, signal, Shutdown::new(), InFlight::default(), Duration::from_secs(5), None));
+
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.contains("127.0.0.1:0"), "{response}");
+
+        tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(2), server)
+            .await
+            .expect("server did not shut down")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_live_socket_is_not_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wm-api.sock");
+        let _live = UnixListener::bind(&path).unwrap();
+        let err = ApiListener::bind(&format!("unix:{}", path.display())).await.err().unwrap();
+        assert!(err.to_string().contains("failed to bind Unix socket"), "{err}");
+    }
+}
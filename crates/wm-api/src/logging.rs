@@ -0,0 +1,144 @@
+use std::io::IsTerminal;
+use tracing::Subscriber;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{fmt, EnvFilter, Layer};
+use wm_config::Config;
+
+/// How log lines are written, resolved from `WM_LOG_FORMAT` and `WM_LOG_TIME`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogSettings {
+    pub pretty: bool,
+    pub time: bool,
+    /// Color pretty output; only when it goes to a terminal
+    pub ansi: bool,
+}
+
+impl LogSettings {
+    pub fn from_config(config: &Config) -> Self {
+        let pretty = config.log_format == "pretty";
+        Self {
+            pretty,
+            // JSON lines go to collectors that stamp their own time
+            time: config.log_time.unwrap_or(pretty),
+            ansi: pretty && std::io::stdout().is_terminal(),
+        }
+    }
+}
+
+/// The formatting layer for `settings`, writing to `writer`
+pub fn fmt_layer<S, W>(settings: LogSettings, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match (settings.pretty, settings.time) {
+        (false, true) => fmt::layer().json().with_current_span(true).with_writer(writer).boxed(),
+        (false, false) => fmt::layer()
+            .json()
+            .with_current_span(true)
+            .without_time()
+            .with_writer(writer)
+            .boxed(),
+        (true, true) => fmt::layer().pretty().with_ansi(settings.ansi).with_writer(writer).boxed(),
+        (true, false) => fmt::layer()
+            .pretty()
+            .with_ansi(settings.ansi)
+            .without_time()
+            .with_writer(writer)
+            .boxed(),
+    }
+}
+
+/// Install the global subscriber: `RUST_LOG` filtering (default `info`) over stdout
+pub fn init(settings: LogSettings) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer(settings, std::io::stdout))
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::Registry;
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Capture {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// What one `info!` looks like under `settings`
+    fn render(settings: LogSettings) -> String {
+        let capture = Capture::default();
+        let subscriber = Registry::default().with(fmt_layer(settings, capture.clone()));
+        tracing::subscriber::with_default(subscriber, || tracing::info!(port = 8080, "listening"));
+        let output = capture.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn settings(format: &str, time: Option<bool>) -> LogSettings {
+        LogSettings::from_config(&Config {
+            log_format: format.into(),
+            log_time: time,
+            ..Config::default()
+        })
+    }
+
+    #[test]
+    fn test_defaults_keep_untimed_json() {
+        let settings = settings("json", None);
+        assert_eq!((settings.pretty, settings.time, settings.ansi), (false, false, false));
+
+        let line: serde_json::Value = serde_json::from_str(render(settings).trim()).unwrap();
+        assert_eq!(line["fields"]["message"], "listening");
+        assert!(line.get("timestamp").is_none(), "{line}");
+
+        let line: serde_json::Value = serde_json::from_str(render(self::settings("json", Some(true))).trim()).unwrap();
+        assert!(line["timestamp"].is_string(), "{line}");
+    }
+
+    #[test]
+    fn test_pretty_is_timestamped_human_output() {
+        let settings = settings("pretty", None);
+        assert!(settings.pretty && settings.time);
+
+        let output = render(settings);
+        assert!(serde_json::from_str::<serde_json::Value>(output.trim()).is_err(), "not JSON: {output}");
+        assert!(output.contains("listening") && output.contains("port: 8080"), "{output}");
+        let year = time::OffsetDateTime::now_utc().year().to_string();
+        assert!(output.trim_start().starts_with(&year), "{output}");
+
+        let untimed = render(self::settings("pretty", Some(false)));
+        assert!(!untimed.trim_start().starts_with(&year), "{untimed}");
+    }
+
+    #[test]
+    fn test_pretty_colors_only_when_asked() {
+        let colored = LogSettings { pretty: true, time: false, ansi: true };
+        assert!(render(colored).contains('\u{1b}'));
+        let plain = LogSettings { ansi: false, ..colored };
+        assert!(!render(plain).contains('\u{1b}'));
+    }
+}
@@ -1,64 +1,291 @@
+mod connection_limit;
+mod etag;
+mod event_sinks;
+mod listener;
+mod logging;
 mod middleware;
+mod retention;
 mod routes;
+mod shutdown;
+mod trusted_proxies;
+mod validated_json;
+mod wolf_events;
 
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
-    response::{IntoResponse, sse::{Sse, Event}},
-    routing::{any, get},
+    response::{IntoResponse, Response, sse::{Sse, Event}},
+    routing::{get, post},
     Json, Router,
 };
+use anyhow::Context as _;
 use http::{Method, header, HeaderName, HeaderValue};
 use serde_json::json;
-use std::{convert::Infallible, sync::Arc, time::Duration};
-use futures_util::stream;
+use std::{
+    convert::Infallible,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use futures_util::{stream, StreamExt};
 use tower_http::cors::{AllowOrigin, CorsLayer};
-use tracing::info;
-use tracing_subscriber::EnvFilter;
+use middleware::cors::CorsPolicy;
+use metrics_exporter_prometheus::PrometheusHandle;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
 use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
-use wm_adapters::wolf_proxy::{WolfProxyClient, WolfProxyConfig};
+use wm_adapters::docker::{unix_docker, DockerApi};
+use wm_adapters::wolf_proxy::{
+    error_response, BackoffStrategy, UpstreamHttpVersion, WolfEndpoint, WolfProxyClient, WolfProxyConfig,
+    WolfTls,
+};
+use wm_adapters::{mock_wolf, UnixWolfApi, WolfApi};
 use wm_config::Config;
-use wm_storage::{new_pool, migrate};
+use wm_adapters::webhook_sink::WebhookSink;
+use wm_adapters::wolf_backoff::{Backoff, DEFAULT_MAX_RETRY_DELAY};
+use wm_core::dedup::EventDedup;
+use wm_core::EventSink;
+use wm_storage::{migrate, new_pool_with, record_boot, PoolSettings, StoredEvent};
+
+/// Events buffered per SSE subscriber before it starts lagging
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// How long SSE clients wait before reconnecting after the stream drops
+const SSE_RETRY: Duration = Duration::from_secs(3);
+
+/// Last frame of an SSE stream ended by graceful shutdown; reconnecting after `retry_ms`
+/// should reach the restarted server
+fn shutdown_frame() -> Event {
+    Event::default()
+        .event("shutdown")
+        .data(serde_json::json!({ "retry_ms": SSE_RETRY.as_millis() as u64 }).to_string())
+}
 
 #[derive(Clone)]
 struct AppState {
     pool: sqlx::SqlitePool,
+    events: broadcast::Sender<StoredEvent>,
+    shutdown: shutdown::Shutdown,
+    /// Idle time after which the SSE stream sends a comment ping
+    sse_keepalive: Duration,
+    /// Most persisted events replayed to a client resuming with `Last-Event-ID`
+    sse_replay_limit: u32,
+    /// Drops back-to-back repeats of incoming events before they are stored
+    dedup: Arc<Mutex<EventDedup>>,
+    /// Set once migrations and the boot record succeed; `/api/v1/*` answers 503 until then
+    ready: Arc<AtomicBool>,
+}
+
+impl AppState {
+    /// State shared by the routes and event ingestion, with `config`'s SSE and dedup settings
+    fn new(pool: sqlx::SqlitePool, config: &Config) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            pool,
+            events,
+            shutdown: shutdown::Shutdown::new(),
+            sse_keepalive: Duration::from_millis(config.sse_keepalive_ms),
+            sse_replay_limit: config.sse_replay_limit,
+            dedup: Arc::new(Mutex::new(EventDedup::new(Duration::from_millis(config.event_dedup_window_ms)))),
+            ready: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Open `/api/v1/*` to traffic
+    fn mark_ready(&self) {
+        self.ready.store(true, Ordering::Release);
+    }
+
+    /// Fan a persisted domain event out to every connected SSE client
+    fn publish(&self, event: StoredEvent) {
+        // No subscribers is fine; the event simply isn't delivered
+        let _ = self.events.send(event);
+    }
+
+    /// Persist an incoming event and publish it; `EventFanout` has already applied the dedup window
+    async fn store_and_publish(&self, event: wm_core::Event) -> anyhow::Result<StoredEvent> {
+        let stored = wm_storage::insert_event(&self.pool, &event).await?;
+        self.publish(stored.clone());
+        Ok(stored)
+    }
+}
+
+/// SSE frame for a persisted event; its row id is the `id:` clients resume from
+fn event_frame(stored: &StoredEvent) -> Option<Event> {
+    match Event::default().id(stored.id.to_string()).json_data(&stored.event) {
+        Ok(frame) => Some(frame),
+        Err(e) => {
+            warn!("Failed to serialize domain event: {}", e);
+            None
+        }
+    }
+}
+
+/// Event `type` tags a subscriber asked for; empty means every event
+#[derive(Debug, Clone, Default)]
+struct TypeFilter(Arc<[&'static str]>);
+
+impl TypeFilter {
+    /// Parse a comma-separated `?types=` value, naming the first unknown tag on failure
+    fn parse(types: &str) -> Result<Self, String> {
+        let kinds = types
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                wm_core::Event::KINDS
+                    .into_iter()
+                    .find(|kind| *kind == name)
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown event type {:?}; expected one of {}",
+                            name,
+                            wm_core::Event::KINDS.join(", ")
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self(kinds.into()))
+    }
+
+    fn matches(&self, event: &StoredEvent) -> bool {
+        self.0.is_empty() || self.0.contains(&event.event.kind())
+    }
+}
+
+/// Turn a broadcast subscription into SSE frames, skipping events a slow client missed,
+/// any with an id at or below `replayed` (already sent from the database), and any `types` excludes
+fn domain_event_stream(
+    rx: broadcast::Receiver<StoredEvent>,
+    replayed: Option<i64>,
+    types: TypeFilter,
+) -> impl futures_core::Stream<Item = Result<Event, Infallible>> {
+    stream::unfold((rx, types), move |(mut rx, types)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) if replayed.is_some_and(|id| event.id <= id) => {}
+                Ok(event) if !types.matches(&event) => {}
+                Ok(event) => {
+                    let Some(frame) = event_frame(&event) else {
+                        continue;
+                    };
+                    return Some((Ok(frame), (rx, types)));
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(skipped = skipped, "SSE subscriber lagged, skipping missed events");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
 }
 
 #[utoipa::path(
     get,
     path = "/healthz",
     responses(
-        (status = 200, description = "OK")
+        (status = 200, description = "Process is alive; dependencies are not checked (see /readyz)")
     )
 )]
 async fn healthz() -> impl IntoResponse {
     Json(serde_json::json!({ "status": "ok" }))
 }
 
+/// Numeric `Last-Event-ID` of a resuming client; anything else starts fresh
+fn last_event_id(headers: &http::HeaderMap) -> Option<i64> {
+    headers
+        .get("last-event-id")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct StreamQuery {
+    /// Comma-separated `Event` type tags to receive
+    types: Option<String>,
+}
+
 #[utoipa::path(
     get,
     path = "/api/v1/events/stream",
+    params(
+        ("types" = Option<String>, Query, description = "Comma-separated Event `type` tags to receive, e.g. `SessionStarted,SessionEnded`; omitted means all")
+    ),
     responses(
-        (status = 200, description = "SSE stream")
+        (status = 200, description = "SSE stream. Each event's `id:` is its `events` row id; send it back as `Last-Event-ID` to replay what was missed. Every `data:` is one Event", body = wm_core::Event, content_type = "text/event-stream"),
+        (status = 400, description = "Unknown event type in `types`", body = wm_adapters::wolf_proxy::ErrorBody)
     )
 )]
-async fn events_stream(State(_state): State<AppState>) -> Sse<impl futures_core::Stream<Item = Result<Event, Infallible>>> {
-    let tick_stream = stream::unfold(tokio::time::interval(Duration::from_secs(5)), |mut interval| async move {
-        interval.tick().await;
-        Some((Ok(Event::default().data(json!({"type": "heartbeat"}).to_string())), interval))
-    });
+async fn events_stream(
+    State(state): State<AppState>,
+    Query(query): Query<StreamQuery>,
+    headers: http::HeaderMap,
+) -> Response {
+    let types = match query.types.as_deref().map(TypeFilter::parse) {
+        None => TypeFilter::default(),
+        Some(Ok(types)) => types,
+        Some(Err(detail)) => return error_response(StatusCode::BAD_REQUEST, "InvalidEventType", &detail),
+    };
 
-    Sse::new(tick_stream)
-        .keep_alive(axum::response::sse::KeepAlive::new().interval(Duration::from_secs(15)))
+    // Subscribe before reading the replay so nothing published in between is lost
+    let rx = state.events.subscribe();
+    let replay = match last_event_id(&headers) {
+        Some(last) if state.sse_replay_limit > 0 => {
+            wm_storage::list_events_after(&state.pool, last, state.sse_replay_limit)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Failed to load SSE replay after event {}: {:#}", last, e);
+                    Vec::new()
+                })
+        }
+        _ => Vec::new(),
+    };
+    let replayed = replay.last().map(|e| e.id);
+
+    let retry = stream::once(async { Ok(Event::default().retry(SSE_RETRY)) });
+    let replay = stream::iter(
+        replay
+            .iter()
+            .filter(|e| types.matches(e))
+            .filter_map(event_frame)
+            .map(Ok)
+            .collect::<Vec<_>>(),
+    );
+    let events = domain_event_stream(rx, replayed, types);
+
+    // End the stream on shutdown so graceful draining doesn't wait on it forever, saying
+    // so first: clients can tell a planned restart from a dropped connection
+    let shutdown = state.shutdown.clone();
+    let goodbye = stream::once(async move { shutdown.is_triggered().then(|| Ok(shutdown_frame())) })
+        .filter_map(std::future::ready);
+    let frames = retry
+        .chain(replay)
+        .chain(events)
+        .take_until(state.shutdown.wait())
+        .chain(goodbye);
+
+    // Pings are SSE comments, which EventSource never surfaces as events
+    Sse::new(frames).keep_alive(
+        axum::response::sse::KeepAlive::new()
+            .interval(state.sse_keepalive)
+            .text("ping"),
+    )
+    .into_response()
 }
 
 #[utoipa::path(
     get,
     path = "/api/v1/ping",
     responses(
-        (status = 200, description = "Ping with DB check"),
+        (status = 200, description = "Ping with DB check and last boot time"),
         (status = 500, description = "Database error")
     )
 )]
@@ -68,34 +295,106 @@ async fn ping(State(state): State<AppState>) -> Result<Json<serde_json::Value>,
         .fetch_one(&state.pool)
         .await;
 
-    match result {
-        Ok(_) => Ok(Json(json!({"ok": true, "db": "up"}))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    if result.is_err() {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
+
+    let boot = wm_storage::latest_boot(&state.pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(json!({"ok": true, "db": "up", "last_boot": boot})))
 }
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(healthz, events_stream, ping),
-    components(schemas()),
+    paths(
+        healthz,
+        routes::health::readyz,
+        events_stream,
+        routes::events_ws::events_ws,
+        routes::events::list_events,
+        routes::events::event_summary,
+        routes::users::list_users,
+        routes::config::get_config,
+        routes::sessions::create_session,
+        routes::sessions::end_session,
+        routes::sessions::get_session,
+        routes::clients::list_clients,
+        routes::breaker::get_breaker,
+        routes::breaker::set_breaker,
+        routes::containers::list_containers,
+        routes::containers::container_status,
+        routes::db::db_status,
+        ping,
+        routes::version::version,
+        routes::metrics::metrics
+    ),
+    components(schemas(
+        wm_core::Event,
+        wm_core::ClientId,
+        wm_core::PairingId,
+        wm_core::SessionId,
+        wm_core::UserId,
+        StoredEvent,
+        routes::events::EventSummary,
+        wm_adapters::wolf_proxy::ErrorBody,
+        wm_adapters::circuit_breaker::BreakerMode,
+        wm_adapters::circuit_breaker::BreakerStatus,
+        routes::version::BuildInfo,
+        wm_storage::MigrationStatus,
+        Config
+    )),
     tags(
         (name = "wm-api", description = "WolfManager API")
     )
 )]
 struct ApiDoc;
 
-/// Build CORS layer with browser-friendly origin checking
-fn build_cors_layer(config: &Config) -> CorsLayer {
-    let public_url = config.public_url.clone();
-    let allow_private = config.allow_private_origins;
+/// Base URL clients should call: `public_url`, else the bind address
+/// (an unspecified IP, or a Unix socket, becomes `localhost`)
+fn server_url(config: &Config) -> String {
+    if let Some(url) = &config.public_url {
+        return url.trim_end_matches('/').to_owned();
+    }
+    if config.bind_addr.starts_with("unix:") {
+        return "http://localhost".to_owned();
+    }
+    match config.bind_addr.parse::<std::net::SocketAddr>() {
+        Ok(addr) if addr.ip().is_unspecified() => format!("http://localhost:{}", addr.port()),
+        Ok(addr) => format!("http://{}", addr),
+        Err(_) => format!("http://{}", config.bind_addr),
+    }
+}
+
+/// The served spec: the derived `ApiDoc` plus the running version and server URL
+fn api_doc(config: &Config) -> utoipa::openapi::OpenApi {
+    let mut spec = ApiDoc::openapi();
+    spec.info.version = env!("CARGO_PKG_VERSION").to_owned();
+    spec.servers = Some(vec![utoipa::openapi::Server::new(server_url(config))]);
+    spec
+}
 
-    // Detect local IPs at startup for CORS allowlist
-    let local_ips = middleware::cors::detect_local_ips();
+/// Build a CORS layer allowing the origins `policy` does
+fn build_cors_layer(config: &Config, policy: CorsPolicy) -> CorsLayer {
+    let origin_pred = match policy {
+        CorsPolicy::Detected(local_ips) => {
+            let public_url = config.public_url.clone();
+            let allowed_origins = config.cors_allowed_origins.clone();
+            let allow_private = config.allow_private_origins;
 
-    // Create origin predicate that checks if browser's Origin header is allowed
-    let origin_pred = AllowOrigin::predicate(move |origin: &HeaderValue, _req| {
-        middleware::cors::origin_allowed(origin, public_url.as_deref(), &local_ips, allow_private)
-    });
+            // Create origin predicate that checks if browser's Origin header is allowed
+            AllowOrigin::predicate(move |origin: &HeaderValue, _req| {
+                middleware::cors::origin_allowed(
+                    origin,
+                    public_url.as_deref(),
+                    &allowed_origins,
+                    &local_ips.read(),
+                    allow_private,
+                )
+            })
+        }
+        CorsPolicy::SameOrigin => AllowOrigin::predicate(middleware::cors::same_origin),
+    };
 
     CorsLayer::new()
         .allow_origin(origin_pred)
@@ -117,30 +416,175 @@ fn build_cors_layer(config: &Config) -> CorsLayer {
         .allow_credentials(false)
 }
 
+/// Assemble the full application router
+fn build_app(
+    state: AppState,
+    config: &Config,
+    wolf_client: Arc<WolfProxyClient>,
+    wolf_api: Arc<dyn WolfApi>,
+    docker: Arc<dyn DockerApi>,
+    metrics: PrometheusHandle,
+) -> Router {
+    // Build a regular Router with manual OpenAPI serving
+    let spec = etag::CachedJson::new(&api_doc(config));
+    let request_timeout = Duration::from_millis(config.request_timeout_ms);
+    let health = routes::health::health_router(routes::health::ReadyState {
+        pool: state.pool.clone(),
+        wolf: wolf_client.clone(),
+        // There is no Wolf to wait for in mock mode
+        check_wolf: config.readyz_check_wolf && !config.mock_wolf,
+    });
+    let wolf_router = if config.mock_wolf {
+        routes::wolf::mock_wolf_router(wolf_api.clone())
+    } else {
+        routes::wolf::wolf_router(
+            wolf_client.clone(),
+            routes::wolf_paths::PathFilter::new(
+                config.wolf_allow_paths.clone(),
+                config.wolf_deny_paths.clone(),
+            ),
+            match config.wolf_ready_mode.as_str() {
+                "request" => routes::wolf::ReadyMode::Request(config.wolf_ready_path.clone()),
+                _ => routes::wolf::ReadyMode::Connect,
+            },
+        )
+    };
+    let wolf_router = middleware::body_limit::limit_body(wolf_router, config.max_body_bytes);
+    let wolf_router = middleware::rate_limit::limit_rate(
+        wolf_router,
+        middleware::rate_limit::RateLimitConfig {
+            per_second: f64::from(config.wolf_rate_limit_rps),
+            burst: config.wolf_rate_limit_burst,
+            trusted_proxies: trusted_proxies::TrustedProxies::new(config.trusted_proxies.clone()),
+            exempt: config.wolf_rate_limit_exempt.clone(),
+            exempt_loopback: config.wolf_rate_limit_exempt_loopback,
+        },
+    );
+
+    // Detect local IPs at startup for CORS allowlist
+    let local_ips = middleware::cors::LocalIps::detect();
+    if config.cors_local_ip_refresh_ms > 0 {
+        local_ips.spawn_refresh(Duration::from_millis(config.cors_local_ip_refresh_ms));
+    }
+    let api_cors = CorsPolicy::Detected(local_ips);
+    let ops_cors = match config.cors_ops_policy.as_str() {
+        "api" => api_cors.clone(),
+        _ => CorsPolicy::SameOrigin,
+    };
+    let auth = middleware::auth::Auth::api_key(config.api_key.as_deref());
+    let access_log =
+        middleware::access_log::AccessLog::new(&config.access_log_level, &config.access_log_exclude);
+    let response_headers = middleware::response_headers::ResponseHeaders::new(
+        config.security_headers,
+        config.content_security_policy.as_deref(),
+        &config.response_headers,
+    )
+    .expect("response headers are checked by Config::validate");
+
+    // Short-lived routes get a deadline; the event streams are excluded because they are meant to stay open
+    let api = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/api/v1/events", get(routes::events::list_events))
+        .route("/api/v1/events/summary", get(routes::events::event_summary))
+        .route("/api/v1/users", get(routes::users::list_users))
+        .route("/api/v1/sessions", post(routes::sessions::create_session))
+        .route("/api/v1/sessions/{id}", get(routes::sessions::get_session))
+        .route("/api/v1/sessions/{id}/end", post(routes::sessions::end_session))
+        .route("/api/v1/ping", get(ping))
+        .route("/api/v1/version", get(routes::version::version))
+        .route("/api/v1/db/status", get(routes::db::db_status))
+        .route("/openapi.json", get(move |headers: http::HeaderMap| std::future::ready(spec.respond(&headers))))
+        .with_state(state.clone())
+        .merge(health)
+        .merge(routes::config::config_router(config))
+        .merge(routes::clients::clients_router(wolf_api))
+        .merge(routes::breaker::breaker_router(wolf_client.clone()))
+        .merge(routes::containers::containers_router(docker));
+    let api = middleware::timeout::limit_duration(api, request_timeout);
+    let ready = state.ready.clone();
+    let streams = Router::new()
+        .route("/api/v1/events/stream", get(events_stream))
+        .route("/api/v1/events/ws", get(routes::events_ws::events_ws))
+        .with_state(state);
+
+    // Swagger UI assets are embedded at build time; the page loads the spec from /openapi.json
+    let docs: Router = SwaggerUi::new("/docs")
+        .config(utoipa_swagger_ui::Config::from("/openapi.json"))
+        .into();
+
+    let app = middleware::body_limit::limit_body(api.merge(streams), config.max_body_bytes)
+        .merge(docs)
+        .nest("/wolfapi", wolf_router)
+        .fallback(routes::fallback::not_found)
+        .method_not_allowed_fallback(routes::fallback::method_not_allowed)
+        // Inside CORS so 401s still carry CORS headers the browser can read
+        .layer(axum::middleware::from_fn_with_state(auth, middleware::auth::require_auth))
+        .layer(axum::middleware::from_fn_with_state(ready, middleware::startup::require_started))
+        .merge(routes::metrics::metrics_router(metrics));
+
+    // The ops endpoints get their own CORS layer; everything else follows the API's
+    let ops = middleware::cors::CorsGroup {
+        paths: middleware::cors::OPS_PATHS,
+        router: app.clone().layer(build_cors_layer(config, ops_cors)),
+    };
+    app.clone()
+        .layer(build_cors_layer(config, api_cors))
+        .layer(axum::middleware::from_fn_with_state(ops, middleware::cors::route_cors_group))
+        .layer(axum::middleware::from_fn_with_state(app, middleware::cors::route_plain_options))
+        .layer(axum::middleware::from_fn(middleware::metrics::track_http_metrics))
+        // Wraps auth, timeouts and the Wolf proxy so all their errors honor `Accept`
+        .layer(axum::middleware::from_fn(middleware::error_format::negotiate_errors))
+        .layer(middleware::compression::compression_layer())
+        // Covers every response, proxied, rejected or not found
+        .layer(axum::middleware::from_fn_with_state(
+            response_headers,
+            middleware::response_headers::inject_response_headers,
+        ))
+        // Inside request_id so each line carries the ID
+        .layer(axum::middleware::from_fn_with_state(access_log, middleware::access_log::access_log))
+        // Outermost so every response, including CORS and auth rejections, carries an ID
+        .layer(axum::middleware::from_fn(middleware::request_id::request_id))
+}
+
+/// Log the resolved configuration, secrets redacted, for troubleshooting
+fn log_effective_config(config: &Config) {
+    match serde_json::to_value(config.redacted()) {
+        Ok(config) => info!(config = %config, "Effective configuration"),
+        Err(e) => warn!("Failed to serialize configuration: {}", e),
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Tracing (JSON logs)
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .json()
-        .with_current_span(false)
-        .without_time()
-        .init();
-
-    let config = Config::load()?;
-    info!("Starting wm-api on {}", config.bind_addr);
+    // Configuration decides the log format, so it's loaded before tracing starts
+    let config = Config::load_layered()?;
+    logging::init(logging::LogSettings::from_config(&config));
+    let build = routes::version::BuildInfo::current();
+    info!(
+        version = build.version,
+        git_commit = build.git_commit,
+        build_timestamp = %build.build_timestamp,
+        "Starting wm-api on {}",
+        config.bind_addr
+    );
+    log_effective_config(&config);
 
-    // Initialize DB
-    let pool = new_pool(&config.db_url).await?;
-    migrate(&pool).await?;
+    let metrics = routes::metrics::install_recorder();
 
-    let state = AppState {
-        pool: pool.clone(),
-    };
+    // Initialize DB
+    let pool = new_pool_with(
+        &config.db_url,
+        PoolSettings {
+            max_connections: config.db_max_connections,
+            acquire_timeout: Duration::from_millis(config.db_acquire_timeout_ms),
+            busy_timeout: Duration::from_millis(config.sqlite_busy_timeout_ms),
+            wal: config.sqlite_wal,
+        },
+    )
+    .await?;
 
-    // Build a regular Router with manual OpenAPI serving
-    let api = ApiDoc::openapi();
+    let state = AppState::new(pool.clone(), &config);
+    let shutdown = state.shutdown.clone();
 
     // Create Wolf proxy client
     let wolf_config = WolfProxyConfig::new(
@@ -151,31 +595,1435 @@ async fn main() -> anyhow::Result<()> {
     .with_retry(
         config.wolf_proxy_retry_attempts,
         config.wolf_proxy_retry_delay_ms,
+    )
+    .with_backoff(
+        match config.wolf_proxy_retry_backoff.as_str() {
+            "fixed" => BackoffStrategy::Fixed,
+            "exponential" => BackoffStrategy::Exponential,
+            _ => BackoffStrategy::Linear,
+        },
+        config.wolf_proxy_retry_max_delay_ms,
+        config.wolf_proxy_retry_jitter,
+    )
+    .with_pool(
+        config.wolf_proxy_pool_max_idle,
+        config.wolf_proxy_pool_idle_timeout_ms,
+    )
+    .with_retry_on_5xx(config.wolf_proxy_retry_on_5xx)
+    .with_retry_budget(
+        config.wolf_proxy_retry_budget,
+        config.wolf_proxy_retry_budget_refill_per_sec,
+    )
+    .with_http_version(match config.wolf_proxy_http_version.as_str() {
+        "http2" => UpstreamHttpVersion::Http2,
+        _ => UpstreamHttpVersion::Http1,
+    })
+    .with_circuit_breaker(
+        config.wolf_breaker_failure_threshold,
+        config.wolf_breaker_cooldown_ms,
+    )
+    .with_concurrency_limit(
+        config.wolf_max_concurrency,
+        config.wolf_concurrency_queue,
+        config.wolf_concurrency_timeout_ms,
+    )
+    .with_response_cache(config.wolf_cache_paths.clone(), config.wolf_cache_ttl_ms)
+    .with_max_buffered_bytes(config.wolf_proxy_max_buffered_bytes)
+    .with_coalescing(config.wolf_proxy_coalesce)
+    .with_max_body_bytes(config.max_body_bytes)
+    .with_header_limits(config.wolf_proxy_max_headers_bytes, config.wolf_proxy_max_header_bytes)
+    .with_max_uri_bytes(config.wolf_proxy_max_uri_bytes)
+    .with_user_agent(
+        Some(config.wolf_proxy_user_agent.as_str())
+            .filter(|ua| !ua.eq_ignore_ascii_case("none"))
+            .map(HeaderValue::from_str)
+            .transpose()
+            .context("invalid WM_WOLF_PROXY_USER_AGENT")?,
+    )
+    .with_normalized_errors(config.wolf_proxy_normalize_errors)
+    .with_host_override(
+        Some(config.wolf_proxy_host.as_str())
+            .filter(|host| !host.is_empty())
+            .map(str::parse::<http::uri::Authority>)
+            .transpose()
+            .context("invalid WM_WOLF_PROXY_HOST")?,
+    )
+    .with_default_content_type(
+        Some(config.wolf_proxy_default_content_type.as_str())
+            .filter(|ct| !ct.eq_ignore_ascii_case("none"))
+            .map(HeaderValue::from_str)
+            .transpose()
+            .context("invalid WM_WOLF_PROXY_DEFAULT_CONTENT_TYPE")?,
+    )
+    .with_trusted_proxies(config.trusted_proxies.clone())
+    .with_strip_headers(
+        config
+            .wolf_proxy_strip_headers
+            .iter()
+            .map(|name| HeaderName::from_bytes(name.as_bytes()))
+            .collect::<Result<_, _>>()
+            .context("invalid WM_WOLF_PROXY_STRIP_HEADERS")?,
     );
+    let wolf_config = match &config.wolf_endpoint {
+        Some(endpoint) => wolf_config.with_endpoint(
+            WolfEndpoint::parse(endpoint, config.wolf_tls).context("invalid WM_WOLF_ENDPOINT")?,
+        ),
+        None => wolf_config,
+    };
+    // Config::validate has checked that these come with a TLS endpoint
+    let wolf_config = if config.wolf_client_cert.is_some() || config.wolf_ca.is_some() {
+        let tls = WolfTls::load(
+            config.wolf_ca.as_deref().map(Path::new),
+            config.wolf_client_cert.as_deref().map(Path::new),
+            config.wolf_client_key.as_deref().map(Path::new),
+        )
+        .context("invalid WM_WOLF_CA, WM_WOLF_CLIENT_CERT or WM_WOLF_CLIENT_KEY")?;
+        wolf_config.with_tls(tls)
+    } else {
+        wolf_config
+    };
+    info!(endpoint = %wolf_config.endpoint, "Wolf API endpoint");
+    let ingest_backoff = wolf_config.retry_backoff();
     let wolf_client = Arc::new(WolfProxyClient::new(wolf_config));
-    let wolf_router = routes::wolf::wolf_router(wolf_client);
 
-    // Build CORS layer
-    let cors = build_cors_layer(&config);
+    let docker = unix_docker(config.docker_sock_path.clone());
 
-    let app = Router::new()
-        .route("/healthz", get(healthz))
-        .route("/api/v1/events/stream", get(events_stream))
-        .route("/api/v1/ping", get(ping))
-        .route("/openapi.json", get(|| async move { Json(api) }))
-        .with_state(state)
-        .nest("/wolfapi", wolf_router)
-        .fallback(any(|| async { "" })) // Catch-all for OPTIONS preflight
-        .layer(cors);
+    let wolf_api: Arc<dyn WolfApi> = if config.mock_wolf {
+        warn!("WM_MOCK_WOLF is set: Wolf requests get canned responses, Wolf is never contacted");
+        mock_wolf()
+    } else {
+        Arc::new(UnixWolfApi::new(wolf_client.clone()))
+    };
+    let app = build_app(state.clone(), &config, wolf_client, wolf_api.clone(), docker, metrics);
 
-    let listener = tokio::net::TcpListener::bind(&config.bind_addr).await?;
+    let listener = listener::ApiListener::bind(&config.bind_addr).await?;
     info!("Listening on {}", config.bind_addr);
 
-    axum::serve(
+    // Listen before migrating, so early requests get a 503 with Retry-After instead of a
+    // refused connection or a half-migrated database
+    let server = tokio::spawn(shutdown::serve(
         listener,
-        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
-    )
-    .await?;
+        app,
+        shutdown::shutdown_signal(),
+        shutdown.clone(),
+        shutdown::InFlight::default(),
+        Duration::from_millis(config.shutdown_grace_period_ms),
+        (config.max_connections > 0).then_some(config.max_connections),
+    ));
+
+    let prepared = async {
+        migrate(&pool).await?;
+        let boot_id = record_boot(&pool, env!("CARGO_PKG_VERSION")).await?;
+        info!(boot_id = boot_id, "Recorded app boot");
+        anyhow::Ok(())
+    }
+    .await;
+    if let Err(e) = prepared {
+        // Only startup 503s were served; nothing worth draining
+        server.abort();
+        return Err(e);
+    }
+    state.mark_ready();
+    info!("Database ready, serving /api/v1");
+
+    if config.event_retention_days > 0 {
+        retention::spawn_event_pruning(
+            pool.clone(),
+            Duration::from_secs(u64::from(config.event_retention_days) * 24 * 60 * 60),
+            Duration::from_millis(config.event_prune_interval_ms),
+            shutdown.clone(),
+        );
+    }
+
+    // Events are stored before they're broadcast, so ingestion waits for the database too
+    if config.wolf_ingest_events && !config.mock_wolf {
+        let mut sinks: Vec<Arc<dyn EventSink>> = vec![Arc::new(event_sinks::BroadcastSink::new(state.clone()))];
+        if let Some(url) = &config.webhook_url {
+            let webhook = WebhookSink::new(
+                url.clone(),
+                config.webhook_secret.clone(),
+                Duration::from_millis(config.webhook_timeout_ms),
+                config.webhook_retry_attempts,
+                Backoff {
+                    strategy: BackoffStrategy::Exponential,
+                    base: Duration::from_millis(config.webhook_retry_delay_ms),
+                    max: DEFAULT_MAX_RETRY_DELAY,
+                    jitter: true,
+                },
+            )?;
+            sinks.push(Arc::new(webhook));
+        }
+        wolf_events::spawn_wolf_ingestion(
+            wolf_api,
+            config.wolf_events_path.clone(),
+            event_sinks::EventFanout::new(state.dedup.clone(), sinks),
+            ingest_backoff,
+            shutdown.clone(),
+        );
+    }
+
+    server.await??;
 
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::ConnectInfo};
+    use http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use wm_adapters::docker::mock_docker;
+    use wm_core::Event as DomainEvent;
+
+    async fn test_state() -> AppState {
+        test_state_with(&Config::default()).await
+    }
+
+    async fn test_state_with(config: &Config) -> AppState {
+        let pool = wm_storage::new_pool("sqlite::memory:").await.unwrap();
+        migrate(&pool).await.unwrap();
+        let state = AppState::new(pool, config);
+        state.mark_ready();
+        state
+    }
+
+    /// Persist and publish, as the routes do
+    async fn record(state: &AppState, event: DomainEvent) -> StoredEvent {
+        let stored = wm_storage::insert_event(&state.pool, &event).await.unwrap();
+        state.publish(stored.clone());
+        stored
+    }
+
+    fn session_ended() -> DomainEvent {
+        DomainEvent::session_ended(wm_core::SessionId(uuid::Uuid::new_v4()))
+    }
+
+    fn test_app(state: AppState) -> Router {
+        test_app_with(state, Config::default())
+    }
+
+    fn test_app_with(state: AppState, config: Config) -> Router {
+        let wolf_client = Arc::new(WolfProxyClient::new(
+            WolfProxyConfig::new("/nonexistent/wolf.sock".into(), 100, 1000).with_retry(1, 1),
+        ));
+        build_app(
+            state,
+            &config,
+            wolf_client,
+            mock_wolf(),
+            mock_docker(),
+            routes::metrics::install_recorder(),
+        )
+    }
+
+    /// Next body frame as text; fails if none arrives within two seconds
+    async fn next_frame(body: &mut Body) -> String {
+        let frame = tokio::time::timeout(Duration::from_secs(2), body.frame())
+            .await
+            .expect("no SSE frame within timeout")
+            .unwrap()
+            .unwrap();
+        String::from_utf8(frame.into_data().unwrap().to_vec()).unwrap()
+    }
+
+    /// Returns status, content type and body
+    async fn get(app: &Router, uri: &str) -> (StatusCode, String, String) {
+        let req = Request::get(uri)
+            .extension(ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 40000))))
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        let status = resp.status();
+        let content_type = resp
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|v| v.to_str().unwrap().to_owned())
+            .unwrap_or_default();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, content_type, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_exposes_http_and_wolf_metrics() {
+        let app = test_app(test_state().await);
+
+        assert_eq!(get(&app, "/healthz").await.0, StatusCode::OK);
+        // Wolf is unreachable, which still counts as a proxied request
+        assert_eq!(get(&app, "/wolfapi/api/v1/apps").await.0, StatusCode::SERVICE_UNAVAILABLE);
+
+        let (status, content_type, body) = get(&app, "/metrics").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(content_type.starts_with("text/plain"));
+        assert!(body.contains("http_requests_total"), "{body}");
+        assert!(body.contains(r#"route="/healthz""#), "{body}");
+        assert!(body.contains("http_request_duration_seconds"), "{body}");
+        assert!(body.contains("wolf_proxy_requests_total"), "{body}");
+        assert!(body.contains(r#"status_class="error""#), "{body}");
+        assert!(body.contains("wolf_proxy_request_duration_seconds"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_latency_histogram_labeled_by_route_template() {
+        let app = test_app(test_state().await);
+        let session = uuid::Uuid::new_v4();
+        assert_eq!(get(&app, &format!("/api/v1/sessions/{session}")).await.0, StatusCode::NOT_FOUND);
+        get(&app, "/wolfapi/api/v1/sessions/abc/pause").await;
+
+        let (_, _, body) = get(&app, "/metrics").await;
+        let samples = |route: &str| {
+            body.lines()
+                .filter(|line| {
+                    line.starts_with("http_request_duration_seconds")
+                        && line.contains(&format!(r#"route="{route}""#))
+                })
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+        let sessions = samples("/api/v1/sessions/{id}");
+        assert!(sessions.iter().any(|l| l.contains(r#"quantile="0.99""#)), "{body}");
+        assert!(sessions.iter().any(|l| l.starts_with("http_request_duration_seconds_count")), "{body}");
+        assert!(!samples(middleware::metrics::WOLF_PROXY_ROUTE).is_empty(), "{body}");
+        // Neither raw path becomes a label
+        assert!(!body.contains(&session.to_string()), "{body}");
+        assert!(!body.contains("abc/pause"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_metrics_in_openapi_spec() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert!(spec["paths"]["/metrics"]["get"]["responses"]["200"]["content"]["text/plain"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_clients_endpoint() {
+        let app = test_app(test_state().await);
+        let (status, _, body) = get(&app, "/api/v1/clients").await;
+        assert_eq!(status, StatusCode::OK);
+        let clients: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(clients.as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_containers_endpoint() {
+        let app = test_app(test_state().await);
+        let (status, content_type, body) = get(&app, "/api/v1/containers").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "application/json");
+        assert!(body.contains("\"name\":\"wolf\""), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_published_event_reaches_sse_stream() {
+        let state = test_state().await;
+        let app = test_app(state.clone());
+
+        let req = Request::get("/api/v1/events/stream").body(Body::empty()).unwrap();
+        let mut body = app.oneshot(req).await.unwrap().into_body();
+        assert!(next_frame(&mut body).await.starts_with("retry: "));
+
+        let session_id = wm_core::SessionId(uuid::Uuid::new_v4());
+        let stored = record(&state, DomainEvent::session_started(session_id)).await;
+
+        let text = next_frame(&mut body).await;
+        assert!(text.starts_with(&format!("id: {}\n", stored.id)), "{text}");
+        assert!(text.contains("data: "), "{text}");
+        assert!(text.contains(r#""type":"SessionStarted""#), "{text}");
+        assert!(text.contains(&session_id.0.to_string()), "{text}");
+    }
+
+    #[tokio::test]
+    async fn test_ingest_drops_repeats_within_dedup_window() {
+        let state = test_state().await;
+        let mut events = state.events.subscribe();
+        let at = time::OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let client = wm_core::ClientId(uuid::Uuid::new_v4());
+        let connected = |at| wm_core::EventBuilder::new(wm_core::FixedClock(at)).client_connected(client);
+        let broadcast: Arc<dyn EventSink> = Arc::new(event_sinks::BroadcastSink::new(state.clone()));
+        let fanout = event_sinks::EventFanout::new(state.dedup.clone(), vec![broadcast]);
+
+        fanout.deliver(connected(at)).await;
+        fanout.deliver(connected(at + Duration::from_millis(500))).await;
+        let window = Duration::from_millis(Config::default().event_dedup_window_ms);
+        fanout.deliver(connected(at + window)).await;
+
+        assert_eq!(wm_storage::list_events_after(&state.pool, 0, 10).await.unwrap().len(), 2);
+        assert_eq!(events.try_recv().unwrap().event.at(), at);
+        assert_eq!(events.try_recv().unwrap().event.at(), at + window);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ingestion_uses_the_configured_dedup_window() {
+        let at = time::OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        for (window_ms, stored) in [(0, 3), (5_000, 1)] {
+            let config = Config {
+                event_dedup_window_ms: window_ms,
+                ..Config::default()
+            };
+            let state = test_state_with(&config).await;
+            // As main wires ingestion: the state's dedup, not one build_app made
+            let _app = test_app_with(state.clone(), config);
+            let broadcast: Arc<dyn EventSink> = Arc::new(event_sinks::BroadcastSink::new(state.clone()));
+            let fanout = event_sinks::EventFanout::new(state.dedup.clone(), vec![broadcast]);
+            let client = wm_core::ClientId(uuid::Uuid::new_v4());
+            for offset in [0, 1_000, 2_000] {
+                let event = wm_core::EventBuilder::new(wm_core::FixedClock(at + Duration::from_millis(offset)))
+                    .client_connected(client);
+                fanout.deliver(event).await;
+            }
+            let events = wm_storage::list_events_after(&state.pool, 0, 10).await.unwrap();
+            assert_eq!(events.len(), stored, "window {window_ms}ms");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sse_starts_with_retry_and_pings_at_keepalive() {
+        let config = Config {
+            sse_keepalive_ms: 50,
+            ..Config::default()
+        };
+        // Held so the event channel stays open
+        let state = test_state_with(&config).await;
+        let app = test_app_with(state.clone(), config);
+
+        let req = Request::get("/api/v1/events/stream").body(Body::empty()).unwrap();
+        let mut body = app.oneshot(req).await.unwrap().into_body();
+        assert_eq!(next_frame(&mut body).await, "retry: 3000\n\n");
+
+        // With nothing published, the only traffic is comment pings at the configured interval
+        let start = std::time::Instant::now();
+        for _ in 0..2 {
+            assert_eq!(next_frame(&mut body).await, ": ping\n\n");
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100), "{:?}", start.elapsed());
+        assert!(start.elapsed() < Duration::from_secs(1), "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_lagged_subscriber_skips_missed_events() {
+        use futures_util::StreamExt;
+
+        let (tx, rx) = broadcast::channel(1);
+        let mut events = Box::pin(domain_event_stream(rx, None, TypeFilter::default()));
+        for id in 1..=3 {
+            let event = session_ended();
+            tx.send(StoredEvent {
+                id,
+                kind: event.kind().into(),
+                at: event.at(),
+                event,
+            })
+            .unwrap();
+        }
+
+        // The two oldest events were dropped; the newest still arrives
+        assert!(events.next().await.is_some());
+        drop(tx);
+        assert!(events.next().await.is_none());
+    }
+
+    async fn open_stream(app: &Router, last_event_id: Option<&str>) -> Body {
+        let mut req = Request::get("/api/v1/events/stream");
+        if let Some(id) = last_event_id {
+            req = req.header("last-event-id", id);
+        }
+        let mut body = app.clone().oneshot(req.body(Body::empty()).unwrap()).await.unwrap().into_body();
+        assert!(next_frame(&mut body).await.starts_with("retry: "));
+        body
+    }
+
+    /// Stream subscribed with `?types=`, past its `retry:` line
+    async fn open_filtered(app: &Router, types: &str) -> Body {
+        let req = Request::get(format!("/api/v1/events/stream?types={types}"));
+        let resp = app.clone().oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let mut body = resp.into_body();
+        assert!(next_frame(&mut body).await.starts_with("retry: "));
+        body
+    }
+
+    /// The `type` of an SSE frame's event
+    fn frame_type(frame: &str) -> String {
+        let data = frame
+            .lines()
+            .find_map(|line| line.strip_prefix("data: "))
+            .unwrap_or_else(|| panic!("no data in {frame:?}"));
+        serde_json::from_str::<serde_json::Value>(data).unwrap()["type"].as_str().unwrap().to_owned()
+    }
+
+    #[tokio::test]
+    async fn test_stream_filtered_to_one_type() {
+        let state = test_state().await;
+        let app = test_app(state.clone());
+        let mut body = open_filtered(&app, "SessionEnded").await;
+
+        let session = wm_core::SessionId(uuid::Uuid::new_v4());
+        record(&state, DomainEvent::session_started(session)).await;
+        let ended = record(&state, DomainEvent::session_ended(session)).await;
+
+        let frame = next_frame(&mut body).await;
+        assert_eq!(frame_id(&frame), ended.id);
+        assert_eq!(frame_type(&frame), "SessionEnded");
+    }
+
+    #[tokio::test]
+    async fn test_stream_filtered_to_several_types() {
+        let state = test_state().await;
+        let app = test_app(state.clone());
+        let mut body = open_filtered(&app, "SessionStarted,%20SessionEnded").await;
+
+        let session = wm_core::SessionId(uuid::Uuid::new_v4());
+        record(&state, DomainEvent::client_connected(wm_core::ClientId(uuid::Uuid::new_v4()))).await;
+        record(&state, DomainEvent::session_started(session)).await;
+        record(&state, DomainEvent::pairing_created(wm_core::PairingId(uuid::Uuid::new_v4()))).await;
+        record(&state, DomainEvent::session_ended(session)).await;
+
+        assert_eq!(frame_type(&next_frame(&mut body).await), "SessionStarted");
+        assert_eq!(frame_type(&next_frame(&mut body).await), "SessionEnded");
+    }
+
+    #[tokio::test]
+    async fn test_stream_rejects_unknown_type() {
+        let app = test_app(test_state().await);
+        let (status, content_type, body) = get(&app, "/api/v1/events/stream?types=SessionStarted,Bogus").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(content_type, "application/json");
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["error"], "InvalidEventType");
+        assert!(body["detail"].as_str().unwrap().contains("\"Bogus\""), "{body}");
+    }
+
+    /// The `id:` of an SSE frame
+    fn frame_id(frame: &str) -> i64 {
+        frame
+            .lines()
+            .find_map(|line| line.strip_prefix("id: "))
+            .unwrap_or_else(|| panic!("no id in {frame:?}"))
+            .parse()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sse_fresh_connection_only_gets_live_events() {
+        let state = test_state().await;
+        record(&state, session_ended()).await;
+        let app = test_app(state.clone());
+
+        let mut body = open_stream(&app, None).await;
+        let live = record(&state, session_ended()).await;
+        assert_eq!(frame_id(&next_frame(&mut body).await), live.id);
+    }
+
+    #[tokio::test]
+    async fn test_sse_resume_replays_missed_events_then_live() {
+        let state = test_state().await;
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            ids.push(record(&state, session_ended()).await.id);
+        }
+        let app = test_app(state.clone());
+
+        let mut body = open_stream(&app, Some(&ids[0].to_string())).await;
+        let replayed = next_frame(&mut body).await;
+        assert_eq!(frame_id(&replayed), ids[1]);
+        assert!(replayed.contains(r#""type":"SessionEnded""#), "{replayed}");
+        assert_eq!(frame_id(&next_frame(&mut body).await), ids[2]);
+
+        let live = record(&state, session_ended()).await;
+        assert_eq!(frame_id(&next_frame(&mut body).await), live.id);
+    }
+
+    #[tokio::test]
+    async fn test_sse_resume_with_unknown_or_stale_id() {
+        let config = Config {
+            sse_replay_limit: 2,
+            ..Config::default()
+        };
+        let state = test_state_with(&config).await;
+        let mut ids = Vec::new();
+        for _ in 0..4 {
+            ids.push(record(&state, session_ended()).await.id);
+        }
+        let app = test_app_with(state.clone(), config);
+
+        // Too far behind: only the newest `sse_replay_limit` events come back
+        let mut stale = open_stream(&app, Some("0")).await;
+        assert_eq!(frame_id(&next_frame(&mut stale).await), ids[2]);
+        assert_eq!(frame_id(&next_frame(&mut stale).await), ids[3]);
+
+        // Ids the server never issued, or that aren't ids at all, replay nothing
+        let mut unknown = open_stream(&app, Some("999999")).await;
+        let mut garbage = open_stream(&app, Some("not-an-id")).await;
+        let live = record(&state, session_ended()).await;
+        assert_eq!(frame_id(&next_frame(&mut stale).await), live.id);
+        assert_eq!(frame_id(&next_frame(&mut unknown).await), live.id);
+        assert_eq!(frame_id(&next_frame(&mut garbage).await), live.id);
+    }
+
+    #[tokio::test]
+    async fn test_events_websocket_receives_published_event() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let state = test_state().await;
+        let app = test_app(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>())
+                .await
+                .unwrap();
+        });
+
+        let mut ws = tokio::net::TcpStream::connect(addr).await.unwrap();
+        ws.write_all(
+            b"GET /api/v1/events/ws HTTP/1.1\r\n\
+              Host: localhost\r\n\
+              Connection: Upgrade\r\n\
+              Upgrade: websocket\r\n\
+              Sec-WebSocket-Version: 13\r\n\
+              Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+        )
+        .await
+        .unwrap();
+        let mut handshake = Vec::new();
+        while !handshake.ends_with(b"\r\n\r\n") {
+            handshake.push(ws.read_u8().await.unwrap());
+        }
+        let handshake = String::from_utf8(handshake).unwrap().to_lowercase();
+        assert!(handshake.starts_with("http/1.1 101"), "{handshake}");
+        assert!(handshake.contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo="), "{handshake}");
+
+        let session_id = wm_core::SessionId(uuid::Uuid::new_v4());
+        record(&state, DomainEvent::session_started(session_id)).await;
+
+        // One unmasked text frame carrying the same JSON as the SSE `data:`
+        let head = [ws.read_u8().await.unwrap(), ws.read_u8().await.unwrap()];
+        assert_eq!(head[0], 0x81);
+        let len = match head[1] {
+            126 => usize::from(ws.read_u16().await.unwrap()),
+            len => usize::from(len),
+        };
+        let mut payload = vec![0u8; len];
+        ws.read_exact(&mut payload).await.unwrap();
+        let event: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(event["type"], "SessionStarted");
+        assert_eq!(event["data"]["session_id"], session_id.0.to_string());
+
+        // Masked close with 1000; the server echoes it and hangs up
+        let mask = [1u8, 2, 3, 4];
+        let mut close = vec![0x88, 0x82];
+        close.extend_from_slice(&mask);
+        close.extend(1000u16.to_be_bytes().iter().zip(mask).map(|(b, m)| b ^ m));
+        ws.write_all(&close).await.unwrap();
+        let mut reply = Vec::new();
+        tokio::time::timeout(Duration::from_secs(2), ws.read_to_end(&mut reply))
+            .await
+            .expect("server kept the socket open")
+            .unwrap();
+        assert_eq!(reply, [0x88, 0x02, 0x03, 0xe8]);
+    }
+
+    #[tokio::test]
+    async fn test_events_websocket_requires_upgrade() {
+        let app = test_app(test_state().await);
+        let resp = app
+            .oneshot(Request::get("/api/v1/events/ws").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UPGRADE_REQUIRED);
+        assert_eq!(resp.headers()["sec-websocket-version"], "13");
+    }
+
+    #[tokio::test]
+    async fn test_list_events_route() {
+        let state = test_state().await;
+        let at = time::OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        wm_storage::insert_event(
+            &state.pool,
+            &DomainEvent::SessionStarted {
+                session_id: wm_core::SessionId(uuid::Uuid::new_v4()),
+                at,
+            },
+        )
+        .await
+        .unwrap();
+        let app = test_app(state);
+
+        let (status, _, body) = get(&app, "/api/v1/events?limit=10").await;
+        assert_eq!(status, StatusCode::OK);
+        let events: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(events[0]["kind"], "SessionStarted");
+        assert_eq!(events[0]["at"], "2023-11-14T22:13:20Z");
+
+        let (status, _, body) = get(&app, "/api/v1/events?before=2023-11-14T22:13:20Z").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "[]");
+
+        let (status, _, _) = get(&app, "/api/v1/events?before=yesterday").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let id = events[0]["id"].as_i64().unwrap();
+        let (status, _, body) = get(&app, &format!("/api/v1/events?before=2023-11-14T22:13:20Z&before_id={}", id + 1)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap()[0]["id"], id);
+
+        let (status, _, _) = get(&app, "/api/v1/events?before_id=1").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_event_summary_route() {
+        let state = test_state().await;
+        let at = time::OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let session_id = wm_core::SessionId(uuid::Uuid::new_v4());
+        let events = [
+            DomainEvent::SessionStarted { session_id, at },
+            DomainEvent::SessionEnded { session_id, at: at + time::Duration::hours(2) },
+            DomainEvent::SessionStarted { session_id, at: at + time::Duration::hours(2) },
+        ];
+        for event in &events {
+            wm_storage::insert_event(&state.pool, event).await.unwrap();
+        }
+        let app = test_app(state);
+
+        let (status, _, body) = get(&app, "/api/v1/events/summary").await;
+        assert_eq!(status, StatusCode::OK);
+        let summary: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(summary, serde_json::json!({
+            "total": 3,
+            "by_type": {"SessionStarted": 2, "SessionEnded": 1},
+            "since": null,
+        }));
+
+        let (_, _, body) = get(&app, "/api/v1/events/summary?since=2023-11-14T23:13:20Z").await;
+        let summary: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(summary["total"], 2);
+        assert_eq!(summary["by_type"]["SessionStarted"], 1);
+        assert_eq!(summary["since"], "2023-11-14T23:13:20Z");
+
+        let (status, _, body) = get(&app, "/api/v1/events/summary?since=an-hour-ago").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("InvalidSince"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_db_status_route() {
+        let state = test_state().await;
+        let app = test_app(state.clone());
+
+        let (status, _, body) = get(&app, "/api/v1/db/status").await;
+        assert_eq!(status, StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["backend"], "sqlite");
+        assert_eq!(body["up_to_date"], true);
+        assert_eq!(body["applied_version"], body["latest_version"]);
+
+        // A migration from a newer build, as left behind by a downgrade
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (20990101, 'newer', 1, x'00', 0)",
+        )
+        .execute(&state.pool)
+        .await
+        .unwrap();
+        let (status, _, body) = get(&app, "/api/v1/db/status").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["applied_version"], 20990101);
+        assert_eq!(body["up_to_date"], false);
+    }
+
+    #[tokio::test]
+    async fn test_list_users_route() {
+        let state = test_state().await;
+        for n in 1..=3 {
+            wm_storage::insert_user(&state.pool, wm_core::UserId(uuid::Uuid::from_u128(n)))
+                .await
+                .unwrap();
+        }
+        let app = test_app(state);
+
+        let (status, _, body) = get(&app, "/api/v1/users?limit=2").await;
+        assert_eq!(status, StatusCode::OK);
+        let page: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(page["users"].as_array().unwrap().len(), 2);
+        let cursor = page["next_cursor"].as_str().unwrap().to_owned();
+        assert_eq!(page["users"][1]["id"], cursor);
+
+        let (status, _, body) = get(&app, &format!("/api/v1/users?limit=2&after={cursor}")).await;
+        assert_eq!(status, StatusCode::OK);
+        let page: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(page["users"].as_array().unwrap().len(), 1);
+        assert!(page["next_cursor"].is_null());
+
+        let (status, _, body) = get(&app, "/api/v1/users?after=not-a-cursor").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("InvalidCursor"), "{body}");
+        assert_eq!(get(&app, "/api/v1/users?limit=101").await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(get(&app, "/api/v1/users?limit=0").await.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle_routes() {
+        let state = test_state().await;
+        let user = wm_core::UserId(uuid::Uuid::new_v4());
+        wm_storage::insert_user(&state.pool, user).await.unwrap();
+        let mut events = state.events.subscribe();
+        let app = test_app(state);
+
+        let post = |uri: String, body: String| {
+            Request::post(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .extension(ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 40000))))
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let json = |resp: axum::response::Response| async move {
+            let status = resp.status();
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+        };
+
+        let body = format!(r#"{{"user_id":"{}"}}"#, user.0);
+        let (status, session) = json(app.clone().oneshot(post("/api/v1/sessions".into(), body)).await.unwrap()).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(session["ended_at"].is_null());
+        let id = session["id"].as_str().unwrap().to_owned();
+        assert!(matches!(
+            events.try_recv().unwrap().event,
+            DomainEvent::SessionStarted { session_id, .. } if session_id.0.to_string() == id
+        ));
+
+        let (status, _, body) = get(&app, &format!("/api/v1/sessions/{id}")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(&user.0.to_string()), "{status} {body}");
+
+        let end = || post(format!("/api/v1/sessions/{id}/end"), String::new());
+        let (status, ended) = json(app.clone().oneshot(end()).await.unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(ended["ended_at"].is_string());
+        assert!(matches!(events.try_recv().unwrap().event, DomainEvent::SessionEnded { .. }));
+
+        // Ending again is idempotent and publishes nothing
+        let (status, again) = json(app.clone().oneshot(end()).await.unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(again["ended_at"], ended["ended_at"]);
+        assert!(events.try_recv().is_err());
+
+        let body = format!(r#"{{"user_id":"{}"}}"#, uuid::Uuid::new_v4());
+        let (status, _) = json(app.clone().oneshot(post("/api/v1/sessions".into(), body)).await.unwrap()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(get(&app, &format!("/api/v1/sessions/{}", uuid::Uuid::new_v4())).await.0, StatusCode::NOT_FOUND);
+        assert_eq!(get(&app, "/api/v1/sessions/not-a-uuid").await.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_create_session_body_errors_are_422() {
+        let app = test_app(test_state().await);
+        for (body, field, expected) in [
+            ("{}", "user_id", None),
+            (r#"{"user_id":42}"#, "user_id", Some("a UUID")),
+        ] {
+            let req = Request::post("/api/v1/sessions")
+                .header(header::CONTENT_TYPE, "application/json")
+                .extension(ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 40000))))
+                .body(Body::from(body))
+                .unwrap();
+            let resp = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+            let json: serde_json::Value =
+                serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+            assert_eq!(json["fields"][0]["field"], field, "{json}");
+            if let Some(expected) = expected {
+                assert!(json["fields"][0]["expected"].as_str().unwrap().contains(expected), "{json}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_version_reports_build_metadata() {
+        let app = test_app(test_state().await);
+
+        let (status, content_type, body) = get(&app, "/api/v1/version").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "application/json");
+        let info: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let keys: Vec<&str> = info.as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(keys, ["build_timestamp", "git_commit", "version"]);
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert!(!info["git_commit"].as_str().unwrap().is_empty());
+        let built = info["build_timestamp"].as_str().unwrap();
+        let parsed = time::OffsetDateTime::parse(built, &time::format_description::well_known::Rfc3339);
+        assert!(parsed.is_ok(), "{built}");
+    }
+
+    #[tokio::test]
+    async fn test_ping_reports_last_boot() {
+        let state = test_state().await;
+        let app = test_app(state.clone());
+
+        let (_, _, body) = get(&app, "/api/v1/ping").await;
+        let ping: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(ping["last_boot"].is_null());
+
+        record_boot(&state.pool, "1.2.3").await.unwrap();
+        let (status, _, body) = get(&app, "/api/v1/ping").await;
+        assert_eq!(status, StatusCode::OK);
+        let ping: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(ping["db"], "up");
+        assert_eq!(ping["last_boot"]["version"], "1.2.3");
+        assert!(ping["last_boot"]["at"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_api_key_guards_api_and_wolf_routes() {
+        let config = Config {
+            api_key: Some("s3cret".into()),
+            ..Config::default()
+        };
+        let app = test_app_with(test_state().await, config);
+
+        assert_eq!(get(&app, "/healthz").await.0, StatusCode::OK);
+        assert_eq!(get(&app, "/api/v1/ping").await.0, StatusCode::UNAUTHORIZED);
+        let (status, _, body) = get(&app, "/wolfapi/api/v1/apps").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body.contains("Unauthorized"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_wolf_path_rules() {
+        let config = Config {
+            wolf_allow_paths: vec!["/api/v1/apps".into(), "/api/v1/admin".into()],
+            wolf_deny_paths: vec!["/api/v1/admin".into()],
+            ..Config::default()
+        };
+        let app = test_app_with(test_state().await, config);
+
+        // Allowed paths reach the (missing) socket
+        assert_eq!(get(&app, "/wolfapi/api/v1/apps").await.0, StatusCode::SERVICE_UNAVAILABLE);
+
+        let (status, content_type, body) = get(&app, "/wolfapi/api/v1/admin/reset").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(content_type, "application/json");
+        assert!(body.contains("PathForbidden"), "{body}");
+
+        assert_eq!(get(&app, "/wolfapi/api/v1/clients").await.0, StatusCode::FORBIDDEN);
+    }
+
+    async fn send(app: &Router, req: http::request::Builder) -> axum::response::Response {
+        let req = req
+            .extension(ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 40000))))
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(req).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_breaker_override_is_admin_only_and_sheds_wolf_traffic() {
+        let config = Config {
+            api_key: Some("s3cret".into()),
+            ..Config::default()
+        };
+        let app = test_app_with(test_state().await, config);
+        let force = |key: Option<&str>, state: &str| {
+            let mut req = Request::post("/api/v1/wolf/breaker")
+                .header(header::CONTENT_TYPE, "application/json")
+                .extension(ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 40000))));
+            if let Some(key) = key {
+                req = req.header("x-api-key", key);
+            }
+            req.body(Body::from(format!(r#"{{"state":"{state}"}}"#))).unwrap()
+        };
+
+        let resp = app.clone().oneshot(force(None, "open")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(get(&app, "/api/v1/wolf/breaker").await.0, StatusCode::UNAUTHORIZED);
+
+        let resp = app.clone().oneshot(force(Some("s3cret"), "open")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = send(&app, Request::get("/wolfapi/api/v1/apps").header("x-api-key", "s3cret")).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        assert!(String::from_utf8_lossy(&body).contains("circuit breaker open"));
+
+        // Handing control back lets requests reach the (missing) socket again
+        app.clone().oneshot(force(Some("s3cret"), "auto")).await.unwrap();
+        let resp = send(&app, Request::get("/wolfapi/api/v1/apps").header("x-api-key", "s3cret")).await;
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        assert!(!String::from_utf8_lossy(&body).contains("circuit breaker open"));
+    }
+
+    #[tokio::test]
+    async fn test_config_endpoint_omits_secrets() {
+        let config = Config {
+            api_key: Some("s3cret".into()),
+            allow_private_origins: true,
+            ..Config::default()
+        };
+        let app = test_app_with(test_state().await, config);
+
+        let req = Request::get("/api/v1/config").header("x-api-key", "s3cret");
+        let resp = send(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["bind_addr"], "0.0.0.0:8080");
+        assert_eq!(body["request_timeout_ms"], 30000);
+        assert_eq!(body["allow_private_origins"], true);
+        assert!(body.get("api_key").is_none(), "{body}");
+        assert!(!body.to_string().contains("s3cret"), "{body}");
+
+        // Behind the API key like the rest of /api/v1
+        assert_eq!(get(&app, "/api/v1/config").await.0, StatusCode::UNAUTHORIZED);
+
+        let (_, _, spec) = get(&app, "/openapi.json").await;
+        let spec: serde_json::Value = serde_json::from_str(&spec).unwrap();
+        let schema = &spec["components"]["schemas"]["Config"];
+        assert!(schema["properties"]["bind_addr"].is_object(), "{schema}");
+        assert!(schema["properties"]["api_key"].is_null(), "{schema}");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_path_is_json_404() {
+        let app = test_app(test_state().await);
+        let (status, content_type, body) = get(&app, "/api/v1/nope").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(content_type, "application/json");
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["error"], "NotFound");
+        assert_eq!(body["detail"], "no route for GET /api/v1/nope");
+    }
+
+    #[tokio::test]
+    async fn test_wrong_method_is_405_with_allow() {
+        let app = test_app(test_state().await);
+        let resp = send(&app, Request::post("/api/v1/ping")).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers()[header::ALLOW], "GET,HEAD");
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "MethodNotAllowed");
+
+        // Routes outside the timeout-wrapped API get the same treatment
+        let resp = send(&app, Request::delete("/api/v1/events/stream")).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers()[header::ALLOW], "GET,HEAD");
+    }
+
+    #[tokio::test]
+    async fn test_options_still_succeeds() {
+        let app = test_app(test_state().await);
+        let preflight = Request::options("/api/v1/ping")
+            .header(header::ORIGIN, "http://localhost:5173")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET");
+        let resp = send(&app, preflight).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "http://localhost:5173");
+        assert!(resp.headers().contains_key(header::ACCESS_CONTROL_ALLOW_METHODS));
+
+        // Preflights are answered even on paths with no route
+        let preflight = Request::options("/api/v1/nope")
+            .header(header::ORIGIN, "http://localhost:5173")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET");
+        assert_eq!(send(&app, preflight).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_plain_options_reports_allowed_methods() {
+        let app = test_app(test_state().await);
+        let resp = send(&app, Request::options("/api/v1/ping")).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(resp.headers()[header::ALLOW], "GET,HEAD");
+        assert!(resp.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+
+        let resp = send(&app, Request::options("/api/v1/sessions/abc/end")).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(resp.headers()[header::ALLOW], "POST");
+
+        let resp = send(&app, Request::options("/api/v1/nope")).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "NotFound");
+    }
+
+    #[tokio::test]
+    async fn test_plain_wolf_options_needs_the_api_key() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Wolf counting every request that reaches it
+        let hits = Arc::new(AtomicUsize::new(0));
+        let seen = hits.clone();
+        let dir = tempfile::tempdir().unwrap();
+        let sock = dir.path().join("wolf.sock");
+        let listener = tokio::net::UnixListener::bind(&sock).unwrap();
+        let wolf = Router::new().fallback(move || {
+            seen.fetch_add(1, Ordering::SeqCst);
+            std::future::ready(StatusCode::NO_CONTENT)
+        });
+        tokio::spawn(async move { axum::serve(listener, wolf).await.unwrap() });
+
+        let config = Config {
+            api_key: Some("s3cret".into()),
+            ..Config::default()
+        };
+        let wolf_client = Arc::new(WolfProxyClient::new(
+            WolfProxyConfig::new(sock.to_string_lossy().into_owned(), 1000, 1000).with_retry(1, 1),
+        ));
+        let app = build_app(
+            test_state().await,
+            &config,
+            wolf_client,
+            mock_wolf(),
+            mock_docker(),
+            routes::metrics::install_recorder(),
+        );
+
+        let resp = send(&app, Request::options("/wolfapi/x")).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(hits.load(Ordering::SeqCst), 0, "the unauthenticated OPTIONS reached Wolf");
+
+        // Preflights are still answered by the CORS layer, without a key and without Wolf
+        let preflight = Request::options("/wolfapi/x")
+            .header(header::ORIGIN, "http://localhost:5173")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET");
+        assert_eq!(send(&app, preflight).await.status(), StatusCode::OK);
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+        let resp = send(&app, Request::options("/wolfapi/x").header("x-api-key", "s3cret")).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ops_routes_reject_origins_the_api_allows() {
+        let app = test_app(test_state().await);
+        let origin = "http://localhost:5173";
+        let resp = send(&app, Request::get("/api/v1/ping").header(header::ORIGIN, origin)).await;
+        assert_eq!(resp.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], origin);
+
+        for path in ["/healthz", "/readyz", "/metrics"] {
+            let resp = send(&app, Request::get(path).header(header::ORIGIN, origin)).await;
+            // The request is still served; the browser just can't read the answer
+            assert_ne!(resp.status(), StatusCode::NOT_FOUND, "{path}");
+            assert!(resp.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none(), "{path}");
+
+            let preflight = Request::options(path)
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET");
+            let resp = send(&app, preflight).await;
+            assert!(resp.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none(), "{path}");
+        }
+
+        // An origin naming the server itself is still allowed
+        let resp = send(
+            &app,
+            Request::get("/healthz").header(header::HOST, "wm.lan:3000").header(header::ORIGIN, "http://wm.lan:3000"),
+        )
+        .await;
+        assert_eq!(resp.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "http://wm.lan:3000");
+    }
+
+    #[tokio::test]
+    async fn test_ops_routes_can_follow_the_api_policy() {
+        let config = Config {
+            cors_ops_policy: "api".into(),
+            ..Config::default()
+        };
+        let app = test_app_with(test_state().await, config);
+        let resp = send(&app, Request::get("/healthz").header(header::ORIGIN, "http://localhost:5173")).await;
+        assert_eq!(resp.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "http://localhost:5173");
+    }
+
+    #[tokio::test]
+    async fn test_wolf_errors_follow_accept() {
+        let app = test_app(test_state().await);
+        for uri in ["/wolfapi/api/v1/apps", "/wolfapi/_ready"] {
+            let req = Request::get(uri)
+                .header(header::ACCEPT, "text/plain")
+                .extension(ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 40000))))
+                .body(Body::empty())
+                .unwrap();
+            let resp = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE, "{uri}");
+            assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/plain; charset=utf-8", "{uri}");
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert!(body.starts_with("UpstreamUnavailable: "), "{uri}: {body}");
+
+            // Without an Accept header the same failure stays JSON
+            let (status, content_type, body) = get(&app, uri).await;
+            assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{uri}");
+            assert_eq!(content_type, "application/json", "{uri}");
+            assert!(body.contains(r#""error":"UpstreamUnavailable""#), "{uri}: {body}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_ends_sse_stream() {
+        let state = test_state().await;
+        let app = test_app(state.clone());
+
+        let req = Request::get("/api/v1/events/stream").body(Body::empty()).unwrap();
+        let mut body = app.oneshot(req).await.unwrap().into_body();
+
+        assert!(next_frame(&mut body).await.starts_with("retry:"));
+        let idle = tokio::time::timeout(Duration::from_millis(100), body.frame()).await;
+        assert!(idle.is_err(), "no shutdown frame before shutdown");
+
+        state.shutdown.trigger();
+        let frame = next_frame(&mut body).await;
+        assert_eq!(frame, "event: shutdown\ndata: {\"retry_ms\":3000}\n\n");
+        let frame = tokio::time::timeout(Duration::from_secs(2), body.frame())
+            .await
+            .expect("SSE stream kept running after shutdown");
+        assert!(frame.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_wolf_proxy_body_limit() {
+        let config = Config {
+            max_body_bytes: 16,
+            ..Config::default()
+        };
+        let app = test_app_with(test_state().await, config);
+
+        let post = |len: usize| {
+            Request::post("/wolfapi/api/v1/apps")
+                .header(header::CONTENT_LENGTH, len)
+                .extension(ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 40000))))
+                .body(Body::from(vec![b'a'; len]))
+                .unwrap()
+        };
+
+        // Under the limit reaches the proxy, which can't connect in tests
+        let resp = app.clone().oneshot(post(16)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let resp = app.clone().oneshot(post(17)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "PayloadTooLarge");
+    }
+
+    #[tokio::test]
+    async fn test_openapi_is_gzip_compressed() {
+        let app = test_app(test_state().await);
+        let req = Request::get("/openapi.json")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[header::CONTENT_ENCODING], "gzip");
+
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..2], &[0x1f, 0x8b], "expected gzip magic bytes");
+    }
+
+    #[tokio::test]
+    async fn test_sse_is_not_compressed_or_buffered() {
+        let state = test_state().await;
+        let app = test_app(state.clone());
+        let req = Request::get("/api/v1/events/stream")
+            .header(header::ACCEPT_ENCODING, "gzip, br")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+
+        // A single small event must arrive on its own rather than waiting for an encoder to fill
+        let mut body = resp.into_body();
+        assert!(next_frame(&mut body).await.starts_with("retry: "));
+        record(&state, session_ended()).await;
+        assert!(next_frame(&mut body).await.starts_with("id: "));
+    }
+
+    #[tokio::test]
+    async fn test_readyz_mounted_and_public() {
+        let config = Config {
+            api_key: Some("s3cret".into()),
+            ..Config::default()
+        };
+        let app = test_app_with(test_state().await, config);
+
+        // Wolf isn't running in tests, but the probe must not be behind the API key
+        let (status, _, body) = get(&app, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.contains(r#""database":{"status":"up"}"#), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_openapi_has_event_and_error_schemas() {
+        let app = test_app(test_state().await);
+        let (status, _, body) = get(&app, "/openapi.json").await;
+        assert_eq!(status, StatusCode::OK);
+        let spec: serde_json::Value = serde_json::from_str(&body).unwrap();
+
+        let schemas = &spec["components"]["schemas"];
+        for name in ["Event", "StoredEvent", "ErrorBody", "SessionId"] {
+            assert!(schemas[name].is_object(), "missing {name} schema");
+        }
+
+        // Paths point at them rather than leaving bodies untyped
+        let paths = &spec["paths"];
+        let stream = &paths["/api/v1/events/stream"]["get"]["responses"]["200"]["content"]["text/event-stream"];
+        assert_eq!(stream["schema"]["$ref"], "#/components/schemas/Event");
+        let not_found = &paths["/api/v1/sessions/{id}"]["get"]["responses"]["404"]["content"]["application/json"];
+        assert_eq!(not_found["schema"]["$ref"], "#/components/schemas/ErrorBody");
+    }
+
+    #[tokio::test]
+    async fn test_mock_wolf_mode_serves_canned_responses() {
+        let config = Config {
+            mock_wolf: true,
+            ..Config::default()
+        };
+        // The real client points at a socket that doesn't exist; mock mode never touches it
+        let app = test_app_with(test_state().await, config);
+
+        let (status, content_type, body) = get(&app, "/wolfapi/anything").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "application/json");
+        assert_eq!(body, r#"{"mock":true}"#);
+
+        let (status, _, body) = get(&app, "/wolfapi/_ready").await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(get(&app, "/readyz").await.0, StatusCode::OK);
+
+        let req = Request::get("/wolfapi/api/v1/events").header(header::ACCEPT, "text/event-stream");
+        let resp = send(&app, req).await;
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/event-stream");
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"data: {\"type\":\"mock\"}\n\n");
+    }
+
+    #[tokio::test]
+    async fn test_openapi_version_and_server_from_config() {
+        let (_, _, body) = get(&test_app(test_state().await), "/openapi.json").await;
+        let spec: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(spec["info"]["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(spec["servers"][0]["url"], "http://localhost:8080");
+
+        let config = Config {
+            public_url: Some("https://wolf.example.com/".into()),
+            ..Config::default()
+        };
+        let (_, _, body) = get(&test_app_with(test_state().await, config), "/openapi.json").await;
+        let spec: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(spec["servers"][0]["url"], "https://wolf.example.com");
+        assert_eq!(spec["servers"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_api_is_503_until_startup_finishes() {
+        let pool = wm_storage::new_pool("sqlite::memory:").await.unwrap();
+        let state = AppState::new(pool.clone(), &Config::default());
+        let app = test_app(state.clone());
+
+        let resp = send(&app, Request::get("/api/v1/ping")).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(resp.headers()[header::RETRY_AFTER], "2");
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "Starting");
+        // Liveness doesn't wait for the database
+        assert_eq!(get(&app, "/healthz").await.0, StatusCode::OK);
+
+        migrate(&pool).await.unwrap();
+        record_boot(&pool, "test").await.unwrap();
+        state.mark_ready();
+        assert_eq!(get(&app, "/api/v1/ping").await.0, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_security_headers_on_every_response() {
+        let app = test_app(test_state().await);
+        for uri in ["/healthz", "/nope", "/wolfapi/api/v1/apps"] {
+            let resp = send(&app, Request::get(uri)).await;
+            assert_eq!(resp.headers()[header::X_CONTENT_TYPE_OPTIONS], "nosniff", "{uri}");
+            assert_eq!(resp.headers()[header::REFERRER_POLICY], "no-referrer", "{uri}");
+        }
+
+        let config = Config {
+            response_headers: vec!["Referrer-Policy: same-origin".into()],
+            ..Config::default()
+        };
+        let app = test_app_with(test_state().await, config);
+        let resp = send(&app, Request::get("/healthz")).await;
+        assert_eq!(resp.headers()[header::REFERRER_POLICY], "same-origin");
+        assert_eq!(resp.headers()[header::X_FRAME_OPTIONS], "DENY");
+    }
+
+    #[tokio::test]
+    async fn test_static_documents_revalidate_with_etag() {
+        let app = test_app(test_state().await);
+        for uri in ["/openapi.json", "/api/v1/config"] {
+            let resp = send(&app, Request::get(uri)).await;
+            assert_eq!(resp.status(), StatusCode::OK, "{uri}");
+            let etag = resp.headers()[header::ETAG].clone();
+            assert!(etag.to_str().unwrap().starts_with('"'), "{uri}: {etag:?}");
+
+            let resp = send(&app, Request::get(uri).header(header::IF_NONE_MATCH, etag.clone())).await;
+            assert_eq!(resp.status(), StatusCode::NOT_MODIFIED, "{uri}");
+            assert_eq!(resp.headers()[header::ETAG], etag);
+            assert!(resp.into_body().collect().await.unwrap().to_bytes().is_empty());
+
+            let resp = send(&app, Request::get(uri).header(header::IF_NONE_MATCH, "\"stale\"")).await;
+            assert_eq!(resp.status(), StatusCode::OK, "{uri}");
+        }
+    }
+
+    #[test]
+    fn test_server_url_keeps_a_specific_bind_address() {
+        let config = Config {
+            bind_addr: "192.168.1.20:9000".into(),
+            ..Config::default()
+        };
+        assert_eq!(server_url(&config), "http://192.168.1.20:9000");
+
+        let config = Config {
+            bind_addr: "unix:/run/wm/wm-api.sock".into(),
+            ..Config::default()
+        };
+        assert_eq!(server_url(&config), "http://localhost");
+    }
+
+    #[tokio::test]
+    async fn test_docs_serves_swagger_ui() {
+        let app = test_app(test_state().await);
+
+        let (status, _, _) = get(&app, "/docs").await;
+        assert!(status.is_redirection());
+
+        let (status, content_type, body) = get(&app, "/docs/").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(content_type.starts_with("text/html"), "{content_type}");
+        assert!(body.contains("swagger-ui"), "{body}");
+
+        let (status, _, body) = get(&app, "/docs/swagger-initializer.js").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("/openapi.json"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_skips_sse() {
+        let state = test_state().await;
+        let config = Config {
+            request_timeout_ms: 50,
+            ..Config::default()
+        };
+        let app = test_app_with(state.clone(), config);
+
+        let req = Request::get("/api/v1/events/stream").body(Body::empty()).unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let mut body = resp.into_body();
+        assert!(next_frame(&mut body).await.starts_with("retry: "));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        record(&state, session_ended()).await;
+        assert!(next_frame(&mut body).await.starts_with("id: "));
+    }
+}
@@ -0,0 +1,186 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::Level;
+
+use super::request_id::RequestId;
+
+/// Target for access log lines, so `RUST_LOG` can tune them separately
+pub const TARGET: &str = "wm_api::access";
+
+/// Which requests get an access log line, and at what level
+#[derive(Debug, Clone)]
+pub struct AccessLog {
+    /// `None` turns access logging off
+    pub level: Option<Level>,
+    /// Exact request paths that are never logged
+    pub exclude: Arc<[String]>,
+}
+
+impl AccessLog {
+    /// Build from config values; `level` is `off` or a tracing level name
+    pub fn new(level: &str, exclude: &[String]) -> Self {
+        let level = match level.to_ascii_lowercase().as_str() {
+            "off" => None,
+            other => Some(other.parse().unwrap_or(Level::INFO)),
+        };
+        Self {
+            level,
+            exclude: exclude.into(),
+        }
+    }
+}
+
+macro_rules! access_event {
+    ($level:expr, $($fields:tt)*) => {
+        match $level {
+            Level::ERROR => tracing::event!(target: TARGET, Level::ERROR, $($fields)*),
+            Level::WARN => tracing::event!(target: TARGET, Level::WARN, $($fields)*),
+            Level::INFO => tracing::event!(target: TARGET, Level::INFO, $($fields)*),
+            Level::DEBUG => tracing::event!(target: TARGET, Level::DEBUG, $($fields)*),
+            Level::TRACE => tracing::event!(target: TARGET, Level::TRACE, $($fields)*),
+        }
+    };
+}
+
+/// Log one line per request once the response headers are ready
+pub async fn access_log(State(log): State<AccessLog>, req: Request, next: Next) -> Response {
+    let Some(level) = log.level else {
+        return next.run(req).await;
+    };
+    let path = req.uri().path().to_owned();
+    if log.exclude.contains(&path) {
+        return next.run(req).await;
+    }
+
+    let method = req.method().clone();
+    let client_ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string());
+    let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+    let start = Instant::now();
+
+    let response = next.run(req).await;
+
+    access_event!(
+        level,
+        method = %method,
+        path = %path,
+        status = response.status().as_u16(),
+        latency_ms = start.elapsed().as_millis() as u64,
+        client_ip = client_ip.as_deref().unwrap_or("-"),
+        request_id = request_id.as_deref().unwrap_or("-"),
+        "request completed"
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use std::io;
+    use std::sync::Mutex;
+    use tower::ServiceExt;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    /// Collects formatted log output for assertions
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Capture {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    impl Capture {
+        fn lines(&self) -> Vec<serde_json::Value> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    async fn call(log: AccessLog, uri: &str) -> Vec<serde_json::Value> {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_max_level(Level::TRACE)
+            .with_writer(capture.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/healthz", get(|| async { "ok" }))
+            .route("/api/v1/ping", get(|| async { "pong" }))
+            .layer(axum::middleware::from_fn_with_state(log, access_log))
+            .layer(axum::middleware::from_fn(crate::middleware::request_id::request_id));
+        let mut req = http::Request::get(uri)
+            .header("x-request-id", "req-789")
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([192, 168, 1, 20], 50000))));
+        app.oneshot(req).await.unwrap();
+
+        capture
+            .lines()
+            .into_iter()
+            .filter(|line| line["target"] == TARGET)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_logs_handled_request() {
+        let lines = call(AccessLog::new("info", &["/healthz".into()]), "/api/v1/ping?x=1").await;
+        assert_eq!(lines.len(), 1);
+
+        let line = &lines[0];
+        assert_eq!(line["level"], "INFO");
+        let fields = &line["fields"];
+        assert_eq!(fields["message"], "request completed");
+        assert_eq!(fields["method"], "GET");
+        assert_eq!(fields["path"], "/api/v1/ping");
+        assert_eq!(fields["status"], 200);
+        assert_eq!(fields["client_ip"], "192.168.1.20");
+        assert_eq!(fields["request_id"], "req-789");
+        assert!(fields["latency_ms"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_excluded_path_not_logged() {
+        let lines = call(AccessLog::new("info", &["/healthz".into()]), "/healthz").await;
+        assert!(lines.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_level_is_configurable() {
+        let lines = call(AccessLog::new("debug", &[]), "/healthz").await;
+        assert_eq!(lines[0]["level"], "DEBUG");
+
+        let lines = call(AccessLog::new("off", &[]), "/api/v1/ping").await;
+        assert!(lines.is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+use async_trait::async_trait;
+use axum::{
+    extract::{OriginalUri, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use http::{HeaderMap, StatusCode};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use wm_adapters::wolf_proxy::error_response;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Path prefixes that require authentication when an authenticator is configured
+const PROTECTED_PREFIXES: &[&str] = &["/api/v1/", "/wolfapi/"];
+
+/// Who a request was authenticated as. `require_auth` inserts it into the request's
+/// extensions, so handlers can take `Option<Extension<AuthContext>>` (absent when auth is off).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthContext {
+    pub principal: String,
+}
+
+/// Why a request was not authenticated; the message becomes the 401's detail
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuthError {
+    /// The request carried none of the credentials the backend reads
+    #[error("missing {0}")]
+    Missing(&'static str),
+    /// Credentials were presented but not accepted
+    #[error("invalid {0}")]
+    Invalid(&'static str),
+}
+
+/// A way of checking a request's credentials, e.g. a static API key or a bearer token
+#[async_trait]
+pub trait Authenticator: Send + Sync {
+    async fn authenticate(&self, headers: &HeaderMap) -> Result<AuthContext, AuthError>;
+}
+
+/// Accepts requests whose `X-API-Key` header matches one configured key
+pub struct StaticApiKeyAuth {
+    key: Arc<str>,
+}
+
+impl StaticApiKeyAuth {
+    /// Principal of every request this backend accepts; the key names no one in particular
+    pub const PRINCIPAL: &'static str = "api-key";
+
+    pub fn new(key: impl Into<Arc<str>>) -> Self {
+        Self { key: key.into() }
+    }
+}
+
+#[async_trait]
+impl Authenticator for StaticApiKeyAuth {
+    async fn authenticate(&self, headers: &HeaderMap) -> Result<AuthContext, AuthError> {
+        let presented = headers.get(API_KEY_HEADER).ok_or(AuthError::Missing("X-API-Key header"))?;
+        if !key_matches(presented.as_bytes(), self.key.as_bytes()) {
+            return Err(AuthError::Invalid("X-API-Key header"));
+        }
+        Ok(AuthContext {
+            principal: Self::PRINCIPAL.into(),
+        })
+    }
+}
+
+/// Configured authenticator; `None` leaves every route open
+#[derive(Clone, Default)]
+pub struct Auth(pub Option<Arc<dyn Authenticator>>);
+
+impl Auth {
+    /// `StaticApiKeyAuth` with `key`, or no authentication without one
+    pub fn api_key(key: Option<&str>) -> Self {
+        Self(key.map(|key| Arc::new(StaticApiKeyAuth::new(key)) as Arc<dyn Authenticator>))
+    }
+}
+
+fn is_protected(path: &str) -> bool {
+    PROTECTED_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix) || path == prefix.trim_end_matches('/'))
+}
+
+/// Compare keys without short-circuiting on the first differing byte
+fn key_matches(presented: &[u8], expected: &[u8]) -> bool {
+    presented.ct_eq(expected).into()
+}
+
+/// Reject protected requests the authenticator doesn't accept, and hand the accepted ones'
+/// `AuthContext` on. CORS preflights pass through so browsers can discover the header is allowed.
+pub async fn require_auth(State(auth): State<Auth>, mut req: Request, next: Next) -> Response {
+    let Some(authenticator) = auth.0 else {
+        return next.run(req).await;
+    };
+
+    // Nested routers see a stripped URI; check the path the client actually sent
+    let path = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_owned())
+        .unwrap_or_else(|| req.uri().path().to_owned());
+
+    // Plain `OPTIONS` is a real request (`/wolfapi` forwards it to Wolf), so only preflights skip auth
+    if super::cors::is_preflight(&req) || !is_protected(&path) {
+        return next.run(req).await;
+    }
+
+    match authenticator.authenticate(req.headers()).await {
+        Ok(context) => {
+            req.extensions_mut().insert(context);
+            next.run(req).await
+        }
+        Err(e) => error_response(StatusCode::UNAUTHORIZED, "Unauthorized", &e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Extension, Router};
+    use http::{HeaderValue, Method, Request};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    fn app(key: Option<&str>) -> Router {
+        let wolf = Router::new().route("/_ready", get(|| async { "ready" }));
+        Router::new()
+            .route("/healthz", get(|| async { "ok" }))
+            .route("/api/v1/ping", get(|| async { "pong" }).options(|| async { "" }))
+            .route(
+                "/api/v1/whoami",
+                get(|context: Option<Extension<AuthContext>>| async move {
+                    context.map(|Extension(context)| context.principal).unwrap_or_default()
+                }),
+            )
+            .nest("/wolfapi", wolf)
+            .layer(axum::middleware::from_fn_with_state(Auth::api_key(key), require_auth))
+    }
+
+    fn headers(key: Option<&'static str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = key {
+            headers.insert(API_KEY_HEADER, HeaderValue::from_static(key));
+        }
+        headers
+    }
+
+    async fn status(app: &Router, method: Method, uri: &str, key: Option<&str>) -> StatusCode {
+        send(app, Request::builder().method(method).uri(uri), key).await
+    }
+
+    async fn send(app: &Router, mut req: http::request::Builder, key: Option<&str>) -> StatusCode {
+        if let Some(key) = key {
+            req = req.header(API_KEY_HEADER, key);
+        }
+        let resp = app.clone().oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        resp.status()
+    }
+
+    #[tokio::test]
+    async fn test_static_api_key_auth() {
+        let auth = StaticApiKeyAuth::new("s3cret");
+        assert_eq!(
+            auth.authenticate(&headers(Some("s3cret"))).await,
+            Ok(AuthContext {
+                principal: "api-key".into()
+            })
+        );
+        assert_eq!(
+            auth.authenticate(&headers(None)).await,
+            Err(AuthError::Missing("X-API-Key header"))
+        );
+        for wrong in ["s3cre", "s3cret!", ""] {
+            assert_eq!(
+                auth.authenticate(&headers(Some(wrong))).await,
+                Err(AuthError::Invalid("X-API-Key header")),
+                "{wrong:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_context_reaches_handlers() {
+        let req = |key: Option<&str>| {
+            let mut req = Request::get("/api/v1/whoami");
+            if let Some(key) = key {
+                req = req.header(API_KEY_HEADER, key);
+            }
+            req.body(Body::empty()).unwrap()
+        };
+        let resp = app(Some("s3cret")).oneshot(req(Some("s3cret"))).await.unwrap();
+        assert_eq!(&resp.into_body().collect().await.unwrap().to_bytes()[..], b"api-key");
+
+        let resp = app(Some("s3cret")).oneshot(req(None)).await.unwrap();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["detail"], "missing X-API-Key header");
+
+        // With auth off nothing is authenticated, so there is no context
+        let resp = app(None).oneshot(req(None)).await.unwrap();
+        assert!(resp.into_body().collect().await.unwrap().to_bytes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_key_rejected() {
+        let app = app(Some("s3cret"));
+        assert_eq!(status(&app, Method::GET, "/api/v1/ping", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&app, Method::GET, "/wolfapi/_ready", None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_key_rejected() {
+        let app = app(Some("s3cret"));
+        assert_eq!(
+            status(&app, Method::GET, "/api/v1/ping", Some("s3cre")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(&app, Method::GET, "/api/v1/ping", Some("s3cret!")).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_correct_key_accepted() {
+        let app = app(Some("s3cret"));
+        assert_eq!(status(&app, Method::GET, "/api/v1/ping", Some("s3cret")).await, StatusCode::OK);
+        assert_eq!(status(&app, Method::GET, "/wolfapi/_ready", Some("s3cret")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_public_routes_and_preflight_stay_open() {
+        let app = app(Some("s3cret"));
+        assert_eq!(status(&app, Method::GET, "/healthz", None).await, StatusCode::OK);
+        let preflight = Request::options("/api/v1/ping").header(http::header::ACCESS_CONTROL_REQUEST_METHOD, "GET");
+        assert_eq!(send(&app, preflight, None).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_plain_options_needs_the_key() {
+        let app = app(Some("s3cret"));
+        assert_eq!(status(&app, Method::OPTIONS, "/api/v1/ping", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&app, Method::OPTIONS, "/wolfapi/_ready", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&app, Method::OPTIONS, "/api/v1/ping", Some("s3cret")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_no_key_configured_leaves_routes_open() {
+        let app = app(None);
+        assert_eq!(status(&app, Method::GET, "/api/v1/ping", None).await, StatusCode::OK);
+    }
+}
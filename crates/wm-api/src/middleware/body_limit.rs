@@ -0,0 +1,97 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    response::{IntoResponse, Response},
+    Router,
+};
+use http::{header, StatusCode};
+use tower_http::limit::RequestBodyLimitLayer;
+use wm_adapters::wolf_proxy::error_response;
+
+/// Cap request bodies on every route of `router` at `limit` bytes.
+/// Applied per router, so streaming routes that need a different cap (or none)
+/// can live on a router this isn't applied to.
+pub fn limit_body<S>(router: Router<S>, limit: usize) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        // Extractors (Json, Bytes) default to 2 MiB; align them with the configured cap
+        .layer(DefaultBodyLimit::max(limit))
+        .layer(RequestBodyLimitLayer::new(limit))
+        .layer(axum::middleware::map_response(move |resp: Response| async move {
+            json_payload_too_large(resp, limit)
+        }))
+}
+
+/// Rewrite plain-text 413s from the limit layer or extractors into our JSON error shape
+fn json_payload_too_large(resp: Response, limit: usize) -> Response {
+    let is_json = resp
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes().starts_with(b"application/json"));
+
+    if resp.status() != StatusCode::PAYLOAD_TOO_LARGE || is_json {
+        return resp;
+    }
+
+    error_response(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "PayloadTooLarge",
+        &format!("request body exceeds limit of {} bytes", limit),
+    )
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::post};
+    use axum::body::Bytes;
+    use futures_util::stream;
+    use http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    const LIMIT: usize = 1024;
+
+    fn app() -> Router {
+        limit_body(
+            Router::new().route("/echo", post(|body: Bytes| async move { body.len().to_string() })),
+            LIMIT,
+        )
+    }
+
+    async fn post_body(body: Body, len: Option<usize>) -> (StatusCode, String) {
+        let mut req = Request::post("/echo");
+        if let Some(len) = len {
+            req = req.header(header::CONTENT_LENGTH, len);
+        }
+        let resp = app().oneshot(req.body(body).unwrap()).await.unwrap();
+        let status = resp.status();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_body_under_limit_accepted() {
+        let (status, body) = post_body(Body::from(vec![b'a'; LIMIT]), Some(LIMIT)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, LIMIT.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_declared_body_over_limit_rejected() {
+        let (status, body) = post_body(Body::from(vec![b'a'; LIMIT + 1]), Some(LIMIT + 1)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["error"], "PayloadTooLarge");
+    }
+
+    #[tokio::test]
+    async fn test_streamed_body_over_limit_rejected() {
+        let chunks = vec![Ok::<_, std::io::Error>(Bytes::from(vec![b'a'; LIMIT])), Ok(Bytes::from_static(b"!"))];
+        let (status, body) = post_body(Body::from_stream(stream::iter(chunks)), None).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(body.contains("PayloadTooLarge"), "{body}");
+    }
+}
@@ -0,0 +1,67 @@
+use http::{header, Extensions, HeaderMap, StatusCode, Version};
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+/// Bodies already encoded upstream (e.g. gzip from Wolf) must pass through untouched
+fn not_already_encoded(_: StatusCode, _: Version, headers: &HeaderMap, _: &Extensions) -> bool {
+    !headers.contains_key(header::CONTENT_ENCODING)
+}
+
+/// gzip/deflate/br per `Accept-Encoding`.
+/// SSE is excluded so frames and keep-alives flush immediately instead of sitting in the encoder.
+pub fn compression_layer() -> CompressionLayer<impl Predicate> {
+    let predicate = SizeAbove::new(256)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE)
+        .and(not_already_encoded);
+
+    CompressionLayer::new()
+        .gzip(true)
+        .deflate(true)
+        .br(true)
+        .compress_when(predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use http::Request;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        let big = "x".repeat(4096);
+        let encoded = big.clone();
+        Router::new()
+            .route("/plain", get(move || async move { big }))
+            .route(
+                "/encoded",
+                get(move || async move { ([(header::CONTENT_ENCODING, "identity-ish")], encoded) }),
+            )
+            .layer(compression_layer())
+    }
+
+    async fn encoding(uri: &str) -> Option<String> {
+        let req = Request::get(uri)
+            .header(header::ACCEPT_ENCODING, "br")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app().oneshot(req).await.unwrap();
+        resp.headers()
+            .get(header::CONTENT_ENCODING)
+            .map(|v| v.to_str().unwrap().to_owned())
+    }
+
+    #[tokio::test]
+    async fn test_compresses_plain_responses() {
+        assert_eq!(encoding("/plain").await.as_deref(), Some("br"));
+    }
+
+    #[tokio::test]
+    async fn test_leaves_encoded_responses_alone() {
+        assert_eq!(encoding("/encoded").await.as_deref(), Some("identity-ish"));
+    }
+}
@@ -1,7 +1,89 @@
-use http::HeaderValue;
-use std::net::{IpAddr, Ipv4Addr};
-use url::Url;
-use tracing::info;
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+    Router,
+};
+use http::{header, request::Parts, uri::Authority, HeaderValue, Method};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use url::{Host, Url};
+use tower_service::Service;
+use tracing::{info, warn};
+
+/// Whether `req` is a CORS preflight: an `OPTIONS` naming the method it asks about
+pub fn is_preflight(req: &Request) -> bool {
+    req.method() == Method::OPTIONS && req.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+/// Laid outside the CORS layer, which answers every `OPTIONS` as a preflight: any other
+/// `OPTIONS` goes straight to `app` (the same routes, without CORS), so a real route can
+/// report its methods in `Allow` and an unknown path gets its 404.
+pub async fn route_plain_options(State(mut app): State<Router>, req: Request, next: Next) -> Response {
+    if req.method() != Method::OPTIONS || is_preflight(&req) {
+        return next.run(req).await;
+    }
+    match app.call(req).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
+
+/// Operational endpoints, which follow `WM_CORS_OPS_POLICY` rather than the API's policy
+pub const OPS_PATHS: &[&str] = &["/healthz", "/readyz", "/metrics"];
+
+/// Which origins a route group's CORS layer allows
+#[derive(Clone)]
+pub enum CorsPolicy {
+    /// The LAN-first rules of [`origin_allowed`], against these detected addresses
+    Detected(LocalIps),
+    /// Only an origin naming the host the request was sent to; in effect no cross-origin access
+    SameOrigin,
+}
+
+/// Routes answering under their own CORS layer instead of the default one.
+/// `router` is the app wrapped in that layer.
+#[derive(Clone)]
+pub struct CorsGroup {
+    pub paths: &'static [&'static str],
+    pub router: Router,
+}
+
+/// Laid over the app wrapped in the default CORS layer: requests for one of the group's
+/// paths go to the group's router, so they get its CORS headers and preflights instead.
+pub async fn route_cors_group(State(mut group): State<CorsGroup>, req: Request, next: Next) -> Response {
+    if !group.paths.contains(&req.uri().path()) {
+        return next.run(req).await;
+    }
+    match group.router.call(req).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
+
+/// Whether `origin` names the host and port the request was addressed to (its `Host`
+/// header). A `Host` without a port stands for the origin scheme's default one.
+pub fn same_origin(origin: &HeaderValue, req: &Parts) -> bool {
+    let Some(url) = origin.to_str().ok().and_then(|o| Url::parse(o).ok()) else {
+        return false;
+    };
+    let Some(host) = req
+        .headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.parse::<Authority>().ok())
+    else {
+        return false;
+    };
+    let ports_match = match host.port_u16() {
+        Some(port) => url.port_or_known_default() == Some(port),
+        None => url.port().is_none(),
+    };
+    ports_match && url.host_str().is_some_and(|origin_host| origin_host.eq_ignore_ascii_case(host.host()))
+}
 
 /// Check if an IPv4 address is in a private range
 fn is_private_ipv4(ip: &Ipv4Addr) -> bool {
@@ -9,21 +91,110 @@ fn is_private_ipv4(ip: &Ipv4Addr) -> bool {
     (a == 10) || (a == 172 && (16..=31).contains(&b)) || (a == 192 && b == 168)
 }
 
-/// Detect local non-loopback IPv4 addresses at startup
-pub fn detect_local_ips() -> Vec<Ipv4Addr> {
+/// Check if an IPv6 address is unique local (fc00::/7) or link-local (fe80::/10)
+fn is_private_ipv6(ip: &Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+}
+
+fn is_private_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_private_ipv4(v4),
+        IpAddr::V6(v6) => is_private_ipv6(v6),
+    }
+}
+
+/// Local interface addresses allowed as same-machine CORS origins.
+/// Cheap to clone; clones share the list, which `refresh` replaces in place.
+#[derive(Clone, Default)]
+pub struct LocalIps(Arc<RwLock<Vec<IpAddr>>>);
+
+impl LocalIps {
+    /// Detect the current addresses once
+    pub fn detect() -> Self {
+        let ips = detect_local_ips();
+        for ip in &ips {
+            info!("Detected local IP for CORS: {}", ip);
+        }
+        Self(Arc::new(RwLock::new(ips)))
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, Vec<IpAddr>> {
+        self.0.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Re-detect addresses, e.g. after a DHCP lease changes them
+    pub fn refresh(&self) {
+        let ips = detect_local_ips();
+        let mut current = self.0.write().unwrap_or_else(|e| e.into_inner());
+        if *current != ips {
+            info!(ips = ?ips, "Local IPs for CORS changed");
+            *current = ips;
+        }
+    }
+
+    /// Re-detect addresses every `every` for the life of the runtime
+    pub fn spawn_refresh(&self, every: Duration) {
+        let ips = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval.tick().await; // The first tick fires immediately; detection just ran
+            loop {
+                interval.tick().await;
+                // Interface enumeration is a blocking syscall
+                let refresh = ips.clone();
+                let _ = tokio::task::spawn_blocking(move || refresh.refresh()).await;
+            }
+        });
+    }
+}
+
+/// Detect every local non-loopback IPv4 and IPv6 interface address
+pub fn detect_local_ips() -> Vec<IpAddr> {
+    let interfaces = if_addrs::get_if_addrs()
+        .map(|interfaces| interfaces.into_iter().map(|iface| iface.ip()).collect());
+    if let Err(e) = &interfaces {
+        warn!("Failed to enumerate network interfaces for CORS, probing default route: {}", e);
+    }
+    local_ips_from(interfaces, probe_route_ips)
+}
+
+/// Keep usable interface addresses, falling back to `probe` if enumeration fails or finds none
+fn local_ips_from(
+    interfaces: io::Result<Vec<IpAddr>>,
+    probe: impl FnOnce() -> Vec<IpAddr>,
+) -> Vec<IpAddr> {
+    let mut ips: Vec<IpAddr> = interfaces
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+        .collect();
+    ips.sort();
+    ips.dedup();
+
+    if ips.is_empty() {
+        probe()
+    } else {
+        ips
+    }
+}
+
+/// Find the interface addresses used for the default IPv4 and IPv6 routes
+fn probe_route_ips() -> Vec<IpAddr> {
     use std::net::UdpSocket;
 
     let mut ips = Vec::new();
 
     // Technique: Connect UDP socket to a public IP (doesn't actually send)
     // to determine which local interface would be used
-    if let Ok(socket) = UdpSocket::bind("0.0.0.0:0") {
-        if let Ok(()) = socket.connect("8.8.8.8:80") {
-            if let Ok(addr) = socket.local_addr() {
-                if let IpAddr::V4(v4) = addr.ip() {
-                    if !v4.is_loopback() {
-                        ips.push(v4);
-                        info!("Detected local IP for CORS: {}", v4);
+    for (bind, target) in [("0.0.0.0:0", "8.8.8.8:80"), ("[::]:0", "[2001:4860:4860::8888]:80")] {
+        if let Ok(socket) = UdpSocket::bind(bind) {
+            if let Ok(()) = socket.connect(target) {
+                if let Ok(addr) = socket.local_addr() {
+                    let ip = addr.ip();
+                    if !ip.is_loopback() && !ip.is_unspecified() {
+                        ips.push(ip);
                     }
                 }
             }
@@ -33,17 +204,52 @@ pub fn detect_local_ips() -> Vec<Ipv4Addr> {
     ips
 }
 
+/// Check an origin against a configured origin: an exact `scheme://host[:port]`,
+/// or `scheme://*.domain[:port]` matching any subdomain (but not the bare domain)
+fn origin_matches(url: &Url, pattern: &str) -> bool {
+    let pattern = pattern.trim().trim_end_matches('/');
+
+    let Some((scheme, rest)) = pattern.split_once("://") else {
+        return false;
+    };
+    if let Some(suffix) = rest.strip_prefix("*.") {
+        // Swap the wildcard for a placeholder label so the URL parser normalizes host and port
+        let Ok(p) = Url::parse(&format!("{}://wildcard.{}", scheme, suffix)) else {
+            return false;
+        };
+        let (Some(origin_host), Some(base)) = (url.host_str(), p.host_str()) else {
+            return false;
+        };
+        let base = &base["wildcard".len()..];
+        return p.scheme() == url.scheme()
+            && p.port_or_known_default() == url.port_or_known_default()
+            && origin_host.len() > base.len()
+            && origin_host.ends_with(base);
+    }
+
+    match Url::parse(pattern) {
+        Ok(p) => {
+            p.scheme() == url.scheme()
+                && p.host_str() == url.host_str()
+                && p.port_or_known_default() == url.port_or_known_default()
+        }
+        Err(_) => false,
+    }
+}
+
 /// Determine if an origin is allowed based on CORS policy
 ///
 /// This function checks the browser's Origin header against:
-/// 1. Exact match with PUBLIC_URL (if configured)
+/// 1. Exact match with PUBLIC_URL or any `WM_CORS_ALLOWED_ORIGINS` entry
+///    (entries may use a `*.` subdomain wildcard)
 /// 2. Exact match with detected local IPs (allows same-machine access)
 /// 3. Localhost/loopback addresses (always allowed for dev)
-/// 4. Private IPv4 ranges (if `allow_private` is true)
+/// 4. Private IPv4 ranges and IPv6 unique-local/link-local addresses (if `allow_private` is true)
 pub fn origin_allowed(
     origin: &HeaderValue,
     public_url: Option<&str>,
-    local_ips: &[Ipv4Addr],
+    allowed_origins: &[String],
+    local_ips: &[IpAddr],
     allow_private: bool,
 ) -> bool {
     // Parse the origin header
@@ -62,20 +268,22 @@ pub fn origin_allowed(
         None => return false,
     };
 
-    // 1) Exact match with PUBLIC_URL
-    if let Some(pub_url) = public_url {
-        if let Ok(p) = Url::parse(pub_url) {
-            if p.scheme() == url.scheme()
-                && p.host_str() == url.host_str()
-                && p.port_or_known_default() == url.port_or_known_default()
-            {
-                return true;
-            }
-        }
+    // 1) Exact match with PUBLIC_URL or the configured allowlist
+    if public_url.is_some_and(|p| origin_matches(&url, p))
+        || allowed_origins.iter().any(|p| origin_matches(&url, p))
+    {
+        return true;
     }
 
+    // IPv6 hosts come back bracketed from host_str, so take the parsed form
+    let origin_ip = match url.host() {
+        Some(Host::Ipv4(v4)) => Some(IpAddr::V4(v4)),
+        Some(Host::Ipv6(v6)) => Some(IpAddr::V6(v6)),
+        _ => None,
+    };
+
     // 2) Check if origin matches any detected local IP (any port allowed)
-    if let Ok(IpAddr::V4(origin_ip)) = host.parse::<IpAddr>() {
+    if let Some(origin_ip) = origin_ip {
         if local_ips.contains(&origin_ip) {
             return true;
         }
@@ -90,10 +298,10 @@ pub fn origin_allowed(
         return true;
     }
 
-    // 4) Optional: All private IPv4 ranges (LAN-wide convenience)
+    // 4) Optional: All private ranges (LAN-wide convenience)
     if allow_private {
-        if let Ok(IpAddr::V4(v4)) = host.parse::<IpAddr>() {
-            if is_private_ipv4(&v4) {
+        if let Some(ip) = origin_ip {
+            if is_private_ip(&ip) {
                 return true;
             }
         }
@@ -112,13 +320,13 @@ mod tests {
         let local_ips = vec![];
 
         let origin = HeaderValue::from_static("http://localhost:3000");
-        assert!(origin_allowed(&origin, None, &local_ips, false));
+        assert!(origin_allowed(&origin, None, &[], &local_ips, false));
 
         let origin = HeaderValue::from_static("http://127.0.0.1:5173");
-        assert!(origin_allowed(&origin, None, &local_ips, false));
+        assert!(origin_allowed(&origin, None, &[], &local_ips, false));
 
         let origin = HeaderValue::from_static("http://[::1]:8080");
-        assert!(origin_allowed(&origin, None, &local_ips, false));
+        assert!(origin_allowed(&origin, None, &[], &local_ips, false));
     }
 
     #[test]
@@ -129,6 +337,7 @@ mod tests {
         assert!(origin_allowed(
             &origin,
             Some("https://app.example.com"),
+            &[],
             &local_ips,
             false
         ));
@@ -138,6 +347,7 @@ mod tests {
         assert!(!origin_allowed(
             &origin,
             Some("https://app.example.com"),
+            &[],
             &local_ips,
             false
         ));
@@ -147,6 +357,7 @@ mod tests {
         assert!(!origin_allowed(
             &origin,
             Some("https://app.example.com"),
+            &[],
             &local_ips,
             false
         ));
@@ -158,18 +369,18 @@ mod tests {
 
         // 192.168.x.x
         let origin = HeaderValue::from_static("http://192.168.1.50:5173");
-        assert!(origin_allowed(&origin, None, &local_ips, true));
-        assert!(!origin_allowed(&origin, None, &local_ips, false));
+        assert!(origin_allowed(&origin, None, &[], &local_ips, true));
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
 
         // 10.x.x.x
         let origin = HeaderValue::from_static("http://10.0.0.1:3000");
-        assert!(origin_allowed(&origin, None, &local_ips, true));
-        assert!(!origin_allowed(&origin, None, &local_ips, false));
+        assert!(origin_allowed(&origin, None, &[], &local_ips, true));
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
 
         // 172.16-31.x.x
         let origin = HeaderValue::from_static("http://172.20.0.1:8080");
-        assert!(origin_allowed(&origin, None, &local_ips, true));
-        assert!(!origin_allowed(&origin, None, &local_ips, false));
+        assert!(origin_allowed(&origin, None, &[], &local_ips, true));
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
     }
 
     #[test]
@@ -177,8 +388,8 @@ mod tests {
         let local_ips = vec![];
 
         let origin = HeaderValue::from_static("http://1.2.3.4:5173");
-        assert!(!origin_allowed(&origin, None, &local_ips, false));
-        assert!(!origin_allowed(&origin, None, &local_ips, true));
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, true));
     }
 
     #[test]
@@ -186,24 +397,162 @@ mod tests {
         let local_ips = vec![];
 
         let origin = HeaderValue::from_static("not-a-url");
-        assert!(!origin_allowed(&origin, None, &local_ips, false));
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
     }
 
     #[test]
     fn test_detected_local_ip_allowed() {
         // Simulate detected local IP
-        let local_ips = vec![Ipv4Addr::new(192, 168, 1, 100)];
+        let local_ips = vec![IpAddr::V4(Ipv4Addr::new(192, 168, 1, 100))];
 
         // Origin matching detected IP should be allowed (any port)
         let origin = HeaderValue::from_static("http://192.168.1.100:5173");
-        assert!(origin_allowed(&origin, None, &local_ips, false));
+        assert!(origin_allowed(&origin, None, &[], &local_ips, false));
 
         let origin = HeaderValue::from_static("http://192.168.1.100:3000");
-        assert!(origin_allowed(&origin, None, &local_ips, false));
+        assert!(origin_allowed(&origin, None, &[], &local_ips, false));
 
         // Different IP should not match (unless allow_private is true)
         let origin = HeaderValue::from_static("http://192.168.1.200:5173");
-        assert!(!origin_allowed(&origin, None, &local_ips, false));
-        assert!(origin_allowed(&origin, None, &local_ips, true)); // allowed via private range
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
+        assert!(origin_allowed(&origin, None, &[], &local_ips, true)); // allowed via private range
+    }
+
+    #[test]
+    fn test_ipv6_unique_local_when_allowed() {
+        let local_ips = vec![];
+
+        let origin = HeaderValue::from_static("http://[fd00::1]:5173");
+        assert!(origin_allowed(&origin, None, &[], &local_ips, true));
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
+
+        let origin = HeaderValue::from_static("http://[fc12:3456::abcd]:3000");
+        assert!(origin_allowed(&origin, None, &[], &local_ips, true));
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
+    }
+
+    #[test]
+    fn test_ipv6_link_local_when_allowed() {
+        let local_ips = vec![];
+
+        let origin = HeaderValue::from_static("http://[fe80::1ff:fe23:4567:890a]:5173");
+        assert!(origin_allowed(&origin, None, &[], &local_ips, true));
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
+    }
+
+    #[test]
+    fn test_ipv6_public_not_allowed() {
+        let local_ips = vec![];
+
+        let origin = HeaderValue::from_static("http://[2001:db8::1]:5173");
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, true));
+    }
+
+    #[test]
+    fn test_detected_local_ipv6_allowed() {
+        let local_ips = vec!["2001:db8::42".parse().unwrap()];
+
+        let origin = HeaderValue::from_static("http://[2001:db8::42]:5173");
+        assert!(origin_allowed(&origin, None, &[], &local_ips, false));
+
+        let origin = HeaderValue::from_static("http://[2001:db8::43]:5173");
+        assert!(!origin_allowed(&origin, None, &[], &local_ips, false));
+    }
+
+    #[test]
+    fn test_allowed_origins_exact_match() {
+        let local_ips = vec![];
+        let allowed = vec![
+            "https://example.com".to_string(),
+            "https://www.example.com".to_string(),
+            "https://admin.example.net:8443".to_string(),
+        ];
+
+        for origin in ["https://example.com", "https://www.example.com", "https://admin.example.net:8443"] {
+            let origin = HeaderValue::from_str(origin).unwrap();
+            assert!(origin_allowed(&origin, None, &allowed, &local_ips, false));
+        }
+
+        // Scheme, port, and host must all match
+        for origin in ["http://example.com", "https://admin.example.net", "https://api.example.com"] {
+            let origin = HeaderValue::from_str(origin).unwrap();
+            assert!(!origin_allowed(&origin, None, &allowed, &local_ips, false));
+        }
+    }
+
+    #[test]
+    fn test_allowed_origins_wildcard_subdomain() {
+        let local_ips = vec![];
+        let allowed = vec!["https://*.example.com".to_string()];
+
+        let origin = HeaderValue::from_static("https://app.example.com");
+        assert!(origin_allowed(&origin, None, &allowed, &local_ips, false));
+
+        let origin = HeaderValue::from_static("https://a.b.example.com");
+        assert!(origin_allowed(&origin, None, &allowed, &local_ips, false));
+
+        // The bare domain, other schemes, look-alike domains, and other ports don't match
+        for origin in [
+            "https://example.com",
+            "http://app.example.com",
+            "https://app.notexample.com",
+            "https://app.example.com.evil.io",
+            "https://app.example.com:8443",
+        ] {
+            let origin = HeaderValue::from_str(origin).unwrap();
+            assert!(!origin_allowed(&origin, None, &allowed, &local_ips, false), "{origin:?}");
+        }
+    }
+
+    #[test]
+    fn test_same_origin_matches_host_header() {
+        let parts = |host: &str| {
+            let (parts, ()) = http::Request::get("/healthz").header(header::HOST, host).body(()).unwrap().into_parts();
+            parts
+        };
+        let allowed = |origin: &'static str, host: &str| same_origin(&HeaderValue::from_static(origin), &parts(host));
+
+        assert!(allowed("http://wm.lan:3000", "wm.lan:3000"));
+        assert!(allowed("https://WM.lan", "wm.lan"));
+        assert!(allowed("https://wm.lan", "wm.lan:443"));
+        assert!(allowed("http://[fd00::1]:3000", "[fd00::1]:3000"));
+
+        assert!(!allowed("http://wm.lan:5173", "wm.lan:3000"));
+        assert!(!allowed("http://wm.lan:3000", "wm.lan"));
+        assert!(!allowed("http://localhost:3000", "wm.lan:3000"));
+        assert!(!allowed("null", "wm.lan:3000"));
+
+        let (no_host, ()) = http::Request::get("/healthz").body(()).unwrap().into_parts();
+        assert!(!same_origin(&HeaderValue::from_static("http://wm.lan"), &no_host));
+    }
+
+    #[test]
+    fn test_all_interface_ips_considered() {
+        let interfaces = vec![
+            "127.0.0.1".parse().unwrap(),
+            "192.168.1.100".parse().unwrap(),
+            "10.20.0.5".parse().unwrap(),
+            "2001:db8::42".parse().unwrap(),
+            "::1".parse().unwrap(),
+        ];
+        let local_ips = local_ips_from(Ok(interfaces), || panic!("probe should not run"));
+        assert_eq!(local_ips.len(), 3);
+
+        for origin in ["http://192.168.1.100:5173", "http://10.20.0.5:3000", "http://[2001:db8::42]:8080"] {
+            let origin = HeaderValue::from_str(origin).unwrap();
+            assert!(origin_allowed(&origin, None, &[], &local_ips, false), "{origin:?}");
+        }
+    }
+
+    #[test]
+    fn test_probe_fallback_when_enumeration_fails() {
+        let probed: IpAddr = "192.168.1.7".parse().unwrap();
+        let err = Err(io::Error::other("no interfaces"));
+        assert_eq!(local_ips_from(err, || vec![probed]), vec![probed]);
+
+        // Only loopback interfaces count as none
+        let loopback_only = Ok(vec!["127.0.0.1".parse().unwrap()]);
+        assert_eq!(local_ips_from(loopback_only, || vec![probed]), vec![probed]);
     }
 }
@@ -0,0 +1,126 @@
+use axum::{body::Body, extract::Request, middleware::Next, response::Response};
+use http::{header, HeaderValue};
+use wm_adapters::wolf_proxy::ErrorBody;
+
+/// Quality the `Accept` header gives `media` (`type/subtype`), from its most specific matching range
+fn quality(accept: &str, media: &str) -> f32 {
+    let (kind, _) = media.split_once('/').unwrap_or((media, ""));
+    let mut best: Option<(u8, f32)> = None;
+    for range in accept.split(',') {
+        let mut parts = range.split(';');
+        let range = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let specificity = if range == media {
+            2
+        } else if range.strip_suffix("/*") == Some(kind) {
+            1
+        } else if range == "*/*" {
+            0
+        } else {
+            continue;
+        };
+        let q = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if best.is_none_or(|(s, _)| specificity > s) {
+            best = Some((specificity, q));
+        }
+    }
+    best.map(|(_, q)| q).unwrap_or(0.0)
+}
+
+/// Whether the client ranks `text/plain` above JSON; ties and a missing header stay JSON
+fn prefers_text(accept: Option<&str>) -> bool {
+    accept.is_some_and(|a| quality(a, "text/plain") > quality(a, "application/json"))
+}
+
+/// Re-render errors from `error_response` as `text/plain` for clients whose `Accept` asks for it.
+/// The status and headers are kept; other responses pass through untouched.
+pub async fn negotiate_errors(req: Request, next: Next) -> Response {
+    let text = prefers_text(req.headers().get(header::ACCEPT).and_then(|v| v.to_str().ok()));
+    let mut response = next.run(req).await;
+    if !text {
+        return response;
+    }
+    let Some(error) = response.extensions_mut().remove::<ErrorBody>() else {
+        return response;
+    };
+
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain; charset=utf-8"));
+    headers.remove(header::CONTENT_LENGTH);
+    *response.body_mut() = Body::from(error.to_text());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use http::StatusCode;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use wm_adapters::wolf_proxy::error_response;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route(
+                "/fail",
+                get(|| async { error_response(StatusCode::BAD_GATEWAY, "UpstreamError", "boom") }),
+            )
+            .layer(axum::middleware::from_fn(negotiate_errors))
+    }
+
+    async fn call(uri: &str, accept: Option<&str>) -> (StatusCode, String, String) {
+        let mut req = http::Request::get(uri);
+        if let Some(accept) = accept {
+            req = req.header(header::ACCEPT, accept);
+        }
+        let resp = app().oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let status = resp.status();
+        let content_type = resp
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|v| v.to_str().unwrap().to_owned())
+            .unwrap_or_default();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, content_type, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_accept_text_plain_gets_text_error() {
+        let (status, content_type, body) = call("/fail", Some("text/plain")).await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert_eq!(content_type, "text/plain; charset=utf-8");
+        assert_eq!(body, "UpstreamError: boom\n");
+    }
+
+    #[tokio::test]
+    async fn test_accept_json_or_missing_gets_json_error() {
+        for accept in [Some("application/json"), None, Some("*/*")] {
+            let (status, content_type, body) = call("/fail", accept).await;
+            assert_eq!(status, StatusCode::BAD_GATEWAY, "{accept:?}");
+            assert_eq!(content_type, "application/json", "{accept:?}");
+            let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+            assert_eq!(body["error"], "UpstreamError");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_successful_responses_are_untouched() {
+        let (status, _, body) = call("/ok", Some("text/plain")).await;
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "ok"));
+    }
+
+    #[test]
+    fn test_prefers_text() {
+        assert!(prefers_text(Some("text/plain")));
+        assert!(prefers_text(Some("text/*, application/json;q=0.5")));
+        assert!(prefers_text(Some("application/json;q=0.1, */*")));
+        assert!(!prefers_text(Some("text/plain;q=0.5, application/json")));
+        assert!(!prefers_text(Some("text/html")));
+        assert!(!prefers_text(Some("*/*")));
+        assert!(!prefers_text(None));
+    }
+}
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::time::Instant;
+use tracing::{field, info_span, Instrument};
+
+pub const HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
+pub const HTTP_REQUEST_DURATION_SECONDS: &str = "http_request_duration_seconds";
+
+/// Label shared by every path the Wolf proxy forwards
+pub const WOLF_PROXY_ROUTE: &str = "/wolfapi/*";
+
+/// Register HTTP metric descriptions with the installed recorder
+pub fn describe() {
+    metrics::describe_counter!(HTTP_REQUESTS_TOTAL, "HTTP requests handled by wm-api");
+    metrics::describe_histogram!(
+        HTTP_REQUEST_DURATION_SECONDS,
+        metrics::Unit::Seconds,
+        "Time spent producing response headers, per route template"
+    );
+}
+
+/// Route template for labels and spans: the matched route, never the raw path.
+/// Everything forwarded to Wolf is one route; unmatched paths share one label so scanners
+/// can't blow up cardinality.
+fn route_label(req: &Request) -> String {
+    let matched = req.extensions().get::<MatchedPath>().map(|p| p.as_str());
+    let path = req.uri().path();
+    match matched {
+        Some("/wolfapi/_ready") => "/wolfapi/_ready".to_owned(),
+        _ if path == "/wolfapi" || path.starts_with("/wolfapi/") => WOLF_PROXY_ROUTE.to_owned(),
+        Some(route) => route.to_owned(),
+        None => "unmatched".to_owned(),
+    }
+}
+
+/// Record request count and latency per route, inside a span carrying the route template
+pub async fn track_http_metrics(req: Request, next: Next) -> Response {
+    let route = route_label(&req);
+    let method = req.method().to_string();
+    let span = info_span!("route", route = %route, latency_ms = field::Empty);
+    let start = Instant::now();
+
+    let response = next.run(req).instrument(span.clone()).await;
+
+    let elapsed = start.elapsed();
+    span.record("latency_ms", elapsed.as_millis() as u64);
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    metrics::counter!(HTTP_REQUESTS_TOTAL, &labels).increment(1);
+    metrics::histogram!(HTTP_REQUEST_DURATION_SECONDS, &labels).record(elapsed.as_secs_f64());
+
+    response
+}
+
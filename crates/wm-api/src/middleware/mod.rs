@@ -1 +1,12 @@
+pub mod access_log;
+pub mod auth;
+pub mod body_limit;
+pub mod compression;
 pub mod cors;
+pub mod error_format;
+pub mod metrics;
+pub mod rate_limit;
+pub mod request_id;
+pub mod response_headers;
+pub mod startup;
+pub mod timeout;
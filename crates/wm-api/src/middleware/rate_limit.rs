@@ -0,0 +1,252 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+    Router,
+};
+use dashmap::DashMap;
+use http::{header, HeaderMap, HeaderValue, StatusCode};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio::time::Instant;
+use tracing::warn;
+use wm_adapters::wolf_proxy::error_response;
+
+use crate::trusted_proxies::TrustedProxies;
+
+/// Above this many tracked clients, idle (fully refilled) buckets are dropped
+const SWEEP_THRESHOLD: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Sustained requests per second per client; `0` disables limiting
+    pub per_second: f64,
+    /// Requests a client may make at once before the rate applies
+    pub burst: u32,
+    /// Peers whose `X-Forwarded-For` is trusted to name the real client
+    pub trusted_proxies: TrustedProxies,
+    /// Clients that are never limited
+    pub exempt: Vec<IpAddr>,
+    pub exempt_loopback: bool,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket per client IP
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    buckets: Arc<DashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            buckets: Arc::new(DashMap::new()),
+        }
+    }
+
+    fn is_exempt(&self, ip: &IpAddr) -> bool {
+        (self.config.exempt_loopback && ip.is_loopback()) || self.config.exempt.contains(ip)
+    }
+
+    /// The client a request is attributed to: the peer, or for trusted proxies
+    /// the nearest `X-Forwarded-For` hop that isn't itself a trusted proxy
+    fn client_ip(&self, peer: SocketAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.config.trusted_proxies.is_trusted_peer(&peer) {
+            return peer.ip();
+        }
+        headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .filter_map(|hop| hop.trim().parse::<IpAddr>().ok())
+            .rev()
+            .find(|ip| !self.config.trusted_proxies.contains(ip))
+            .unwrap_or(peer.ip())
+    }
+
+    /// Take a token for `ip`, or return how many seconds until one is available
+    fn acquire(&self, ip: IpAddr) -> Result<(), u64> {
+        let now = Instant::now();
+        let burst = f64::from(self.config.burst.max(1));
+        let rate = self.config.per_second;
+
+        if self.buckets.len() > SWEEP_THRESHOLD {
+            self.buckets.retain(|_, b| {
+                b.tokens + now.duration_since(b.updated).as_secs_f64() * rate < burst
+            });
+        }
+
+        let mut bucket = self.buckets.entry(ip).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / rate).ceil().max(1.0) as u64)
+        }
+    }
+}
+
+/// Rate limit every route of `router` per client IP. No-op when the rate is zero.
+pub fn limit_rate<S>(router: Router<S>, config: RateLimitConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    if config.per_second <= 0.0 {
+        return router;
+    }
+    router.layer(axum::middleware::from_fn_with_state(
+        RateLimiter::new(config),
+        rate_limit,
+    ))
+}
+
+async fn rate_limit(State(limiter): State<RateLimiter>, req: Request, next: Next) -> Response {
+    let Some(ConnectInfo(peer)) = req.extensions().get::<ConnectInfo<SocketAddr>>().copied() else {
+        return next.run(req).await;
+    };
+    let ip = limiter.client_ip(peer, req.headers());
+    if limiter.is_exempt(&ip) {
+        return next.run(req).await;
+    }
+
+    match limiter.acquire(ip) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => {
+            warn!(client_ip = %ip, path = %req.uri().path(), "Rate limit exceeded");
+            let mut response = error_response(
+                StatusCode::TOO_MANY_REQUESTS,
+                "RateLimited",
+                &format!("too many requests; retry after {}s", retry_after),
+            );
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn config(per_second: f64, burst: u32) -> RateLimitConfig {
+        RateLimitConfig {
+            per_second,
+            burst,
+            trusted_proxies: TrustedProxies::default(),
+            exempt: vec![],
+            exempt_loopback: true,
+        }
+    }
+
+    fn app(config: RateLimitConfig) -> Router {
+        limit_rate(Router::new().route("/apps", get(|| async { "ok" })), config)
+    }
+
+    async fn call(app: &Router, peer: [u8; 4], forwarded_for: Option<&str>) -> Response {
+        let mut req = http::Request::get("/apps");
+        if let Some(xff) = forwarded_for {
+            req = req.header("x-forwarded-for", xff);
+        }
+        let mut req = req.body(Body::empty()).unwrap();
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((peer, 40000))));
+        app.clone().oneshot(req).await.unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_exhausted_bucket_returns_429_then_recovers() {
+        let app = app(config(2.0, 3));
+        let client = [192, 168, 1, 50];
+
+        for _ in 0..3 {
+            assert_eq!(call(&app, client, None).await.status(), StatusCode::OK);
+        }
+        let resp = call(&app, client, None).await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(resp.headers()[header::RETRY_AFTER], "1");
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/json");
+
+        // Other clients have their own bucket
+        assert_eq!(call(&app, [192, 168, 1, 51], None).await.status(), StatusCode::OK);
+
+        // Half a second refills one token at 2 req/s
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert_eq!(call(&app, client, None).await.status(), StatusCode::OK);
+        assert_eq!(call(&app, client, None).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_exempt_clients_not_limited() {
+        let mut config = config(1.0, 1);
+        config.exempt = vec!["10.0.0.9".parse().unwrap()];
+        let app = app(config);
+
+        for _ in 0..5 {
+            assert_eq!(call(&app, [127, 0, 0, 1], None).await.status(), StatusCode::OK);
+            assert_eq!(call(&app, [10, 0, 0, 9], None).await.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_forwarded_for_only_from_trusted_proxy() {
+        let mut config = config(1.0, 1);
+        config.trusted_proxies = TrustedProxies::new(vec!["10.0.0.0/30".parse().unwrap()]);
+        let app = app(config);
+        let proxy = [10, 0, 0, 1];
+
+        // Behind the trusted proxy, each forwarded client gets its own bucket
+        assert_eq!(call(&app, proxy, Some("203.0.113.7")).await.status(), StatusCode::OK);
+        assert_eq!(call(&app, proxy, Some("203.0.113.8")).await.status(), StatusCode::OK);
+        assert_eq!(
+            call(&app, proxy, Some("203.0.113.7")).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // A spoofed leftmost hop doesn't escape the limit
+        assert_eq!(
+            call(&app, proxy, Some("198.51.100.1, 203.0.113.8")).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // Other hops inside the trusted range are skipped to reach the client
+        assert_eq!(call(&app, proxy, Some("203.0.113.11, 10.0.0.2")).await.status(), StatusCode::OK);
+        assert_eq!(
+            call(&app, proxy, Some("203.0.113.11")).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // Untrusted peers can't pick their bucket
+        assert_eq!(call(&app, [192, 168, 1, 60], Some("203.0.113.9")).await.status(), StatusCode::OK);
+        assert_eq!(
+            call(&app, [192, 168, 1, 60], Some("203.0.113.10")).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_zero_rate_disables_limiting() {
+        let app = app(config(0.0, 1));
+        for _ in 0..5 {
+            assert_eq!(call(&app, [192, 168, 1, 50], None).await.status(), StatusCode::OK);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use http::HeaderValue;
+use tracing::{info_span, Instrument};
+use uuid::Uuid;
+use wm_adapters::request_id::{self, REQUEST_ID_HEADER};
+
+/// Correlation ID for the current request, available as an extension
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Accept client IDs that are short and header-safe; anything else is replaced
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_.:".contains(&b))
+}
+
+/// Reuse the client's `X-Request-Id` or mint one, then tag logs, errors and the response with it
+pub async fn request_id(mut req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| is_valid(v))
+        .map(str::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let header = HeaderValue::from_str(&id).expect("request id is header-safe");
+
+    req.headers_mut().insert(REQUEST_ID_HEADER, header.clone());
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let span = info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+    let mut response = request_id::scope(id.into(), next.run(req))
+        .instrument(span)
+        .await;
+
+    response.headers_mut().insert(REQUEST_ID_HEADER, header);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Extension, Router};
+    use http::StatusCode;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use wm_adapters::wolf_proxy::error_response;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/echo", get(|Extension(id): Extension<RequestId>| async move { id.0 }))
+            .route(
+                "/fail",
+                get(|| async { error_response(StatusCode::BAD_GATEWAY, "UpstreamError", "boom") }),
+            )
+            .layer(axum::middleware::from_fn(request_id))
+    }
+
+    async fn call(uri: &str, id: Option<&str>) -> (String, String) {
+        let mut req = http::Request::get(uri);
+        if let Some(id) = id {
+            req = req.header(REQUEST_ID_HEADER, id);
+        }
+        let resp = app().oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let header = resp.headers()[REQUEST_ID_HEADER].to_str().unwrap().to_owned();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (header, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_generates_id_when_missing() {
+        let (header, body) = call("/echo", None).await;
+        assert!(Uuid::parse_str(&header).is_ok(), "{header}");
+        assert_eq!(body, header);
+    }
+
+    #[tokio::test]
+    async fn test_preserves_supplied_id() {
+        let (header, body) = call("/echo", Some("abc-123")).await;
+        assert_eq!(header, "abc-123");
+        assert_eq!(body, "abc-123");
+    }
+
+    #[tokio::test]
+    async fn test_replaces_unsafe_id() {
+        let (header, _) = call("/echo", Some("has spaces")).await;
+        assert!(Uuid::parse_str(&header).is_ok(), "{header}");
+    }
+
+    #[tokio::test]
+    async fn test_error_body_carries_id() {
+        let (_, body) = call("/fail", Some("abc-123")).await;
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["request_id"], "abc-123");
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Context, Result};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use http::{HeaderName, HeaderValue};
+use std::sync::Arc;
+
+/// Sent on every response unless `WM_SECURITY_HEADERS=false`
+pub const SECURITY_HEADERS: [(&str, &str); 3] = [
+    ("x-content-type-options", "nosniff"),
+    ("referrer-policy", "no-referrer"),
+    ("x-frame-options", "DENY"),
+];
+
+/// Static headers added to every response
+#[derive(Debug, Clone, Default)]
+pub struct ResponseHeaders(Arc<[(HeaderName, HeaderValue)]>);
+
+impl ResponseHeaders {
+    /// The security defaults (when `security` is set), then `csp` as `Content-Security-Policy`,
+    /// then each `Name: value` override in turn. An override replaces an earlier header of
+    /// the same name; `Name:` with nothing after it removes it.
+    pub fn new(security: bool, csp: Option<&str>, overrides: &[String]) -> Result<Self> {
+        let mut headers: Vec<(HeaderName, HeaderValue)> = Vec::new();
+        let mut set = |name: HeaderName, value: Option<HeaderValue>| {
+            headers.retain(|(existing, _)| *existing != name);
+            if let Some(value) = value {
+                headers.push((name, value));
+            }
+        };
+
+        if security {
+            for (name, value) in SECURITY_HEADERS {
+                set(HeaderName::from_static(name), Some(HeaderValue::from_static(value)));
+            }
+        }
+        if let Some(csp) = csp {
+            let value = HeaderValue::from_str(csp).context("invalid WM_CONTENT_SECURITY_POLICY")?;
+            set(http::header::CONTENT_SECURITY_POLICY, Some(value));
+        }
+        for entry in overrides {
+            let (name, value) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("WM_RESPONSE_HEADERS entry {:?} is not `Name: value`", entry))?;
+            let name = HeaderName::from_bytes(name.trim().as_bytes())
+                .with_context(|| format!("invalid header name in WM_RESPONSE_HEADERS entry {:?}", entry))?;
+            let value = match value.trim() {
+                "" => None,
+                value => Some(
+                    HeaderValue::from_str(value)
+                        .with_context(|| format!("invalid header value in WM_RESPONSE_HEADERS entry {:?}", entry))?,
+                ),
+            };
+            set(name, value);
+        }
+
+        Ok(Self(headers.into()))
+    }
+}
+
+/// Add the configured headers to every response. A header the response already carries,
+/// such as one Wolf sent through `/wolfapi`, is left alone rather than doubled up.
+pub async fn inject_response_headers(
+    State(headers): State<ResponseHeaders>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+    for (name, value) in headers.0.iter() {
+        response.headers_mut().entry(name).or_insert_with(|| value.clone());
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use http::{header, HeaderMap};
+    use tower::ServiceExt;
+
+    async fn call(headers: ResponseHeaders, uri: &str) -> HeaderMap {
+        let app = Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route("/framed", get(|| async { ([(header::X_FRAME_OPTIONS, "SAMEORIGIN")], "wolf") }))
+            .layer(axum::middleware::from_fn_with_state(headers, inject_response_headers));
+        let resp = app.oneshot(http::Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+        resp.headers().clone()
+    }
+
+    #[tokio::test]
+    async fn test_security_defaults() {
+        let headers = call(ResponseHeaders::new(true, None, &[]).unwrap(), "/ok").await;
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::REFERRER_POLICY], "no-referrer");
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert!(!headers.contains_key(header::CONTENT_SECURITY_POLICY), "CSP is opt-in");
+
+        let headers = call(ResponseHeaders::new(false, None, &[]).unwrap(), "/ok").await;
+        assert!(!headers.contains_key(header::X_CONTENT_TYPE_OPTIONS));
+    }
+
+    #[tokio::test]
+    async fn test_override_replaces_default() {
+        let overrides = [
+            "Referrer-Policy: strict-origin-when-cross-origin".to_owned(),
+            "X-Frame-Options:".to_owned(),
+            "Permissions-Policy: camera=(), microphone=()".to_owned(),
+        ];
+        let injected = ResponseHeaders::new(true, Some("default-src 'self'"), &overrides).unwrap();
+        let headers = call(injected, "/ok").await;
+        let referrer: Vec<_> = headers.get_all(header::REFERRER_POLICY).iter().collect();
+        assert_eq!(referrer, ["strict-origin-when-cross-origin"]);
+        assert!(!headers.contains_key(header::X_FRAME_OPTIONS), "an empty value removes it");
+        assert_eq!(headers["permissions-policy"], "camera=(), microphone=()");
+        assert_eq!(headers[header::CONTENT_SECURITY_POLICY], "default-src 'self'");
+    }
+
+    #[tokio::test]
+    async fn test_upstream_header_is_not_duplicated() {
+        let headers = call(ResponseHeaders::new(true, None, &[]).unwrap(), "/framed").await;
+        let framing: Vec<_> = headers.get_all(header::X_FRAME_OPTIONS).iter().collect();
+        assert_eq!(framing, ["SAMEORIGIN"]);
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    }
+
+    #[test]
+    fn test_malformed_override_is_rejected() {
+        for entry in ["no-colon", "bad name: x", "X-Test: bad\u{7f}value"] {
+            let err = ResponseHeaders::new(true, None, &[entry.to_owned()]).unwrap_err();
+            assert!(err.to_string().contains("WM_RESPONSE_HEADERS"), "{entry}: {err}");
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use http::{header, HeaderValue, StatusCode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use wm_adapters::wolf_proxy::error_response;
+
+/// Seconds a client is asked to wait while the database is being prepared
+pub const STARTUP_RETRY_AFTER_SECS: u64 = 2;
+
+/// Answer `/api/v1/*` with 503 and `Retry-After` until `ready` is set, i.e. until migrations
+/// and the boot record are done. Other paths, `/healthz` among them, are served throughout.
+pub async fn require_started(State(ready): State<Arc<AtomicBool>>, req: Request, next: Next) -> Response {
+    if ready.load(Ordering::Acquire) || !req.uri().path().starts_with("/api/v1/") {
+        return next.run(req).await;
+    }
+    let mut response = error_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Starting",
+        "WolfManager is still preparing its database; retry shortly",
+    );
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(STARTUP_RETRY_AFTER_SECS));
+    response
+}
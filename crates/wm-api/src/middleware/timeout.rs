@@ -0,0 +1,79 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+    Router,
+};
+use http::StatusCode;
+use std::time::Duration;
+use tracing::warn;
+use wm_adapters::wolf_proxy::error_response;
+
+/// Bound how long handlers on `router` may take to produce a response.
+/// Only apply to short-lived routes; SSE and the Wolf proxy are long-lived by design.
+pub fn limit_duration<S>(router: Router<S>, timeout: Duration) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(axum::middleware::from_fn_with_state(timeout, request_timeout))
+}
+
+async fn request_timeout(State(timeout): State<Duration>, req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_owned();
+    match tokio::time::timeout(timeout, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!(path = %path, timeout_ms = timeout.as_millis(), "Request timed out");
+            error_response(
+                StatusCode::GATEWAY_TIMEOUT,
+                "RequestTimeout",
+                &format!("request did not complete within {}ms", timeout.as_millis()),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get};
+    use http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        let router = Router::new()
+            .route("/fast", get(|| async { "ok" }))
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    "late"
+                }),
+            );
+        limit_duration(router, Duration::from_millis(50))
+    }
+
+    async fn call(uri: &str) -> (StatusCode, String) {
+        let resp = app()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_slow_handler_times_out() {
+        let (status, body) = call("/slow").await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["error"], "RequestTimeout");
+    }
+
+    #[tokio::test]
+    async fn test_fast_handler_unaffected() {
+        assert_eq!(call("/fast").await, (StatusCode::OK, "ok".to_owned()));
+    }
+}
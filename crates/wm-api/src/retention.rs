@@ -0,0 +1,101 @@
+use sqlx::SqlitePool;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+use wm_storage::prune_events;
+
+use crate::shutdown::Shutdown;
+
+/// Delete events older than `retention`, logging the outcome; returns how many were removed
+async fn prune_once(pool: &SqlitePool, retention: Duration) -> u64 {
+    let cutoff = OffsetDateTime::now_utc() - retention;
+    match prune_events(pool, cutoff).await {
+        Ok(removed) => {
+            info!(removed, cutoff = %cutoff, "Pruned old events");
+            removed
+        }
+        Err(e) => {
+            warn!("Failed to prune old events: {:#}", e);
+            0
+        }
+    }
+}
+
+/// Prune events older than `retention` right away and then every `every`, until shutdown.
+/// Runs in the background so a large first prune never delays startup.
+pub fn spawn_event_pruning(pool: SqlitePool, retention: Duration, every: Duration, shutdown: Shutdown) {
+    tokio::spawn(async move {
+        let stop = shutdown.wait();
+        tokio::pin!(stop);
+        let mut interval = tokio::time::interval(every);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = &mut stop => break,
+                _ = interval.tick() => {
+                    prune_once(&pool, retention).await;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wm_core::{Event, SessionId};
+    use wm_storage::{insert_event, list_events_after, migrate, new_pool};
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    async fn insert_aged(pool: &SqlitePool, age: Duration) {
+        let event = Event::SessionStarted {
+            session_id: SessionId(uuid::Uuid::new_v4()),
+            at: OffsetDateTime::now_utc() - age,
+        };
+        insert_event(pool, &event).await.unwrap();
+    }
+
+    async fn pool_with_events(ages: &[Duration]) -> SqlitePool {
+        let pool = new_pool("sqlite::memory:").await.unwrap();
+        migrate(&pool).await.unwrap();
+        for age in ages {
+            insert_aged(&pool, *age).await;
+        }
+        pool
+    }
+
+    async fn remaining(pool: &SqlitePool) -> usize {
+        list_events_after(pool, 0, 100).await.unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn test_prune_once_keeps_recent_events() {
+        let pool = pool_with_events(&[DAY * 40, DAY * 31, DAY, Duration::ZERO]).await;
+        assert_eq!(prune_once(&pool, DAY * 30).await, 2);
+        assert_eq!(remaining(&pool).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_pruning_task_runs_until_shutdown() {
+        let pool = pool_with_events(&[DAY * 2, Duration::ZERO]).await;
+        let shutdown = Shutdown::new();
+        spawn_event_pruning(pool.clone(), DAY, Duration::from_millis(20), shutdown.clone());
+
+        // The first run happens right away, without waiting a full interval
+        for _ in 0..50 {
+            if remaining(&pool).await == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(remaining(&pool).await, 1);
+
+        shutdown.trigger();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        insert_aged(&pool, DAY * 2).await;
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(remaining(&pool).await, 2, "no runs after shutdown");
+    }
+}
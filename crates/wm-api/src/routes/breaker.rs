@@ -0,0 +1,150 @@
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::warn;
+use wm_adapters::circuit_breaker::{BreakerMode, BreakerStatus};
+use wm_adapters::wolf_proxy::{ErrorBody, WolfProxyClient};
+
+use crate::validated_json::ValidatedJson;
+
+#[derive(Debug, Deserialize)]
+pub struct SetBreaker {
+    state: BreakerMode,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/wolf/breaker",
+    responses(
+        (status = 200, description = "Wolf circuit breaker mode, state and failure count", body = BreakerStatus)
+    )
+)]
+pub async fn get_breaker(State(client): State<Arc<WolfProxyClient>>) -> Json<BreakerStatus> {
+    Json(client.circuit_breaker().status())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/wolf/breaker",
+    request_body(content = String, description = r#"`{"state": "open" | "closed" | "auto"}`. `open` rejects all Wolf traffic, `closed` lets it all through, `auto` hands control back with the failure count reset"#, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Override applied; the resulting status", body = BreakerStatus),
+        (status = 400, description = "Body is not JSON", body = ErrorBody),
+        (status = 422, description = "`state` is missing or not a known mode; `fields` names the problem", body = ErrorBody)
+    )
+)]
+pub async fn set_breaker(
+    State(client): State<Arc<WolfProxyClient>>,
+    ValidatedJson(body): ValidatedJson<SetBreaker>,
+) -> Response {
+    let breaker = client.circuit_breaker();
+    breaker.set_mode(body.state);
+    warn!(mode = ?body.state, "Wolf circuit breaker mode changed by operator");
+    Json(breaker.status()).into_response()
+}
+
+pub fn breaker_router(client: Arc<WolfProxyClient>) -> Router {
+    Router::new()
+        .route("/api/v1/wolf/breaker", get(get_breaker).post(set_breaker))
+        .with_state(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use http::{header, Request, StatusCode};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use wm_adapters::wolf_proxy::WolfProxyConfig;
+
+    fn client() -> Arc<WolfProxyClient> {
+        let config = WolfProxyConfig::new("/nonexistent/wolf.sock".into(), 1000, 5000).with_circuit_breaker(2, 60_000);
+        Arc::new(WolfProxyClient::new(config))
+    }
+
+    async fn call(client: &Arc<WolfProxyClient>, req: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let resp = breaker_router(client.clone()).oneshot(req).await.unwrap();
+        let status = resp.status();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn set(client: &Arc<WolfProxyClient>, body: &str) -> (StatusCode, serde_json::Value) {
+        let req = Request::post("/api/v1/wolf/breaker")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_owned()))
+            .unwrap();
+        call(client, req).await
+    }
+
+    async fn read(client: &Arc<WolfProxyClient>) -> serde_json::Value {
+        let (status, body) = call(client, Request::get("/api/v1/wolf/breaker").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        body
+    }
+
+    #[tokio::test]
+    async fn test_get_reports_auto_closed_by_default() {
+        let body = read(&client()).await;
+        assert_eq!(body["mode"], "auto");
+        assert_eq!(body["state"], "closed");
+        assert_eq!(body["consecutive_failures"], 0);
+        assert_eq!(body["failure_threshold"], 2);
+        assert_eq!(body["cooldown_ms"], 60_000);
+    }
+
+    #[tokio::test]
+    async fn test_force_open_then_closed() {
+        let client = client();
+        let (status, body) = set(&client, r#"{"state":"open"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((body["mode"].as_str(), body["state"].as_str()), (Some("open"), Some("open")));
+        assert!(client.circuit_breaker().try_acquire().is_err());
+        assert_eq!(read(&client).await["state"], "open");
+
+        let (status, body) = set(&client, r#"{"state":"closed"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((body["mode"].as_str(), body["state"].as_str()), (Some("closed"), Some("closed")));
+        for _ in 0..3 {
+            client.circuit_breaker().record_failure();
+        }
+        assert!(client.circuit_breaker().try_acquire().is_ok(), "pinned closed ignores failures");
+        assert_eq!(read(&client).await["consecutive_failures"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_auto_restores_normal_behavior() {
+        let client = client();
+        let breaker = client.circuit_breaker();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(read(&client).await["state"], "open");
+
+        let (status, body) = set(&client, r#"{"state":"auto"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((body["state"].as_str(), body["consecutive_failures"].as_u64()), (Some("closed"), Some(0)));
+        assert!(breaker.try_acquire().is_ok());
+
+        // Back under the breaker's own control: the threshold trips it again
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(read(&client).await["state"], "open");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_state_is_rejected() {
+        let client = client();
+        for body in [r#"{"state":"half_open"}"#, r#"{}"#, "open"] {
+            let (status, json) = set(&client, body).await;
+            assert!(status.is_client_error(), "{body}: {status}");
+            assert_eq!(json["error"], "InvalidBody", "{body}");
+        }
+        assert_eq!(read(&client).await["mode"], "auto");
+    }
+}
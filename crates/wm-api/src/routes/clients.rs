@@ -0,0 +1,89 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use std::sync::Arc;
+use tracing::error;
+use wm_adapters::wolf_proxy::{error_response, ErrorBody, WolfProxyError};
+use wm_adapters::WolfApi;
+
+use super::wolf::proxy_error_status;
+
+#[derive(Clone)]
+pub struct ClientsState {
+    pub wolf: Arc<dyn WolfApi>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/clients",
+    responses(
+        (status = 200, description = "Moonlight clients paired with Wolf"),
+        (status = 502, description = "Wolf answered with an error or unreadable JSON", body = ErrorBody),
+        (status = 503, description = "Wolf is not reachable", body = ErrorBody)
+    )
+)]
+pub async fn list_clients(State(state): State<ClientsState>) -> Response {
+    match state.wolf.list_clients().await {
+        Ok(clients) => Json(clients).into_response(),
+        Err(e) => {
+            error!("Failed to list Wolf clients: {:#}", e);
+            // Transport failures keep the proxy's statuses; anything else is a bad answer
+            let (status, code) = match e.downcast_ref::<WolfProxyError>() {
+                Some(proxy_error) => {
+                    let (status, code, _) = proxy_error_status(proxy_error);
+                    (status, code)
+                }
+                None => (StatusCode::BAD_GATEWAY, "UpstreamError"),
+            };
+            error_response(status, code, &format!("{:#}", e))
+        }
+    }
+}
+
+pub fn clients_router(wolf: Arc<dyn WolfApi>) -> Router {
+    Router::new()
+        .route("/api/v1/clients", get(list_clients))
+        .with_state(ClientsState { wolf })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use wm_adapters::mock_wolf;
+    use wm_adapters::wolf_proxy::WolfProxyConfig;
+
+    async fn get(wolf: Arc<dyn WolfApi>) -> (StatusCode, serde_json::Value) {
+        let resp = clients_router(wolf)
+            .oneshot(Request::get("/api/v1/clients").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_list_clients() {
+        let (status, body) = get(mock_wolf()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_wolf_is_503() {
+        let wolf = wm_adapters::unix_wolf(
+            WolfProxyConfig::new("/nonexistent/wolf.sock".into(), 100, 1000).with_retry(1, 1),
+        );
+        let (status, body) = get(wolf).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["error"], "UpstreamUnavailable");
+    }
+}
@@ -0,0 +1,24 @@
+use axum::{extract::State, http::HeaderMap, response::Response, routing::get, Router};
+use std::sync::Arc;
+use wm_config::Config;
+
+use crate::etag::CachedJson;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/config",
+    responses(
+        (status = 200, description = "Effective settings; secrets are omitted and URL credentials masked", body = Config),
+        (status = 304, description = "`If-None-Match` matches the current `ETag`")
+    )
+)]
+pub async fn get_config(State(view): State<Arc<CachedJson>>, headers: HeaderMap) -> Response {
+    view.respond(&headers)
+}
+
+/// Serves `config` as loaded at startup; it never changes while running
+pub fn config_router(config: &Config) -> Router {
+    Router::new()
+        .route("/api/v1/config", get(get_config))
+        .with_state(Arc::new(CachedJson::new(&config.public_view())))
+}
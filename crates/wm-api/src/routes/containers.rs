@@ -0,0 +1,122 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use std::sync::Arc;
+use tracing::{error, warn};
+use wm_adapters::docker::{DockerApi, DockerError};
+use wm_adapters::wolf_proxy::{error_response, ErrorBody};
+
+#[derive(Clone)]
+pub struct ContainersState {
+    pub docker: Arc<dyn DockerApi>,
+}
+
+/// Map adapter failures to JSON errors; a missing docker.sock is a 503, not a 500
+fn docker_error_response(e: anyhow::Error) -> Response {
+    match e.downcast_ref::<DockerError>() {
+        Some(DockerError::Unavailable { .. }) => {
+            warn!("Docker unavailable: {}", e);
+            error_response(StatusCode::SERVICE_UNAVAILABLE, "ServiceUnavailable", &e.to_string())
+        }
+        Some(DockerError::NotFound(_)) => {
+            error_response(StatusCode::NOT_FOUND, "ContainerNotFound", &e.to_string())
+        }
+        None => {
+            error!("Docker request failed: {:#}", e);
+            error_response(StatusCode::BAD_GATEWAY, "DockerError", &format!("{:#}", e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/containers",
+    responses(
+        (status = 200, description = "All containers known to Docker, running or not"),
+        (status = 503, description = "docker.sock is missing or not accepting connections", body = ErrorBody)
+    )
+)]
+pub async fn list_containers(State(state): State<ContainersState>) -> Response {
+    match state.docker.list_containers().await {
+        Ok(containers) => Json(containers).into_response(),
+        Err(e) => docker_error_response(e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/containers/{id}",
+    params(("id" = String, Path, description = "Container ID or name")),
+    responses(
+        (status = 200, description = "Container lifecycle state"),
+        (status = 404, description = "No such container", body = ErrorBody),
+        (status = 503, description = "docker.sock is missing or not accepting connections", body = ErrorBody)
+    )
+)]
+pub async fn container_status(
+    State(state): State<ContainersState>,
+    Path(id): Path<String>,
+) -> Response {
+    match state.docker.container_status(&id).await {
+        Ok(status) => Json(status).into_response(),
+        Err(e) => docker_error_response(e),
+    }
+}
+
+pub fn containers_router(docker: Arc<dyn DockerApi>) -> Router {
+    Router::new()
+        .route("/api/v1/containers", get(list_containers))
+        .route("/api/v1/containers/{id}", get(container_status))
+        .with_state(ContainersState { docker })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use wm_adapters::docker::{mock_docker, unix_docker};
+
+    async fn get(docker: Arc<dyn DockerApi>, uri: &str) -> (StatusCode, serde_json::Value) {
+        let resp = containers_router(docker)
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_list_containers() {
+        let (status, body) = get(mock_docker(), "/api/v1/containers").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["name"], "wolf");
+        assert_eq!(body[0]["state"], "running");
+    }
+
+    #[tokio::test]
+    async fn test_container_status() {
+        let (status, body) = get(mock_docker(), "/api/v1/containers/wolf").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["running"], true);
+
+        let (status, body) = get(mock_docker(), "/api/v1/containers/missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "ContainerNotFound");
+    }
+
+    #[tokio::test]
+    async fn test_missing_docker_socket_is_503() {
+        let docker = unix_docker("/nonexistent/docker.sock".into());
+        let (status, body) = get(docker, "/api/v1/containers").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["error"], "ServiceUnavailable");
+    }
+}
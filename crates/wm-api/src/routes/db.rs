@@ -0,0 +1,42 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::{error, warn};
+use wm_adapters::wolf_proxy::{error_response, ErrorBody};
+use wm_storage::MigrationStatus;
+
+use crate::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/db/status",
+    responses(
+        (status = 200, description = "Applied and embedded migration versions, and whether they match", body = MigrationStatus),
+        (status = 503, description = "The database has migrations this binary doesn't know, likely after a downgrade", body = MigrationStatus),
+        (status = 500, description = "Database error", body = ErrorBody)
+    )
+)]
+pub async fn db_status(State(state): State<AppState>) -> Response {
+    match wm_storage::migration_status(&state.pool).await {
+        Ok(status) if status.is_ahead() => {
+            warn!(
+                applied_version = status.applied_version,
+                latest_version = status.latest_version,
+                "Database schema is newer than this binary"
+            );
+            (StatusCode::SERVICE_UNAVAILABLE, Json(status)).into_response()
+        }
+        Ok(status) => Json(status).into_response(),
+        Err(e) => {
+            error!("Failed to read migration status: {}", e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "DatabaseError",
+                "failed to read migration status",
+            )
+        }
+    }
+}
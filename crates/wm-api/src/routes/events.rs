@@ -0,0 +1,142 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tracing::error;
+use utoipa::ToSchema;
+use wm_adapters::wolf_proxy::{error_response, ErrorBody};
+
+use wm_storage::StoredEvent;
+
+use crate::AppState;
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct ListEventsQuery {
+    /// Page size (default 50, max 500)
+    limit: Option<u32>,
+    /// RFC 3339 cursor; only events strictly older than this are returned
+    before: Option<String>,
+    /// Id half of the cursor; with it, events at exactly `before` with a smaller id are returned too
+    before_id: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/events",
+    params(
+        ("limit" = Option<u32>, Query, description = "Page size (default 50, max 500)"),
+        ("before" = Option<String>, Query, description = "RFC 3339 cursor; pass the `at` of the last event of the previous page"),
+        ("before_id" = Option<i64>, Query, description = "Pass the `id` of the last event of the previous page along with `before`, so events sharing its timestamp are not skipped")
+    ),
+    responses(
+        (status = 200, description = "Stored events, newest first", body = [StoredEvent]),
+        (status = 400, description = "Invalid cursor", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody)
+    )
+)]
+pub async fn list_events(
+    State(state): State<AppState>,
+    Query(query): Query<ListEventsQuery>,
+) -> Response {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let before = match query.before.as_deref().map(|s| OffsetDateTime::parse(s, &Rfc3339)) {
+        None => None,
+        // No id keeps the cursor strictly before `at`, as no id is below i64::MIN
+        Some(Ok(before)) => Some((before, query.before_id.unwrap_or(i64::MIN))),
+        Some(Err(e)) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "InvalidCursor",
+                &format!("before must be an RFC 3339 timestamp: {}", e),
+            )
+        }
+    };
+    if before.is_none() && query.before_id.is_some() {
+        return error_response(StatusCode::BAD_REQUEST, "InvalidCursor", "before_id requires before");
+    }
+
+    match wm_storage::list_events(&state.pool, limit, before).await {
+        Ok(events) => Json(events).into_response(),
+        Err(e) => {
+            error!("Failed to list events: {}", e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "DatabaseError",
+                "failed to read events",
+            )
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventSummaryQuery {
+    /// RFC 3339 timestamp; only events at or after it are counted
+    since: Option<String>,
+}
+
+/// Headline event numbers for dashboards
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EventSummary {
+    /// Events counted, across all types
+    pub total: u64,
+    /// Count per event `type`; types with no events are absent
+    pub by_type: BTreeMap<String, u64>,
+    /// The `since` the counts start from, if one was given
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>)]
+    pub since: Option<OffsetDateTime>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/events/summary",
+    params(
+        ("since" = Option<String>, Query, description = "RFC 3339 timestamp; count only events at or after it, e.g. an hour ago for recent activity")
+    ),
+    responses(
+        (status = 200, description = "Event totals, overall and per type", body = EventSummary),
+        (status = 400, description = "Invalid `since`", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody)
+    )
+)]
+pub async fn event_summary(
+    State(state): State<AppState>,
+    Query(query): Query<EventSummaryQuery>,
+) -> Response {
+    let since = match query.since.as_deref().map(|s| OffsetDateTime::parse(s, &Rfc3339)) {
+        None => None,
+        Some(Ok(since)) => Some(since),
+        Some(Err(e)) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "InvalidSince",
+                &format!("since must be an RFC 3339 timestamp: {}", e),
+            )
+        }
+    };
+
+    match wm_storage::event_counts(&state.pool, since).await {
+        Ok(by_type) => Json(EventSummary {
+            total: by_type.values().sum(),
+            by_type,
+            since,
+        })
+        .into_response(),
+        Err(e) => {
+            error!("Failed to count events: {}", e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "DatabaseError",
+                "failed to count events",
+            )
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! The domain event feed over a WebSocket, for clients that prefer one to SSE.
+//!
+//! Only what the feed needs from RFC 6455 is implemented: the server sends
+//! unfragmented text frames and pings, and answers the client's pings and close.
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use base64::Engine;
+use futures_util::{stream, StreamExt};
+use http::{header, HeaderMap, HeaderValue};
+use hyper::upgrade::OnUpgrade;
+use hyper_util::rt::TokioIo;
+use sha1::{Digest, Sha1};
+use std::io;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+use wm_adapters::wolf_proxy::{error_response, ErrorBody};
+use wm_storage::StoredEvent;
+
+use crate::AppState;
+
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Clients only send control frames; anything bigger than this is refused
+const MAX_CLIENT_FRAME: u64 = 64 * 1024;
+
+const OP_TEXT: u8 = 0x1;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+const CLOSE_GOING_AWAY: u16 = 1001;
+const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+
+/// `Sec-WebSocket-Accept` for a client's `Sec-WebSocket-Key`
+fn accept_key(key: &[u8]) -> String {
+    let mut sha = Sha1::new();
+    sha.update(key);
+    sha.update(WS_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(sha.finalize())
+}
+
+fn upgrade_required(detail: &str) -> Response {
+    let mut response = error_response(StatusCode::UPGRADE_REQUIRED, "UpgradeRequired", detail);
+    response
+        .headers_mut()
+        .insert("sec-websocket-version", HeaderValue::from_static("13"));
+    response
+}
+
+fn is_version_13(headers: &HeaderMap) -> bool {
+    headers
+        .get("sec-websocket-version")
+        .is_some_and(|v| v.as_bytes() == b"13")
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/events/ws",
+    responses(
+        (status = 101, description = "Switched to a WebSocket that carries the same event JSON as the SSE stream, one text message each, with periodic pings"),
+        (status = 400, description = "Missing Sec-WebSocket-Key", body = ErrorBody),
+        (status = 426, description = "Not a WebSocket version 13 handshake", body = ErrorBody)
+    )
+)]
+pub async fn events_ws(State(state): State<AppState>, mut req: Request) -> Response {
+    if !super::wolf::is_websocket_upgrade(req.headers()) || !is_version_13(req.headers()) {
+        return upgrade_required("this endpoint only speaks WebSocket (version 13)");
+    }
+    let Some(key) = req.headers().get("sec-websocket-key") else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "InvalidHandshake",
+            "missing Sec-WebSocket-Key header",
+        );
+    };
+    let accept = accept_key(key.as_bytes());
+    let Some(on_upgrade) = req.extensions_mut().remove::<OnUpgrade>() else {
+        warn!("WebSocket upgrade requested on a connection that cannot upgrade");
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "UpgradeUnavailable",
+            "This connection does not support protocol upgrades",
+        );
+    };
+
+    // Subscribe now so events published while the client finishes the handshake still arrive
+    let events = state.events.subscribe();
+    let ping_every = state.sse_keepalive;
+    let shutdown = state.shutdown.clone();
+    tokio::spawn(async move {
+        match on_upgrade.await {
+            Ok(upgraded) => {
+                info!("Event WebSocket connected");
+                pump(TokioIo::new(upgraded), events, ping_every, shutdown.wait()).await;
+                info!("Event WebSocket closed");
+            }
+            Err(e) => warn!("Event WebSocket upgrade failed: {}", e),
+        }
+    });
+
+    (
+        StatusCode::SWITCHING_PROTOCOLS,
+        [
+            (header::UPGRADE, HeaderValue::from_static("websocket")),
+            (header::CONNECTION, HeaderValue::from_static("upgrade")),
+        ],
+        [("sec-websocket-accept", accept)],
+    )
+        .into_response()
+}
+
+/// Forward broadcast events to the socket until the client closes, the connection
+/// drops, or `shutdown` resolves
+async fn pump<S>(
+    io: S,
+    mut events: broadcast::Receiver<StoredEvent>,
+    ping_every: Duration,
+    shutdown: impl std::future::Future<Output = ()>,
+) where
+    S: AsyncRead + AsyncWrite,
+{
+    let (rx, mut tx) = tokio::io::split(io);
+    // Frames are read through a stream so a half-read frame survives the select below
+    let frames = stream::unfold(Some(rx), |rx| async move {
+        let mut rx = rx?;
+        let frame = read_frame(&mut rx).await;
+        let rx = frame.is_ok().then_some(rx);
+        Some((frame, rx))
+    });
+    tokio::pin!(frames, shutdown);
+    let mut ping = tokio::time::interval_at(tokio::time::Instant::now() + ping_every, ping_every);
+
+    loop {
+        let sent = tokio::select! {
+            _ = &mut shutdown => {
+                let _ = tx.write_all(&close_frame(CLOSE_GOING_AWAY, "server shutting down")).await;
+                break;
+            }
+            event = events.recv() => match event {
+                Ok(stored) => match serde_json::to_vec(&stored.event) {
+                    Ok(json) => tx.write_all(&encode_frame(OP_TEXT, &json)).await,
+                    Err(e) => {
+                        warn!("Failed to serialize domain event: {}", e);
+                        Ok(())
+                    }
+                },
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(skipped = skipped, "Event WebSocket lagged, skipping missed events");
+                    Ok(())
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    let _ = tx.write_all(&close_frame(CLOSE_GOING_AWAY, "event feed closed")).await;
+                    break;
+                }
+            },
+            _ = ping.tick() => tx.write_all(&encode_frame(OP_PING, b"")).await,
+            frame = frames.next() => match frame {
+                Some(Ok((OP_CLOSE, payload))) => {
+                    // Echo the client's status code to complete the close handshake
+                    let _ = tx.write_all(&encode_frame(OP_CLOSE, &payload[..payload.len().min(2)])).await;
+                    break;
+                }
+                Some(Ok((OP_PING, payload))) => tx.write_all(&encode_frame(OP_PONG, &payload)).await,
+                // Pongs and client messages carry nothing the feed needs
+                Some(Ok(_)) => Ok(()),
+                Some(Err(e)) if e.kind() == io::ErrorKind::InvalidData => {
+                    warn!("Event WebSocket protocol error: {}", e);
+                    let _ = tx.write_all(&close_frame(CLOSE_PROTOCOL_ERROR, &e.to_string())).await;
+                    break;
+                }
+                Some(Err(e)) => {
+                    debug!("Event WebSocket client disconnected: {}", e);
+                    break;
+                }
+                None => break,
+            },
+        };
+        if let Err(e) = sent {
+            debug!("Event WebSocket write failed: {}", e);
+            break;
+        }
+    }
+    let _ = tx.shutdown().await;
+}
+
+/// A final, unfragmented, unmasked frame, as servers send them
+fn encode_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+fn close_frame(code: u16, reason: &str) -> Vec<u8> {
+    let mut payload = code.to_be_bytes().to_vec();
+    // Control frame payloads are capped at 125 bytes
+    payload.extend(reason.bytes().take(123));
+    encode_frame(OP_CLOSE, &payload)
+}
+
+/// Read one frame as `(opcode, unmasked payload)`. Malformed frames are `InvalidData`.
+async fn read_frame<R: AsyncRead + Unpin>(r: &mut R) -> io::Result<(u8, Vec<u8>)> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    let mut head = [0u8; 2];
+    r.read_exact(&mut head).await?;
+    if head[1] & 0x80 == 0 {
+        return Err(invalid("client frames must be masked"));
+    }
+    let len = match head[1] & 0x7f {
+        126 => u64::from(r.read_u16().await?),
+        127 => r.read_u64().await?,
+        len => u64::from(len),
+    };
+    if len > MAX_CLIENT_FRAME {
+        return Err(invalid("frame too large"));
+    }
+    let mut mask = [0u8; 4];
+    r.read_exact(&mut mask).await?;
+    let mut payload = vec![0u8; len as usize];
+    r.read_exact(&mut payload).await?;
+    for (i, b) in payload.iter_mut().enumerate() {
+        *b ^= mask[i % 4];
+    }
+    Ok((head[0] & 0x0f, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::DuplexStream;
+
+    /// Clients must mask every frame
+    fn client_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![0x80 | opcode, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    /// Server frames are unmasked; decode them like the client would
+    async fn server_frame(r: &mut DuplexStream) -> (u8, Vec<u8>) {
+        let mut head = [0u8; 2];
+        r.read_exact(&mut head).await.unwrap();
+        assert_eq!(head[1] & 0x80, 0, "server frames must not be masked");
+        let len = match head[1] {
+            126 => usize::from(r.read_u16().await.unwrap()),
+            len => usize::from(len),
+        };
+        let mut payload = vec![0u8; len];
+        r.read_exact(&mut payload).await.unwrap();
+        (head[0] & 0x0f, payload)
+    }
+
+    fn stored(id: i64) -> StoredEvent {
+        let event = wm_core::Event::SessionEnded {
+            session_id: wm_core::SessionId(uuid::Uuid::from_u128(id as u128)),
+            at: time::OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+        };
+        StoredEvent {
+            id,
+            kind: event.kind().into(),
+            at: event.at(),
+            event,
+        }
+    }
+
+    #[test]
+    fn test_accept_key() {
+        // The example handshake from RFC 6455 section 1.3
+        assert_eq!(accept_key(b"dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn test_encode_frame_lengths() {
+        assert_eq!(encode_frame(OP_PING, b""), [0x89, 0]);
+        let medium = encode_frame(OP_TEXT, &[b'x'; 300]);
+        assert_eq!(&medium[..4], [0x81, 126, 0x01, 0x2c]);
+        assert_eq!(medium.len(), 304);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pings_answers_pings_and_skips_lag() {
+        let (server, mut client) = tokio::io::duplex(4096);
+        let (tx, rx) = broadcast::channel(1);
+        let pump = tokio::spawn(pump(server, rx, Duration::from_secs(15), std::future::pending()));
+
+        tokio::time::sleep(Duration::from_secs(15)).await;
+        assert_eq!(server_frame(&mut client).await, (OP_PING, vec![]));
+
+        client.write_all(&client_frame(OP_PING, b"hi")).await.unwrap();
+        assert_eq!(server_frame(&mut client).await, (OP_PONG, b"hi".to_vec()));
+
+        // A lagging subscriber drops the oldest events but keeps the connection
+        for id in 1..=3 {
+            tx.send(stored(id)).unwrap();
+        }
+        let (opcode, json) = server_frame(&mut client).await;
+        assert_eq!(opcode, OP_TEXT);
+        let event: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(event["data"]["session_id"], uuid::Uuid::from_u128(3).to_string());
+
+        client.write_all(&client_frame(OP_CLOSE, &1000u16.to_be_bytes())).await.unwrap();
+        assert_eq!(server_frame(&mut client).await, (OP_CLOSE, 1000u16.to_be_bytes().to_vec()));
+        pump.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unmasked_client_frame_is_protocol_error() {
+        let (server, mut client) = tokio::io::duplex(4096);
+        let (_tx, rx) = broadcast::channel(1);
+        let pump = tokio::spawn(pump(server, rx, Duration::from_secs(15), std::future::pending()));
+
+        client.write_all(&encode_frame(OP_TEXT, b"hello")).await.unwrap();
+        let (opcode, payload) = server_frame(&mut client).await;
+        assert_eq!(opcode, OP_CLOSE);
+        assert_eq!(payload[..2], CLOSE_PROTOCOL_ERROR.to_be_bytes());
+        pump.await.unwrap();
+    }
+}
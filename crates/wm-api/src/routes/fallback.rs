@@ -0,0 +1,28 @@
+use axum::{
+    http::{Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use wm_adapters::wolf_proxy::error_response;
+
+/// JSON 404 for paths no route matches
+pub async fn not_found(method: Method, uri: Uri) -> Response {
+    error_response(
+        StatusCode::NOT_FOUND,
+        "NotFound",
+        &format!("no route for {} {}", method, uri.path()),
+    )
+}
+
+/// JSON 405 for a known path hit with the wrong method; axum adds the `Allow` header.
+/// A plain `OPTIONS` gets a 204 with the same `Allow` instead; preflights are answered by
+/// the CORS layer before they get here.
+pub async fn method_not_allowed(method: Method, uri: Uri) -> Response {
+    if method == Method::OPTIONS {
+        return StatusCode::NO_CONTENT.into_response();
+    }
+    error_response(
+        StatusCode::METHOD_NOT_ALLOWED,
+        "MethodNotAllowed",
+        &format!("{} is not supported on {}", method, uri.path()),
+    )
+}
@@ -0,0 +1,167 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::warn;
+use wm_adapters::wolf_proxy::WolfProxyClient;
+
+#[derive(Clone)]
+pub struct ReadyState {
+    pub pool: sqlx::SqlitePool,
+    pub wolf: Arc<WolfProxyClient>,
+    /// Skip the Wolf check for deployments that start Wolf lazily
+    pub check_wolf: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+enum Check {
+    Up,
+    Down { error: String },
+    Skipped,
+}
+
+impl Check {
+    fn from_result(result: anyhow::Result<()>) -> Self {
+        match result {
+            Ok(()) => Check::Up,
+            Err(e) => Check::Down { error: e.to_string() },
+        }
+    }
+
+    fn is_ok(&self) -> bool {
+        !matches!(self, Check::Down { .. })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Checks {
+    database: Check,
+    wolf: Check,
+}
+
+async fn check_database(pool: &sqlx::SqlitePool) -> anyhow::Result<()> {
+    sqlx::query_scalar::<_, i64>("SELECT 1").fetch_one(pool).await?;
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    responses(
+        (status = 200, description = "Database and Wolf are reachable"),
+        (status = 503, description = "A dependency is down; body lists each check")
+    )
+)]
+pub async fn readyz(State(state): State<ReadyState>) -> impl IntoResponse {
+    let database = Check::from_result(check_database(&state.pool).await);
+    let wolf = if state.check_wolf {
+        Check::from_result(state.wolf.check_readiness().await)
+    } else {
+        Check::Skipped
+    };
+
+    let ready = database.is_ok() && wolf.is_ok();
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        warn!(database = ?database, wolf = ?wolf, "Readiness check failed");
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let body = serde_json::json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "checks": Checks { database, wolf },
+    });
+    (status, Json(body))
+}
+
+pub fn health_router(state: ReadyState) -> Router {
+    Router::new()
+        .route("/readyz", get(readyz))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use wm_adapters::wolf_proxy::WolfProxyConfig;
+
+    async fn pool() -> sqlx::SqlitePool {
+        wm_storage::new_pool("sqlite::memory:").await.unwrap()
+    }
+
+    fn wolf(path: &str) -> Arc<WolfProxyClient> {
+        Arc::new(WolfProxyClient::new(WolfProxyConfig::new(path.into(), 200, 1000)))
+    }
+
+    async fn ready(state: ReadyState) -> (StatusCode, serde_json::Value) {
+        let req = Request::get("/readyz").body(Body::empty()).unwrap();
+        let resp = health_router(state).oneshot(req).await.unwrap();
+        let status = resp.status();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_all_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let sock = dir.path().join("wolf.sock");
+        let _listener = tokio::net::UnixListener::bind(&sock).unwrap();
+
+        let (status, body) = ready(ReadyState {
+            pool: pool().await,
+            wolf: wolf(sock.to_str().unwrap()),
+            check_wolf: true,
+        })
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ready");
+        assert_eq!(body["checks"]["database"]["status"], "up");
+        assert_eq!(body["checks"]["wolf"]["status"], "up");
+    }
+
+    #[tokio::test]
+    async fn test_db_down() {
+        let pool = pool().await;
+        pool.close().await;
+
+        let (status, body) = ready(ReadyState {
+            pool,
+            wolf: wolf("/nonexistent/wolf.sock"),
+            check_wolf: false,
+        })
+        .await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["checks"]["database"]["status"], "down");
+        assert!(body["checks"]["database"]["error"].is_string());
+        assert_eq!(body["checks"]["wolf"]["status"], "skipped");
+    }
+
+    #[tokio::test]
+    async fn test_wolf_down() {
+        let (status, body) = ready(ReadyState {
+            pool: pool().await,
+            wolf: wolf("/nonexistent/wolf.sock"),
+            check_wolf: true,
+        })
+        .await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "not_ready");
+        assert_eq!(body["checks"]["database"]["status"], "up");
+        assert_eq!(body["checks"]["wolf"]["status"], "down");
+        assert!(body["checks"]["wolf"]["error"]
+            .as_str()
+            .unwrap()
+            .contains("wolf.sock not found"));
+    }
+}
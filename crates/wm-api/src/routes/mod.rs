@@ -1 +1,15 @@
+pub mod breaker;
+pub mod clients;
+pub mod config;
+pub mod containers;
+pub mod db;
+pub mod events;
+pub mod events_ws;
+pub mod fallback;
+pub mod health;
+pub mod metrics;
+pub mod sessions;
+pub mod users;
+pub mod version;
 pub mod wolf;
+pub mod wolf_paths;
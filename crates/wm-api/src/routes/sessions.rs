@@ -0,0 +1,129 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use time::OffsetDateTime;
+use tracing::error;
+use uuid::Uuid;
+use wm_adapters::wolf_proxy::{error_response, ErrorBody};
+use wm_core::{SessionId, UserId};
+use wm_storage::SessionEnd;
+
+use crate::validated_json::ValidatedJson;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSession {
+    user_id: UserId,
+}
+
+fn database_error(e: anyhow::Error) -> Response {
+    error!("Session query failed: {:#}", e);
+    error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "DatabaseError",
+        "failed to access sessions",
+    )
+}
+
+fn session_not_found(id: SessionId) -> Response {
+    error_response(
+        StatusCode::NOT_FOUND,
+        "SessionNotFound",
+        &format!("session {id} not found"),
+    )
+}
+
+/// Path IDs are parsed by hand so a bad one gets a JSON error
+fn invalid_session_id(e: uuid::Error) -> Response {
+    error_response(
+        StatusCode::BAD_REQUEST,
+        "InvalidSessionId",
+        &format!("session id must be a UUID: {}", e),
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/sessions",
+    request_body(content = String, description = r#"`{"user_id": "<uuid>"}`"#, content_type = "application/json"),
+    responses(
+        (status = 201, description = "Session started; SessionStarted is published to the event stream"),
+        (status = 404, description = "No such user", body = ErrorBody),
+        (status = 422, description = "`user_id` is missing or not a UUID; `fields` names the problem", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody)
+    )
+)]
+pub async fn create_session(
+    State(state): State<AppState>,
+    ValidatedJson(body): ValidatedJson<CreateSession>,
+) -> Response {
+    let id = SessionId(Uuid::new_v4());
+    let at = OffsetDateTime::now_utc();
+    match wm_storage::create_session(&state.pool, id, body.user_id, at).await {
+        Ok(Some((session, event))) => {
+            state.publish(event);
+            (StatusCode::CREATED, Json(session)).into_response()
+        }
+        Ok(None) => error_response(
+            StatusCode::NOT_FOUND,
+            "UserNotFound",
+            &format!("user {} not found", body.user_id),
+        ),
+        Err(e) => database_error(e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/sessions/{id}/end",
+    params(("id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 200, description = "Session ended. Idempotent: ending an ended session returns it unchanged and publishes nothing"),
+        (status = 400, description = "Session ID is not a UUID", body = ErrorBody),
+        (status = 404, description = "No such session", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody)
+    )
+)]
+pub async fn end_session(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let id = match id.parse::<SessionId>() {
+        Ok(id) => id,
+        Err(e) => return invalid_session_id(e),
+    };
+    let at = OffsetDateTime::now_utc();
+    match wm_storage::end_session(&state.pool, id, at).await {
+        Ok(SessionEnd::Ended(session, event)) => {
+            state.publish(event);
+            Json(session).into_response()
+        }
+        Ok(SessionEnd::AlreadyEnded(session)) => Json(session).into_response(),
+        Ok(SessionEnd::NotFound) => session_not_found(id),
+        Err(e) => database_error(e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/sessions/{id}",
+    params(("id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 200, description = "Session, with `ended_at` null while it is running"),
+        (status = 400, description = "Session ID is not a UUID", body = ErrorBody),
+        (status = 404, description = "No such session", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody)
+    )
+)]
+pub async fn get_session(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let id = match id.parse::<SessionId>() {
+        Ok(id) => id,
+        Err(e) => return invalid_session_id(e),
+    };
+    match wm_storage::get_session(&state.pool, id).await {
+        Ok(Some(session)) => Json(session).into_response(),
+        Ok(None) => session_not_found(id),
+        Err(e) => database_error(e),
+    }
+}
@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use tracing::error;
+use uuid::Uuid;
+use wm_adapters::wolf_proxy::{error_response, ErrorBody};
+use wm_core::UserId;
+
+use crate::AppState;
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct ListUsersQuery {
+    /// Page size (default 50, max 100)
+    limit: Option<u32>,
+    /// `next_cursor` from the previous page
+    after: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users",
+    params(
+        ("limit" = Option<u32>, Query, description = "Page size (default 50, max 100)"),
+        ("after" = Option<String>, Query, description = "Cursor; pass the `next_cursor` of the previous page")
+    ),
+    responses(
+        (status = 200, description = "Users ordered by id, with `next_cursor` (null on the last page)"),
+        (status = 400, description = "Invalid limit or cursor", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody)
+    )
+)]
+pub async fn list_users(
+    State(state): State<AppState>,
+    Query(query): Query<ListUsersQuery>,
+) -> Response {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "InvalidLimit",
+            &format!("limit must be between 1 and {}", MAX_LIMIT),
+        );
+    }
+    let after = match query.after.as_deref().map(Uuid::parse_str) {
+        None => None,
+        Some(Ok(after)) => Some(UserId(after)),
+        Some(Err(e)) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "InvalidCursor",
+                &format!("after must be a cursor returned as next_cursor: {}", e),
+            )
+        }
+    };
+
+    match wm_storage::list_users(&state.pool, limit, after).await {
+        Ok(page) => Json(page).into_response(),
+        Err(e) => {
+            error!("Failed to list users: {}", e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "DatabaseError",
+                "failed to read users",
+            )
+        }
+    }
+}
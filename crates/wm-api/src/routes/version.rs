@@ -0,0 +1,41 @@
+use axum::Json;
+use serde::Serialize;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use utoipa::ToSchema;
+
+/// Which build is running, stamped at compile time by `build.rs`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BuildInfo {
+    /// Crate version from `Cargo.toml`
+    pub version: &'static str,
+    /// Abbreviated git commit, or `unknown` when built outside a checkout
+    pub git_commit: &'static str,
+    /// When the binary was built (RFC 3339, UTC)
+    pub build_timestamp: String,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        let built_at = env!("WM_BUILD_EPOCH")
+            .parse::<i64>()
+            .ok()
+            .and_then(|secs| OffsetDateTime::from_unix_timestamp(secs).ok())
+            .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit: env!("WM_BUILD_COMMIT"),
+            build_timestamp: built_at.format(&Rfc3339).unwrap_or_default(),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/version",
+    responses(
+        (status = 200, description = "Version, commit and build time of the running binary", body = BuildInfo)
+    )
+)]
+pub async fn version() -> Json<BuildInfo> {
+    Json(BuildInfo::current())
+}
@@ -1,39 +1,203 @@
 use axum::{
     body::Body,
     extract::{ConnectInfo, Request, State},
-    http::{StatusCode, Uri},
-    response::Response,
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Response},
     routing::any,
-    Router,
+    Json, Router,
 };
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
-use tracing::{error, warn};
-use wm_adapters::wolf_proxy::{error_response, WolfProxyClient};
+use tracing::{error, info_span, warn};
+use hyper::upgrade::OnUpgrade;
+use crate::middleware::request_id::RequestId;
+use super::wolf_paths::PathFilter;
+use wm_adapters::wolf_proxy::{
+    error_response, ErrorBody, spawn_tunnel, UpgradeOutcome, WolfBody, WolfProxyClient, WolfProxyError,
+};
+use wm_adapters::WolfApi;
 
 #[derive(Clone)]
 pub struct WolfProxyState {
     pub client: Arc<WolfProxyClient>,
+    pub paths: Arc<PathFilter>,
+    pub ready: ReadyMode,
+}
+
+/// How far `/wolfapi/_ready` goes to decide Wolf is ready
+#[derive(Debug, Clone, Default)]
+pub enum ReadyMode {
+    /// Wolf accepts a connection
+    #[default]
+    Connect,
+    /// Wolf also answers a GET of this path with a 2xx
+    Request(String),
+}
+
+/// 503 naming the readiness check that failed
+fn not_ready(check: &str, code: &str, detail: String) -> Response {
+    warn!(check, "Wolf readiness check failed: {}", detail);
+    let error = ErrorBody::new(code, &detail);
+    let mut body = serde_json::json!(error);
+    body["check"] = check.into();
+    let mut response = (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response();
+    response.extensions_mut().insert(error);
+    response
 }
 
-/// Health check endpoint for Wolf socket readiness
+/// Health check endpoint for Wolf endpoint readiness
 async fn wolf_ready(State(state): State<WolfProxyState>) -> Response {
-    match state.client.check_readiness().await {
-        Ok(_) => Response::builder()
-            .status(StatusCode::OK)
-            .body(Body::from(r#"{"status":"ok"}"#))
-            .unwrap(),
+    let endpoint = state.client.endpoint();
+    if let Err(e) = state.client.check_readiness().await {
+        let code = match e.downcast_ref() {
+            Some(WolfProxyError::PermissionDenied { .. }) => "UpstreamPermissionDenied",
+            _ => "UpstreamUnavailable",
+        };
+        return not_ready("connect", code, format!("Wolf not reachable at {}: {}", endpoint, e));
+    }
+    if let ReadyMode::Request(path) = &state.ready {
+        if let Err(e) = state.client.check_endpoint(path).await {
+            return not_ready(
+                "request",
+                "UpstreamUnavailable",
+                format!("Wolf at {} is not answering: {}", endpoint, e),
+            );
+        }
+    }
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .body(Body::from(r#"{"status":"ok"}"#))
+        .unwrap()
+}
+
+/// Check whether the client is asking to switch to WebSocket
+pub(crate) fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::UPGRADE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.eq_ignore_ascii_case("websocket"))
+        .unwrap_or(false)
+}
+
+/// Status, error code, and detail prefix returned for each proxy failure
+pub(crate) fn proxy_error_status(e: &WolfProxyError) -> (StatusCode, &'static str, &'static str) {
+    match e {
+        WolfProxyError::BodyTooLarge(_) => {
+            (StatusCode::PAYLOAD_TOO_LARGE, "PayloadTooLarge", "Request body too large")
+        }
+        WolfProxyError::HeadersTooLarge(_) => (
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+            "HeadersTooLarge",
+            "Request headers too large",
+        ),
+        WolfProxyError::UriTooLong { .. } => (StatusCode::URI_TOO_LONG, "UriTooLong", "Request URI too long"),
+        WolfProxyError::RequestBody(_) => {
+            (StatusCode::BAD_REQUEST, "InvalidBody", "Failed to read request body")
+        }
+        WolfProxyError::ConnectTimeout { .. } | WolfProxyError::ReadTimeout(_) => {
+            (StatusCode::GATEWAY_TIMEOUT, "UpstreamTimeout", "Wolf API request timed out")
+        }
+        WolfProxyError::ConnectFailed { .. } | WolfProxyError::CircuitOpen(_) => {
+            (StatusCode::SERVICE_UNAVAILABLE, "UpstreamUnavailable", "Failed to connect to Wolf")
+        }
+        WolfProxyError::PermissionDenied { .. } => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "UpstreamPermissionDenied",
+            "Not allowed to connect to Wolf",
+        ),
+        WolfProxyError::QueueFull { .. } | WolfProxyError::QueueTimeout(_) => {
+            (StatusCode::SERVICE_UNAVAILABLE, "UpstreamBusy", "Too many concurrent Wolf requests")
+        }
+        WolfProxyError::Handshake { .. } => {
+            (StatusCode::BAD_GATEWAY, "UpstreamHandshakeFailed", "Failed to connect to Wolf")
+        }
+        WolfProxyError::InvalidRequest(_) | WolfProxyError::Upstream(_) => {
+            (StatusCode::BAD_GATEWAY, "UpstreamError", "Wolf API request failed")
+        }
+    }
+}
+
+/// Map a failed proxy call to the JSON error returned to the client
+fn proxy_error_response(e: WolfProxyError) -> Response {
+    error!("Wolf proxy request failed: {}", e);
+    let (status, code, prefix) = proxy_error_status(&e);
+    error_response(status, code, &format!("{}: {}", prefix, e))
+}
+
+/// Convert an upstream response, reporting conversion failures as 502
+async fn forward_response(
+    client: &WolfProxyClient,
+    response: http::Response<WolfBody>,
+) -> Response {
+    match client.response_to_axum(response).await {
+        Ok(axum_response) => axum_response,
         Err(e) => {
-            warn!("Wolf readiness check failed: {}", e);
+            error!("Failed to convert response: {}", e);
             error_response(
-                StatusCode::SERVICE_UNAVAILABLE,
-                "UpstreamUnavailable",
-                &format!("wolf.sock not reachable: {}", e),
+                StatusCode::BAD_GATEWAY,
+                "ResponseConversionError",
+                &format!("Failed to convert upstream response: {}", e),
             )
         }
     }
 }
 
+/// Hand a WebSocket handshake to Wolf and tunnel the connection once both sides upgrade
+async fn wolf_websocket(
+    state: &WolfProxyState,
+    mut req: Request,
+    uri: Uri,
+    client_ip: Option<IpAddr>,
+) -> Response {
+    let Some(client_upgrade) = req.extensions_mut().remove::<OnUpgrade>() else {
+        warn!("WebSocket upgrade requested on a connection that cannot upgrade");
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "UpgradeUnavailable",
+            "This connection does not support protocol upgrades",
+        );
+    };
+
+    let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+
+    match state
+        .client
+        .proxy_upgrade(uri, req.headers().clone(), client_ip)
+        .await
+    {
+        Ok(UpgradeOutcome::Switched { response, upstream }) => {
+            // The tunnel outlives this request; give its logs their own span
+            let span = info_span!("websocket_tunnel", request_id = request_id.as_deref().unwrap_or("-"));
+            let _entered = span.enter();
+            spawn_tunnel(client_upgrade, upstream);
+            response
+        }
+        Ok(UpgradeOutcome::Rejected(response)) => forward_response(&state.client, response).await,
+        Err(e) => proxy_error_response(e),
+    }
+}
+
+/// `/wolfapi` paths WolfManager answers itself. Any other path whose first segment starts
+/// with `_` is a 404 rather than a request to Wolf, so a typo like `/_redy` fails loudly.
+pub const CONTROL_PATHS: &[&str] = &["/_ready"];
+
+/// The 404 for an underscore-prefixed path that isn't one of `CONTROL_PATHS`
+fn unknown_control_path(path: &str) -> Option<Response> {
+    if !path.starts_with("/_") || CONTROL_PATHS.contains(&path) {
+        return None;
+    }
+    Some(error_response(
+        StatusCode::NOT_FOUND,
+        "UnknownControlPath",
+        &format!(
+            "/wolfapi{} is not a WolfManager control endpoint (known: {}); it was not sent to Wolf",
+            path,
+            CONTROL_PATHS.join(", ")
+        ),
+    ))
+}
+
 /// Catch-all proxy handler for Wolf API
 async fn wolf_proxy(
     State(state): State<WolfProxyState>,
@@ -45,26 +209,14 @@ async fn wolf_proxy(
     let uri = req.uri().clone();
     let headers = req.headers().clone();
 
-    // Check for WebSocket upgrade
-    if headers
-        .get("upgrade")
-        .and_then(|v| v.to_str().ok())
-        .map(|v| v.eq_ignore_ascii_case("websocket"))
-        .unwrap_or(false)
-    {
-        warn!("WebSocket upgrade attempted on Wolf proxy - not yet supported");
-        return error_response(
-            StatusCode::NOT_IMPLEMENTED,
-            "NotImplemented",
-            "WebSocket proxying is not yet supported",
-        );
-    }
-
     // Strip /wolfapi prefix from URI
     let stripped_path = uri
         .path()
         .strip_prefix("/wolfapi")
         .unwrap_or(uri.path());
+    if let Some(response) = unknown_control_path(stripped_path) {
+        return response;
+    }
 
     // Reconstruct URI with stripped path
     let new_uri = if let Some(query) = uri.query() {
@@ -85,76 +237,328 @@ async fn wolf_proxy(
         }
     };
 
-    // Extract body
-    let body = match axum::body::to_bytes(req.into_body(), usize::MAX).await {
-        Ok(b) => b,
-        Err(e) => {
-            error!("Failed to read request body: {}", e);
-            return error_response(
-                StatusCode::BAD_REQUEST,
-                "InvalidBody",
-                &format!("Failed to read request body: {}", e),
-            );
-        }
-    };
+    if !state.paths.is_allowed(new_uri.path()) {
+        warn!(path = %new_uri.path(), "Blocked Wolf API path");
+        return error_response(
+            StatusCode::FORBIDDEN,
+            "PathForbidden",
+            &format!("Wolf API path {} is not allowed", new_uri.path()),
+        );
+    }
 
     // Get client IP
-    let client_ip = Some(addr.ip().to_string());
+    let client_ip = Some(addr.ip());
+
+    if is_websocket_upgrade(&headers) {
+        return wolf_websocket(&state, req, new_uri, client_ip).await;
+    }
+
+    // Stream the body through; the proxy enforces the size limit
+    let body = req.into_body();
 
     // Proxy the request
+    let head = method == http::Method::HEAD;
     match state
         .client
         .proxy_request(method, new_uri, headers, body, client_ip)
         .await
     {
-        Ok(response) => {
-            // Convert hyper response to axum response
-            match WolfProxyClient::response_to_axum(response).await {
-                Ok(axum_response) => axum_response,
-                Err(e) => {
-                    error!("Failed to convert response: {}", e);
-                    error_response(
-                        StatusCode::BAD_GATEWAY,
-                        "ResponseConversionError",
-                        &format!("Failed to convert upstream response: {}", e),
-                    )
-                }
-            }
-        }
-        Err(e) => {
-            error!("Wolf proxy request failed: {}", e);
-
-            // Determine appropriate error response
-            let error_msg = e.to_string();
-            if error_msg.contains("timeout") {
-                error_response(
-                    StatusCode::GATEWAY_TIMEOUT,
-                    "UpstreamTimeout",
-                    &format!("Wolf API request timed out: {}", e),
-                )
-            } else if error_msg.contains("connection") || error_msg.contains("connect") {
-                error_response(
-                    StatusCode::SERVICE_UNAVAILABLE,
-                    "UpstreamUnavailable",
-                    &format!("Failed to connect to wolf.sock: {}", e),
-                )
-            } else {
-                error_response(
-                    StatusCode::BAD_GATEWAY,
-                    "UpstreamError",
-                    &format!("Wolf API request failed: {}", e),
-                )
-            }
-        }
+        // A HEAD answer is headers only, Content-Length included, even if Wolf sent a body
+        Ok(response) if head => forward_response(&state.client, response)
+            .await
+            .map(|_| Body::empty()),
+        Ok(response) => forward_response(&state.client, response).await,
+        Err(e) => proxy_error_response(e),
     }
 }
 
 /// Create Wolf API proxy router
-pub fn wolf_router(client: Arc<WolfProxyClient>) -> Router {
-    let state = WolfProxyState { client };
+pub fn wolf_router(client: Arc<WolfProxyClient>, paths: PathFilter, ready: ReadyMode) -> Router {
+    let state = WolfProxyState {
+        client,
+        paths: Arc::new(paths),
+        ready,
+    };
 
     Router::new()
         .route("/_ready", any(wolf_ready))
         .fallback(wolf_proxy)
         .with_state(state)
 }
+
+/// Answer a `/wolfapi` request from a `WolfApi` stand-in: its SSE stream when the client
+/// asks for `text/event-stream`, otherwise its passthrough body as JSON
+async fn mock_proxy(State(wolf): State<Arc<dyn WolfApi>>, req: Request) -> Response {
+    if let Some(response) = unknown_control_path(req.uri().path()) {
+        return response;
+    }
+    let method = req.method().clone();
+    let path = req.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/").to_owned();
+    let wants_stream = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/event-stream"));
+
+    if wants_stream {
+        return match wolf.sse_stream(&path).await {
+            Ok(stream) => ([(header::CONTENT_TYPE, "text/event-stream")], Body::from_stream(stream))
+                .into_response(),
+            Err(e) => error_response(StatusCode::BAD_GATEWAY, "UpstreamError", &format!("{:#}", e)),
+        };
+    }
+
+    let body = match axum::body::to_bytes(req.into_body(), usize::MAX).await {
+        Ok(body) => (!body.is_empty()).then_some(body),
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, "InvalidBody", &e.to_string()),
+    };
+    match wolf.send_passthrough(method, &path, body).await {
+        Ok(bytes) => ([(header::CONTENT_TYPE, "application/json")], bytes).into_response(),
+        Err(e) => error_response(StatusCode::BAD_GATEWAY, "UpstreamError", &format!("{:#}", e)),
+    }
+}
+
+/// `/wolfapi` for `WM_MOCK_WOLF`: canned answers from `wolf`, and always ready
+pub fn mock_wolf_router(wolf: Arc<dyn WolfApi>) -> Router {
+    Router::new()
+        .route("/_ready", any(|| async { Json(serde_json::json!({"status": "ok", "mock": true})) }))
+        .fallback(mock_proxy)
+        .with_state(wolf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+    use std::time::Duration;
+    use tower::ServiceExt;
+    use wm_adapters::wolf_proxy::WolfProxyConfig;
+    use wm_adapters::response_cache::{CacheKey, CachedResponse};
+
+    /// Serve `app` as Wolf on a Unix socket
+    fn fake_wolf(app: Router) -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let sock = dir.path().join("wolf.sock");
+        let listener = tokio::net::UnixListener::bind(&sock).unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (dir, sock.to_string_lossy().into_owned())
+    }
+
+    /// A Wolf stand-in that accepts connections but answers every request with 500
+    fn failing_wolf() -> (tempfile::TempDir, String) {
+        fake_wolf(Router::new().fallback(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+    }
+
+    async fn ready(path: &str, mode: ReadyMode) -> (StatusCode, serde_json::Value) {
+        let client = Arc::new(WolfProxyClient::new(
+            WolfProxyConfig::new(path.into(), 200, 1000).with_retry(1, 10),
+        ));
+        let resp = wolf_router(client, PathFilter::default(), mode)
+            .oneshot(Request::get("/_ready").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_head_returns_headers_without_a_body() {
+        let (_dir, path) = fake_wolf(Router::new().fallback(|| async {
+            ([(header::CONTENT_TYPE, "application/json"), (header::CONTENT_LENGTH, "11")], r#"{"apps":[]}"#)
+        }));
+        let client = Arc::new(WolfProxyClient::new(WolfProxyConfig::new(path, 200, 1000).with_retry(1, 10)));
+        let app = wolf_router(client, PathFilter::default(), ReadyMode::Connect);
+
+        let req = Request::head("/api/v1/apps")
+            .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))))
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[header::CONTENT_LENGTH], "11");
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/json");
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.is_empty(), "{body:?}");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_uri_on_a_cached_path_is_414() {
+        let (_dir, path) = fake_wolf(Router::new().fallback(|| async { r#"{"version":"1"}"# }));
+        let client = Arc::new(WolfProxyClient::new(
+            WolfProxyConfig::new(path, 200, 1000)
+                .with_retry(1, 10)
+                .with_max_uri_bytes(32)
+                .with_response_cache(vec!["/api/v1/version".into()], 60_000),
+        ));
+        let long = format!("/api/v1/version?q={}", "a".repeat(64));
+        client.response_cache().put(
+            CacheKey::new(&long.parse().unwrap(), &http::HeaderMap::new()),
+            CachedResponse {
+                status: StatusCode::OK,
+                headers: http::HeaderMap::new(),
+                body: axum::body::Bytes::from_static(b"{}"),
+            },
+        );
+        let app = wolf_router(client, PathFilter::default(), ReadyMode::Connect);
+
+        let req = Request::get(long)
+            .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))))
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::URI_TOO_LONG);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_control_paths_are_local_404s() {
+        let (_dir, path) = failing_wolf();
+        let client = Arc::new(WolfProxyClient::new(WolfProxyConfig::new(path, 200, 1000).with_retry(1, 10)));
+        let app = wolf_router(client, PathFilter::default(), ReadyMode::Connect);
+        let call = |uri: &str| {
+            let req = Request::get(uri)
+                .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))))
+                .body(Body::empty())
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let resp = app.oneshot(req).await.unwrap();
+                let status = resp.status();
+                let body = resp.into_body().collect().await.unwrap().to_bytes();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+
+        let (status, body) = call("/_ready").await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+
+        for uri in ["/_bogus", "/_redy", "/_ready/extra"] {
+            let (status, body) = call(uri).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{uri}");
+            assert!(body.contains("UnknownControlPath") && body.contains("/_ready"), "{uri}: {body}");
+        }
+
+        // Genuine Wolf paths are proxied; this Wolf answers everything with 500
+        let (status, _) = call("/api/clients").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_connect_mode_ignores_failing_endpoint() {
+        let (_dir, path) = failing_wolf();
+        let (status, body) = ready(&path, ReadyMode::Connect).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_request_mode_reports_failed_check() {
+        let (_dir, path) = failing_wolf();
+        let (status, body) = ready(&path, ReadyMode::Request("/api/v1/apps".into())).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["check"], "request");
+        assert!(body["detail"].as_str().unwrap().contains("500"), "{body}");
+
+        let (status, body) =
+            ready("/nonexistent/wolf.sock", ReadyMode::Request("/api/v1/apps".into())).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["check"], "connect");
+    }
+
+    #[test]
+    fn test_proxy_errors_map_to_statuses() {
+        let target = || "wolf.sock".to_string();
+        let reason = || "boom".to_string();
+        let cases = [
+            (WolfProxyError::BodyTooLarge(1024), StatusCode::PAYLOAD_TOO_LARGE, "PayloadTooLarge"),
+            (
+                WolfProxyError::HeadersTooLarge(reason()),
+                StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+                "HeadersTooLarge",
+            ),
+            (
+                WolfProxyError::UriTooLong { len: 20000, limit: 16384 },
+                StatusCode::URI_TOO_LONG,
+                "UriTooLong",
+            ),
+            (WolfProxyError::RequestBody(reason()), StatusCode::BAD_REQUEST, "InvalidBody"),
+            (
+                WolfProxyError::ConnectTimeout { attempts: 3 },
+                StatusCode::GATEWAY_TIMEOUT,
+                "UpstreamTimeout",
+            ),
+            (
+                WolfProxyError::ReadTimeout(Duration::from_secs(30)),
+                StatusCode::GATEWAY_TIMEOUT,
+                "UpstreamTimeout",
+            ),
+            (
+                WolfProxyError::ConnectFailed { target: target(), reason: reason() },
+                StatusCode::SERVICE_UNAVAILABLE,
+                "UpstreamUnavailable",
+            ),
+            (
+                WolfProxyError::CircuitOpen(Duration::from_secs(5)),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "UpstreamUnavailable",
+            ),
+            (
+                WolfProxyError::PermissionDenied { target: target(), reason: reason() },
+                StatusCode::SERVICE_UNAVAILABLE,
+                "UpstreamPermissionDenied",
+            ),
+            (
+                WolfProxyError::QueueFull { limit: 8 },
+                StatusCode::SERVICE_UNAVAILABLE,
+                "UpstreamBusy",
+            ),
+            (
+                WolfProxyError::QueueTimeout(Duration::from_secs(5)),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "UpstreamBusy",
+            ),
+            (
+                WolfProxyError::Handshake { target: target(), reason: reason() },
+                StatusCode::BAD_GATEWAY,
+                "UpstreamHandshakeFailed",
+            ),
+            (WolfProxyError::InvalidRequest(reason()), StatusCode::BAD_GATEWAY, "UpstreamError"),
+            (WolfProxyError::Upstream(reason()), StatusCode::BAD_GATEWAY, "UpstreamError"),
+        ];
+
+        for (error, status, code) in cases {
+            let response = proxy_error_response(error.clone());
+            assert_eq!(response.status(), status, "{error:?}");
+            assert_eq!(proxy_error_status(&error).1, code, "{error:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_and_malformed_bodies() {
+        use http_body_util::BodyExt;
+
+        async fn json(response: Response) -> serde_json::Value {
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice(&body).unwrap()
+        }
+
+        let response = proxy_error_response(WolfProxyError::BodyTooLarge(1024));
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = json(response).await;
+        assert_eq!(body["error"], "PayloadTooLarge");
+        assert!(body["detail"].as_str().unwrap().contains("1024 bytes"), "{body}");
+
+        let response = proxy_error_response(WolfProxyError::RequestBody("unexpected EOF".into()));
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = json(response).await;
+        assert_eq!(body["error"], "InvalidBody");
+        assert!(body["detail"].as_str().unwrap().contains("unexpected EOF"), "{body}");
+    }
+
+    #[test]
+    fn test_status_does_not_depend_on_wording() {
+        // Messages that used to be substring-matched no longer steer the status
+        let error = WolfProxyError::Upstream("connection timeout exceeds limit".into());
+        assert_eq!(proxy_error_status(&error).0, StatusCode::BAD_GATEWAY);
+    }
+}
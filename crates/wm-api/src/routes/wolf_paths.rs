@@ -0,0 +1,132 @@
+//! Allow/deny rules for which Wolf API paths `/wolfapi` forwards
+
+/// Path rules checked before a request is proxied to Wolf.
+///
+/// Patterns without `*` match the path and everything below it (`/api/v1/apps`
+/// matches `/api/v1/apps/42` but not `/api/v1/appsX`). In patterns with `*`, a
+/// single `*` matches within one path segment and `**` matches across segments.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl PathFilter {
+    pub fn new(allow: Vec<String>, deny: Vec<String>) -> Self {
+        Self { allow, deny }
+    }
+
+    /// Denied paths are always blocked; otherwise an empty allowlist allows everything
+    pub fn is_allowed(&self, path: &str) -> bool {
+        let path = normalize(path);
+        if self.deny.iter().any(|p| matches(p, &path)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|p| matches(p, &path))
+    }
+}
+
+/// Decode percent escapes and resolve `.`/`..`/empty segments, so encoded or
+/// dotted variants of a path can't slip past the rules
+fn normalize(path: &str) -> String {
+    let decoded = percent_decode(path);
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in decoded.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            s => segments.push(s),
+        }
+    }
+    format!("/{}", segments.join("/"))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    let hex = |b: u8| (b as char).to_digit(16);
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push((hi * 16 + lo) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn matches(pattern: &str, path: &str) -> bool {
+    if !pattern.contains('*') {
+        let prefix = pattern.trim_end_matches('/');
+        return path == prefix
+            || prefix.is_empty()
+            || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'));
+    }
+    glob(pattern.as_bytes(), path.as_bytes())
+}
+
+/// `*` matches any run of non-`/` bytes, `**` any run of bytes
+fn glob(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=path.len()).any(|i| glob(rest, &path[i..])),
+        [b'*', rest @ ..] => {
+            let segment_end = path.iter().position(|&b| b == b'/').unwrap_or(path.len());
+            (0..=segment_end).any(|i| glob(rest, &path[i..]))
+        }
+        [c, rest @ ..] => path.first() == Some(c) && glob(rest, &path[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(allow: &[&str], deny: &[&str]) -> PathFilter {
+        PathFilter::new(
+            allow.iter().map(|s| s.to_string()).collect(),
+            deny.iter().map(|s| s.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_empty_rules_allow_all() {
+        assert!(filter(&[], &[]).is_allowed("/api/v1/apps"));
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let f = filter(&["/api/v1/apps", "/api/v1/sessions/*/status"], &[]);
+        assert!(f.is_allowed("/api/v1/apps"));
+        assert!(f.is_allowed("/api/v1/apps/42"));
+        assert!(f.is_allowed("/api/v1/sessions/abc/status"));
+        assert!(!f.is_allowed("/api/v1/appsX"));
+        assert!(!f.is_allowed("/api/v1/sessions/abc/def/status"));
+        assert!(!f.is_allowed("/api/v1/clients"));
+    }
+
+    #[test]
+    fn test_denylist_wins_over_allowlist() {
+        let f = filter(&["/api/v1/**"], &["/api/v1/admin", "/api/v1/**/delete"]);
+        assert!(f.is_allowed("/api/v1/apps"));
+        assert!(!f.is_allowed("/api/v1/admin"));
+        assert!(!f.is_allowed("/api/v1/admin/shutdown"));
+        assert!(!f.is_allowed("/api/v1/apps/42/delete"));
+    }
+
+    #[test]
+    fn test_encoded_and_dotted_paths_are_normalized() {
+        let f = filter(&[], &["/api/v1/admin"]);
+        assert!(!f.is_allowed("/api/v1/apps/../admin"));
+        assert!(!f.is_allowed("/api/v1//admin"));
+        assert!(!f.is_allowed("/api/v1/%61dmin"));
+        assert!(!f.is_allowed("/api/v1/apps/%2e%2e/admin"));
+    }
+}
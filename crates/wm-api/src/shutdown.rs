@@ -0,0 +1,253 @@
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+    serve::ListenerExt,
+    Router,
+};
+use http_body_util::BodyExt;
+use std::future::{Future, IntoFuture};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::connection_limit::LimitedListener;
+use crate::listener::{ApiListener, UNIX_PEER};
+
+/// Resolves on Ctrl-C or SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl-C, shutting down"),
+        _ = terminate => info!("Received SIGTERM, shutting down"),
+    }
+}
+
+/// Broadcast that the server is shutting down so long-lived streams can end
+#[derive(Clone)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(false);
+        Self { tx: Arc::new(tx) }
+    }
+
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// Resolves once [`Shutdown::trigger`] has been called
+    pub fn wait(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut rx = self.tx.subscribe();
+        async move {
+            let _ = rx.wait_for(|stopping| *stopping).await;
+        }
+    }
+}
+
+/// Requests whose response body has not finished yet, SSE streams included
+#[derive(Clone, Default)]
+pub struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+struct InFlightGuard(Arc<AtomicUsize>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub async fn track_in_flight(State(in_flight): State<InFlight>, req: Request, next: Next) -> Response {
+    in_flight.0.fetch_add(1, Ordering::Relaxed);
+    let guard = InFlightGuard(in_flight.0.clone());
+
+    // Hold the guard until the body is fully sent or dropped
+    next.run(req).await.map(|body| {
+        Body::new(body.map_frame(move |frame| {
+            let _ = &guard;
+            frame
+        }))
+    })
+}
+
+/// Serve `app` until `signal` resolves, then stop accepting connections and give
+/// in-flight requests up to `grace` to finish. At most `max_connections` are open at once.
+pub async fn serve(
+    listener: ApiListener,
+    app: Router,
+    signal: impl Future<Output = ()> + Send + 'static,
+    shutdown: Shutdown,
+    in_flight: InFlight,
+    grace: Duration,
+    max_connections: Option<usize>,
+) -> anyhow::Result<()> {
+    let app = app.layer(axum::middleware::from_fn_with_state(in_flight.clone(), track_in_flight));
+    let stopping = shutdown.wait();
+
+    let graceful = async move {
+        signal.await;
+        // End SSE streams so their connections can drain
+        shutdown.trigger();
+    };
+    let server: Pin<Box<dyn Future<Output = std::io::Result<()>> + Send>> = match listener {
+        // axum derives `ConnectInfo<SocketAddr>` only for a bare `TcpListener` or a `TapIo`
+        // wrapping any listener, hence the no-op tap
+        ApiListener::Tcp(listener) => Box::pin(
+            axum::serve(
+                LimitedListener::new(listener, max_connections).tap_io(|_| ()),
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+                .with_graceful_shutdown(graceful)
+                .into_future(),
+        ),
+        // Handlers still find a `ConnectInfo<SocketAddr>`, standing in for the peer's
+        ApiListener::Unix(listener) => Box::pin(
+            axum::serve(
+                LimitedListener::new(listener, max_connections),
+                app.layer(axum::Extension(ConnectInfo(UNIX_PEER))).into_make_service(),
+            )
+                .with_graceful_shutdown(graceful)
+                .into_future(),
+        ),
+    };
+
+    tokio::select! {
+        result = server => {
+            result?;
+            info!("Server stopped cleanly");
+        }
+        _ = async {
+            stopping.await;
+            tokio::time::sleep(grace).await;
+        } => {
+            warn!(
+                open_connections = in_flight.count(),
+                grace_ms = grace.as_millis(),
+                "Shutdown grace period expired with connections still open"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::oneshot;
+
+    async fn start(
+        app: Router,
+        grace: Duration,
+    ) -> (SocketAddr, oneshot::Sender<()>, tokio::task::JoinHandle<anyhow::Result<()>>, InFlight) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+        let in_flight = InFlight::default();
+        let handle = tokio::spawn(serve(
+            ApiListener::Tcp(listener),
+            app,
+            async move {
+                let _ = rx.await;
+            },
+            Shutdown::new(),
+            in_flight.clone(),
+            grace,
+            None,
+        ));
+        (addr, tx, handle, in_flight)
+    }
+
+    async fn send_get(addr: SocketAddr, path: &str) -> tokio::net::TcpStream {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let req = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        stream.write_all(req.as_bytes()).await.unwrap();
+        stream
+    }
+
+    #[tokio::test]
+    async fn test_signal_resolves_server() {
+        let app = Router::new().route("/healthz", get(|| async { "ok" }));
+        let (addr, tx, handle, _) = start(app, Duration::from_secs(5)).await;
+
+        let mut stream = send_get(addr, "/healthz").await;
+        let mut buf = [0u8; 64];
+        let n = stream.read(&mut buf).await.unwrap();
+        assert!(String::from_utf8_lossy(&buf[..n]).starts_with("HTTP/1.1 200"));
+        drop(stream);
+
+        tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(2), handle)
+            .await
+            .expect("server did not shut down")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_grace_period_bounds_slow_requests() {
+        let app = Router::new().route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                "late"
+            }),
+        );
+        let (addr, tx, handle, in_flight) = start(app, Duration::from_millis(100)).await;
+
+        let _stream = send_get(addr, "/slow").await;
+        while in_flight.count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(2), handle)
+            .await
+            .expect("grace period was not enforced")
+            .unwrap()
+            .unwrap();
+        assert_eq!(in_flight.count(), 1);
+    }
+}
@@ -0,0 +1,62 @@
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// Reverse proxies (`WM_TRUSTED_PROXIES`) whose forwarding headers we believe
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Arc<[IpNet]>);
+
+impl TrustedProxies {
+    pub fn new(networks: Vec<IpNet>) -> Self {
+        Self(networks.into())
+    }
+
+    /// Whether `ip` falls in any trusted range. IPv4-mapped IPv6 addresses match IPv4 ranges.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.0.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Whether the direct peer is a trusted proxy, so its `X-Forwarded-For` may name the client
+    pub fn is_trusted_peer(&self, addr: &SocketAddr) -> bool {
+        self.contains(&addr.ip())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxies(cidrs: &[&str]) -> TrustedProxies {
+        TrustedProxies::new(cidrs.iter().map(|c| c.parse().unwrap()).collect())
+    }
+
+    fn peer(ip: &str) -> SocketAddr {
+        SocketAddr::new(ip.parse().unwrap(), 40000)
+    }
+
+    #[test]
+    fn test_ipv4_ranges() {
+        let trusted = proxies(&["10.0.0.0/8", "192.168.1.5/32"]);
+        assert!(trusted.is_trusted_peer(&peer("10.1.2.3")));
+        assert!(trusted.is_trusted_peer(&peer("192.168.1.5")));
+        assert!(!trusted.is_trusted_peer(&peer("192.168.1.6")));
+        assert!(!trusted.is_trusted_peer(&peer("11.0.0.1")));
+        // A v4-mapped peer (dual-stack listener) is the same host
+        assert!(trusted.is_trusted_peer(&peer("::ffff:10.0.0.1")));
+    }
+
+    #[test]
+    fn test_ipv6_ranges() {
+        let trusted = proxies(&["fd00::/8", "2001:db8::1/128"]);
+        assert!(trusted.is_trusted_peer(&peer("fd12:3456::1")));
+        assert!(trusted.is_trusted_peer(&peer("2001:db8::1")));
+        assert!(!trusted.is_trusted_peer(&peer("2001:db8::2")));
+        assert!(!trusted.is_trusted_peer(&peer("10.0.0.1")));
+    }
+
+    #[test]
+    fn test_nothing_trusted_by_default() {
+        assert!(!TrustedProxies::default().is_trusted_peer(&peer("127.0.0.1")));
+    }
+}
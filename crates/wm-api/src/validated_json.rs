@@ -0,0 +1,187 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use wm_adapters::wolf_proxy::{error_response, ErrorBody};
+
+/// JSON request body whose deserialization failures are reported field by field.
+/// Well-formed JSON that doesn't fit `T` is a 422 whose `fields` name each offending path
+/// and, when serde says, the type it expected; a body that isn't JSON at all stays a 400.
+pub struct ValidatedJson<T>(pub T);
+
+/// One entry of a 422 body's `fields`
+#[derive(Debug, Serialize)]
+struct FieldError {
+    /// Dotted path to the field, e.g. `settings.volume` or `apps[2].id`
+    field: String,
+    problem: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected: Option<String>,
+}
+
+/// Split a serde message into the problem and the type it wanted:
+/// `invalid type: string "x", expected u32` becomes (`invalid type: string "x"`, `u32`)
+fn describe(message: &str) -> (String, Option<String>) {
+    // serde_json appends the position, which means little to a client that sent the body
+    let message = message.split(" at line ").next().unwrap_or(message);
+    match message.split_once(", expected ") {
+        Some((problem, expected)) => (problem.to_owned(), Some(expected.to_owned())),
+        None => (message.to_owned(), None),
+    }
+}
+
+fn field_error(path: &str, message: &str) -> FieldError {
+    let (problem, expected) = describe(message);
+    // A missing field is reported at its parent; name the field itself
+    let missing = problem
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.strip_suffix('`'));
+    let field = match (path, missing) {
+        (".", Some(name)) => name.to_owned(),
+        (parent, Some(name)) => format!("{}.{}", parent, name),
+        (path, None) => path.to_owned(),
+    };
+    FieldError {
+        field,
+        problem: if missing.is_some() { "missing".to_owned() } else { problem },
+        expected,
+    }
+}
+
+fn validation_failed(error: FieldError) -> Response {
+    let detail = format!("{}: {}", error.field, error.problem);
+    let body = ErrorBody::new("InvalidBody", &detail);
+    let mut json = serde_json::json!(body);
+    json["fields"] = serde_json::json!([error]);
+    let mut response = (StatusCode::UNPROCESSABLE_ENTITY, Json(json)).into_response();
+    response.extensions_mut().insert(body);
+    response
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+        .is_some_and(|mime| mime == "application/json" || mime.ends_with("+json"))
+}
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_json(req.headers()) {
+            return Err(error_response(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "UnsupportedMediaType",
+                "expected a request with `Content-Type: application/json`",
+            ));
+        }
+        let body = Bytes::from_request(req, state).await.map_err(IntoResponse::into_response)?;
+
+        let not_json = |e: serde_json::Error| {
+            error_response(
+                StatusCode::BAD_REQUEST,
+                "InvalidBody",
+                &format!("request body is not valid JSON: {}", e),
+            )
+        };
+        let mut de = serde_json::Deserializer::from_slice(&body);
+        let value = match serde_path_to_error::deserialize(&mut de) {
+            Ok(value) => value,
+            Err(e) if e.inner().is_data() => {
+                return Err(validation_failed(field_error(&e.path().to_string(), &e.inner().to_string())));
+            }
+            Err(e) => return Err(not_json(e.into_inner())),
+        };
+        de.end().map_err(not_json)?;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::post, Router};
+    use http_body_util::BodyExt;
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Volume {
+        level: u8,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Settings {
+        name: String,
+        volume: Volume,
+    }
+
+    async fn call(content_type: &str, body: &str) -> (StatusCode, serde_json::Value) {
+        let app = Router::new().route("/", post(|ValidatedJson(_): ValidatedJson<Settings>| async { "ok" }));
+        let req = http::Request::post("/")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body.to_owned()))
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        let status = resp.status();
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_valid_body_passes() {
+        let (status, _) = call("application/json", r#"{"name":"tv","volume":{"level":3}}"#).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_missing_field_is_422() {
+        let (status, body) = call("application/json", r#"{"volume":{"level":3}}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"], "InvalidBody");
+        assert_eq!(body["fields"][0]["field"], "name");
+        assert_eq!(body["fields"][0]["problem"], "missing");
+
+        let (_, body) = call("application/json", r#"{"name":"tv","volume":{}}"#).await;
+        assert_eq!(body["fields"][0]["field"], "volume.level", "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_wrong_type_is_422_with_expected_type() {
+        let (status, body) = call("application/json", r#"{"name":"tv","volume":{"level":"loud"}}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let field = &body["fields"][0];
+        assert_eq!(field["field"], "volume.level");
+        assert_eq!(field["problem"], r#"invalid type: string "loud""#);
+        assert_eq!(field["expected"], "u8");
+        assert_eq!(body["detail"], r#"volume.level: invalid type: string "loud""#);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_json_and_wrong_content_type() {
+        let (status, body) = call("application/json", r#"{"name":"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "InvalidBody");
+        assert!(body.get("fields").is_none());
+
+        let (status, _) = call("application/json", r#"{"name":"tv","volume":{"level":3}} trailing"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = call("text/plain", "{}").await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(body["error"], "UnsupportedMediaType");
+    }
+}
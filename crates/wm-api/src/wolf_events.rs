@@ -0,0 +1,210 @@
+use futures_util::StreamExt;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+use wm_adapters::sse::SseDecoder;
+use wm_adapters::wolf_backoff::Backoff;
+use wm_adapters::WolfApi;
+use wm_core::wolf::WolfRawEvent;
+use wm_core::Normalize;
+
+use crate::event_sinks::EventFanout;
+use crate::shutdown::Shutdown;
+
+/// Normalize one Wolf payload and hand the resulting domain events to the sinks
+async fn handle_payload(fanout: &EventFanout, payload: &str) {
+    let raw: WolfRawEvent = match serde_json::from_str(payload) {
+        Ok(raw) => raw,
+        Err(e) => {
+            debug!("Skipping unparseable Wolf event: {}", e);
+            return;
+        }
+    };
+    for event in raw.normalize() {
+        fanout.deliver(event).await;
+    }
+}
+
+/// Follow one connection to Wolf's event stream until it ends.
+/// Returns whether anything arrived, keepalive comments included, so a healthy stream
+/// resets the backoff.
+async fn follow(wolf: &dyn WolfApi, path: &str, fanout: &EventFanout) -> anyhow::Result<bool> {
+    let mut stream = wolf.sse_stream(path).await?;
+    info!(path, "Connected to Wolf event stream");
+    let mut decoder = SseDecoder::new();
+    let mut received = false;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        received |= !chunk.is_empty();
+        for event in decoder.push(&chunk) {
+            handle_payload(fanout, &event.data).await;
+        }
+    }
+    Ok(received)
+}
+
+/// Pull Wolf's event stream at `path` into `fanout`'s sinks (storage and the SSE broadcast
+/// among them), reconnecting with `backoff` whenever the stream fails or ends, until shutdown
+pub fn spawn_wolf_ingestion(
+    wolf: Arc<dyn WolfApi>,
+    path: String,
+    fanout: EventFanout,
+    backoff: Backoff,
+    shutdown: Shutdown,
+) {
+    tokio::spawn(async move {
+        let stop = shutdown.wait();
+        tokio::pin!(stop);
+        let mut attempt = 0;
+        loop {
+            let result = tokio::select! {
+                _ = &mut stop => break,
+                result = follow(wolf.as_ref(), &path, &fanout) => result,
+            };
+            match result {
+                Ok(true) => {
+                    attempt = 0;
+                    warn!("Wolf event stream ended; reconnecting");
+                }
+                Ok(false) => warn!("Wolf event stream ended before sending anything"),
+                Err(e) => warn!("Wolf event stream failed: {:#}", e),
+            }
+            attempt += 1;
+            tokio::select! {
+                _ = &mut stop => break,
+                _ = tokio::time::sleep(backoff.delay(attempt)) => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use axum::body::Bytes;
+    use futures_core::Stream;
+    use futures_util::stream;
+    use http::Method;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use wm_adapters::wolf_backoff::BackoffStrategy;
+    use wm_core::{Event, EventSink};
+    use crate::event_sinks::BroadcastSink;
+    use crate::AppState;
+    use wm_storage::{list_events_after, migrate, new_pool};
+
+    const CLIENT: &str = "7f1c2d3e-0000-4000-8000-000000000001";
+    const SESSION: &str = "7f1c2d3e-0000-4000-8000-000000000002";
+
+    type ChunkStream = Pin<Box<dyn Stream<Item = anyhow::Result<Bytes>> + Send>>;
+
+    /// Serves `connections` in order, one per `sse_stream` call; each stream stays open after
+    /// its last chunk. Calls past the end fail, as if Wolf were down.
+    struct ScriptedWolf {
+        connections: Vec<Result<Vec<String>, &'static str>>,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl WolfApi for ScriptedWolf {
+        async fn send_passthrough(&self, _: Method, _: &str, _: Option<Bytes>) -> anyhow::Result<Bytes> {
+            Err(anyhow!("not used"))
+        }
+
+        async fn sse_stream(&self, path: &str) -> anyhow::Result<ChunkStream> {
+            assert_eq!(path, "/api/v1/events");
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.connections.get(call) {
+                Some(Ok(chunks)) => {
+                    let chunks = stream::iter(chunks.clone()).map(|c| Ok(Bytes::from(c)));
+                    Ok(Box::pin(chunks.chain(stream::pending())))
+                }
+                Some(Err(e)) => Err(anyhow!(*e)),
+                None => Err(anyhow!("connection refused")),
+            }
+        }
+    }
+
+    fn backoff() -> Backoff {
+        Backoff {
+            strategy: BackoffStrategy::Fixed,
+            base: Duration::from_millis(10),
+            max: Duration::from_millis(10),
+            jitter: false,
+        }
+    }
+
+    async fn state() -> AppState {
+        let pool = new_pool("sqlite::memory:").await.unwrap();
+        migrate(&pool).await.unwrap();
+        AppState::new(pool, &wm_config::Config::default())
+    }
+
+    /// Deliver to storage and the broadcast only, as with no webhook configured
+    fn fanout(state: &AppState) -> EventFanout {
+        let broadcast: Arc<dyn EventSink> = Arc::new(BroadcastSink::new(state.clone()));
+        EventFanout::new(state.dedup.clone(), vec![broadcast])
+    }
+
+    fn spawn(wolf: ScriptedWolf, state: &AppState) -> Shutdown {
+        let shutdown = Shutdown::new();
+        let path = "/api/v1/events".to_owned();
+        spawn_wolf_ingestion(Arc::new(wolf), path, fanout(state), backoff(), shutdown.clone());
+        shutdown
+    }
+
+    async fn next_event(rx: &mut tokio::sync::broadcast::Receiver<wm_storage::StoredEvent>) -> Event {
+        tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .expect("no event within timeout")
+            .unwrap()
+            .event
+    }
+
+    #[tokio::test]
+    async fn test_wolf_events_reach_subscribers_and_storage() {
+        let state = state().await;
+        let mut rx = state.events.subscribe();
+        let connected = format!(r#"data: {{"type":"client_connected","client_id":"{CLIENT}"}}"#);
+        let started = format!("data: {{\"type\":\"session_started\",\r\ndata: \"session_id\":\"{SESSION}\"}}\r\n\r\n");
+        let (head, tail) = connected.split_at(20);
+        let wolf = ScriptedWolf {
+            connections: vec![Ok(vec![
+                ": keepalive\n\n".to_owned(),
+                head.to_owned(),
+                tail.to_owned(),
+                "\n\ndata: {\"type\":\"volume_changed\"}\n\n".to_owned(),
+                started,
+            ])],
+            calls: AtomicUsize::new(0),
+        };
+        let shutdown = spawn(wolf, &state);
+
+        // A frame split across chunks, then a multi-line CRLF frame; the unmodelled one is skipped
+        assert!(matches!(next_event(&mut rx).await, Event::ClientConnected { client_id, .. } if client_id.0.to_string() == CLIENT));
+        assert!(matches!(next_event(&mut rx).await, Event::SessionStarted { session_id, .. } if session_id.0.to_string() == SESSION));
+        assert_eq!(list_events_after(&state.pool, 0, 10).await.unwrap().len(), 2);
+        shutdown.trigger();
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_stream_failure() {
+        let state = state().await;
+        let mut rx = state.events.subscribe();
+        let started = format!("data: {{\"type\":\"session_started\",\"session_id\":\"{SESSION}\"}}\n\n");
+        let wolf = Arc::new(ScriptedWolf {
+            connections: vec![Err("wolf.sock not found"), Err("wolf.sock not found"), Ok(vec![started])],
+            calls: AtomicUsize::new(0),
+        });
+        let shutdown = Shutdown::new();
+        spawn_wolf_ingestion(wolf.clone(), "/api/v1/events".into(), fanout(&state), backoff(), shutdown.clone());
+
+        assert!(matches!(next_event(&mut rx).await, Event::SessionStarted { .. }));
+        assert_eq!(wolf.calls.load(Ordering::SeqCst), 3);
+
+        shutdown.trigger();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(wolf.calls.load(Ordering::SeqCst), 3, "no reconnects after shutdown");
+    }
+}
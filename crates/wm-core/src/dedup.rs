@@ -0,0 +1,125 @@
+//! Collapse repeated events before they are stored and broadcast
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+use time::OffsetDateTime;
+
+use crate::Event;
+
+/// Hash of everything in an event except its timestamp, so a repeat stamped a moment later matches
+pub fn content_hash(event: &Event) -> u64 {
+    let mut value = serde_json::to_value(event).unwrap_or_default();
+    if let Some(data) = value.get_mut("data").and_then(|d| d.as_object_mut()) {
+        data.remove("at");
+    }
+    let mut hasher = DefaultHasher::new();
+    value.to_string().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Drops an event identical to the one just before it when both fall within `window`.
+/// Only consecutive repeats collapse: connect, disconnect, connect again is three events.
+#[derive(Debug, Clone)]
+pub struct EventDedup {
+    window: Duration,
+    last: Option<(u64, OffsetDateTime)>,
+}
+
+impl EventDedup {
+    /// A zero `window` admits everything
+    pub fn new(window: Duration) -> Self {
+        Self { window, last: None }
+    }
+
+    /// Whether `event` should go on to storage and subscribers
+    pub fn admit(&mut self, event: &Event) -> bool {
+        if self.window.is_zero() {
+            return true;
+        }
+        let hash = content_hash(event);
+        let at = event.at();
+        if let Some((last_hash, last_at)) = self.last {
+            if last_hash == hash && (at - last_at).abs() < self.window {
+                // Measure from the first of a burst so a steady repeat still gets through once per window
+                return false;
+            }
+        }
+        self.last = Some((hash, at));
+        true
+    }
+
+    /// Keep only the events `admit` lets through, in order
+    pub fn filter(&mut self, events: Vec<Event>) -> Vec<Event> {
+        events.into_iter().filter(|event| self.admit(event)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientId, EventBuilder, FixedClock};
+    use time::macros::datetime;
+    use uuid::Uuid;
+
+    const WINDOW: Duration = Duration::from_secs(2);
+
+    fn connected(client: u128, at: OffsetDateTime) -> Event {
+        EventBuilder::new(FixedClock(at)).client_connected(ClientId(Uuid::from_u128(client)))
+    }
+
+    fn disconnected(client: u128, at: OffsetDateTime) -> Event {
+        EventBuilder::new(FixedClock(at)).client_disconnected(ClientId(Uuid::from_u128(client)))
+    }
+
+    #[test]
+    fn test_repeats_within_window_collapse() {
+        let t = datetime!(2025-01-02 03:04:05 UTC);
+        let mut dedup = EventDedup::new(WINDOW);
+        let kept = dedup.filter(vec![
+            connected(1, t),
+            connected(1, t),
+            connected(1, t + Duration::from_millis(1500)),
+        ]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].at(), t);
+    }
+
+    #[test]
+    fn test_repeats_outside_window_pass() {
+        let t = datetime!(2025-01-02 03:04:05 UTC);
+        let mut dedup = EventDedup::new(WINDOW);
+        assert!(dedup.admit(&connected(1, t)));
+        assert!(dedup.admit(&connected(1, t + WINDOW)));
+        assert!(!dedup.admit(&connected(1, t + WINDOW + Duration::from_millis(10))));
+    }
+
+    #[test]
+    fn test_only_consecutive_identical_events_collapse() {
+        let t = datetime!(2025-01-02 03:04:05 UTC);
+        let mut dedup = EventDedup::new(WINDOW);
+        let kept = dedup.filter(vec![
+            connected(1, t),
+            connected(2, t),
+            connected(1, t),
+            disconnected(1, t),
+            connected(1, t),
+        ]);
+        assert_eq!(kept.len(), 5);
+    }
+
+    #[test]
+    fn test_zero_window_disables_dedup() {
+        let t = datetime!(2025-01-02 03:04:05 UTC);
+        let mut dedup = EventDedup::new(Duration::ZERO);
+        assert_eq!(dedup.filter(vec![connected(1, t), connected(1, t)]).len(), 2);
+    }
+
+    #[test]
+    fn test_content_hash_ignores_timestamp() {
+        let t = datetime!(2025-01-02 03:04:05 UTC);
+        assert_eq!(content_hash(&connected(1, t)), content_hash(&connected(1, t + WINDOW)));
+        assert_ne!(content_hash(&connected(1, t)), content_hash(&connected(2, t)));
+        assert_ne!(content_hash(&connected(1, t)), content_hash(&disconnected(1, t)));
+    }
+}
@@ -1,31 +1,318 @@
 use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
 use time::OffsetDateTime;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+pub mod clock;
+pub mod dedup;
+pub mod sink;
+pub mod wolf;
+
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use sink::EventSink;
+
 // Domain ID types
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash, ToSchema)]
 pub struct UserId(pub Uuid);
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash, ToSchema)]
 pub struct ClientId(pub Uuid);
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash, ToSchema)]
 pub struct PairingId(pub Uuid);
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash, ToSchema)]
 pub struct SessionId(pub Uuid);
 
-// Domain events
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// IDs print and parse as their bare UUID, so they work directly in `Path` extractors and `format!`
+macro_rules! uuid_id {
+    ($($id:ident),+) => {$(
+        impl fmt::Display for $id {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl FromStr for $id {
+            type Err = uuid::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Uuid::parse_str(s).map(Self)
+            }
+        }
+
+        impl From<Uuid> for $id {
+            fn from(id: Uuid) -> Self {
+                Self(id)
+            }
+        }
+    )+};
+}
+
+uuid_id!(UserId, ClientId, PairingId, SessionId);
+
+/// Version of the `Event` payload shape. Readers accept every earlier version.
+/// 1: IDs and timestamps only. 2: optional `ClientConnected.name` and `PairingCreated.pin`.
+pub const EVENT_SCHEMA_VERSION: u32 = 2;
+
+// Domain events. `at` uses time's default serde form:
+// `[year, ordinal day, hour, minute, second, nanosecond, offset hours, offset minutes, offset seconds]`
+// Fields added after v1 are optional and omitted when empty, so old payloads still parse and
+// new payloads without them serialize exactly as before.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(tag = "type", content = "data")]
 pub enum Event {
-    ClientConnected { client_id: ClientId, at: OffsetDateTime },
-    ClientDisconnected { client_id: ClientId, at: OffsetDateTime },
-    PairingCreated { pairing_id: PairingId, at: OffsetDateTime },
-    SessionStarted { session_id: SessionId, at: OffsetDateTime },
-    SessionEnded { session_id: SessionId, at: OffsetDateTime },
+    ClientConnected {
+        client_id: ClientId,
+        /// Client's display name, when Wolf reports one
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[schema(value_type = Vec<i64>)]
+        at: OffsetDateTime,
+    },
+    ClientDisconnected {
+        client_id: ClientId,
+        #[schema(value_type = Vec<i64>)]
+        at: OffsetDateTime,
+    },
+    PairingCreated {
+        pairing_id: PairingId,
+        /// PIN the user enters in Moonlight to complete pairing
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pin: Option<String>,
+        #[schema(value_type = Vec<i64>)]
+        at: OffsetDateTime,
+    },
+    SessionStarted {
+        session_id: SessionId,
+        #[schema(value_type = Vec<i64>)]
+        at: OffsetDateTime,
+    },
+    SessionEnded {
+        session_id: SessionId,
+        #[schema(value_type = Vec<i64>)]
+        at: OffsetDateTime,
+    },
+}
+
+/// Builds events stamped with `clock`'s current time; optional fields start empty
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventBuilder<C = SystemClock> {
+    clock: C,
+}
+
+impl<C: Clock> EventBuilder<C> {
+    pub fn new(clock: C) -> Self {
+        Self { clock }
+    }
+
+    pub fn client_connected(&self, client_id: ClientId) -> Event {
+        Event::ClientConnected { client_id, name: None, at: self.clock.now() }
+    }
+
+    pub fn client_disconnected(&self, client_id: ClientId) -> Event {
+        Event::ClientDisconnected { client_id, at: self.clock.now() }
+    }
+
+    pub fn pairing_created(&self, pairing_id: PairingId) -> Event {
+        Event::PairingCreated { pairing_id, pin: None, at: self.clock.now() }
+    }
+
+    pub fn session_started(&self, session_id: SessionId) -> Event {
+        Event::SessionStarted { session_id, at: self.clock.now() }
+    }
+
+    pub fn session_ended(&self, session_id: SessionId) -> Event {
+        Event::SessionEnded { session_id, at: self.clock.now() }
+    }
+}
+
+const SYSTEM_EVENTS: EventBuilder = EventBuilder { clock: SystemClock };
+
+// Shorthands stamped with the system clock
+impl Event {
+    pub fn client_connected(client_id: ClientId) -> Self {
+        SYSTEM_EVENTS.client_connected(client_id)
+    }
+
+    pub fn client_disconnected(client_id: ClientId) -> Self {
+        SYSTEM_EVENTS.client_disconnected(client_id)
+    }
+
+    pub fn pairing_created(pairing_id: PairingId) -> Self {
+        SYSTEM_EVENTS.pairing_created(pairing_id)
+    }
+
+    pub fn session_started(session_id: SessionId) -> Self {
+        SYSTEM_EVENTS.session_started(session_id)
+    }
+
+    pub fn session_ended(session_id: SessionId) -> Self {
+        SYSTEM_EVENTS.session_ended(session_id)
+    }
+}
+
+impl Event {
+    /// Every variant's `type` tag
+    pub const KINDS: [&'static str; 5] = [
+        "ClientConnected",
+        "ClientDisconnected",
+        "PairingCreated",
+        "SessionStarted",
+        "SessionEnded",
+    ];
+
+    /// Variant name, matching the serialized `type` tag
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::ClientConnected { .. } => "ClientConnected",
+            Event::ClientDisconnected { .. } => "ClientDisconnected",
+            Event::PairingCreated { .. } => "PairingCreated",
+            Event::SessionStarted { .. } => "SessionStarted",
+            Event::SessionEnded { .. } => "SessionEnded",
+        }
+    }
+
+    /// When the event happened
+    pub fn at(&self) -> OffsetDateTime {
+        match self {
+            Event::ClientConnected { at, .. }
+            | Event::ClientDisconnected { at, .. }
+            | Event::PairingCreated { at, .. }
+            | Event::SessionStarted { at, .. }
+            | Event::SessionEnded { at, .. } => *at,
+        }
+    }
 }
 
 pub trait Normalize {
     fn normalize(self) -> Vec<Event>;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    const CLIENT: &str = "7f1c2d3e-0000-4000-8000-000000000001";
+    /// `2025-01-02T03:04:05Z` in time's tuple form
+    const AT: &str = "[2025,2,3,4,5,0,0,0,0]";
+
+    fn round_trip<T>(id: T)
+    where
+        T: fmt::Display + FromStr<Err = uuid::Error> + PartialEq + fmt::Debug,
+    {
+        let text = id.to_string();
+        assert_eq!(text, CLIENT);
+        assert_eq!(text.parse::<T>().unwrap(), id);
+        assert!("not-a-uuid".parse::<T>().is_err());
+    }
+
+    #[test]
+    fn test_ids_round_trip_through_strings() {
+        let uuid: Uuid = CLIENT.parse().unwrap();
+        round_trip(UserId::from(uuid));
+        round_trip(ClientId::from(uuid));
+        round_trip(PairingId::from(uuid));
+        round_trip(SessionId::from(uuid));
+    }
+
+    #[test]
+    fn test_builder_stamps_the_clock_time() {
+        let at = datetime!(2025-01-02 03:04:05 UTC);
+        let events = EventBuilder::new(FixedClock(at));
+        let id: Uuid = CLIENT.parse().unwrap();
+        let built = [
+            events.client_connected(ClientId(id)),
+            events.client_disconnected(ClientId(id)),
+            events.pairing_created(PairingId(id)),
+            events.session_started(SessionId(id)),
+            events.session_ended(SessionId(id)),
+        ];
+        assert!(built.iter().all(|event| event.at() == at), "{built:?}");
+        assert!(matches!(built[0], Event::ClientConnected { name: None, .. }));
+    }
+
+    #[test]
+    fn test_shorthands_use_the_system_clock() {
+        let before = OffsetDateTime::now_utc();
+        let event = Event::session_started(SessionId(Uuid::new_v4()));
+        assert!(event.at() >= before && event.at() <= OffsetDateTime::now_utc());
+    }
+
+    #[test]
+    fn test_kinds_match_type_tags() {
+        let id = Uuid::new_v4();
+        let events = [
+            Event::client_connected(ClientId(id)),
+            Event::client_disconnected(ClientId(id)),
+            Event::pairing_created(PairingId(id)),
+            Event::session_started(SessionId(id)),
+            Event::session_ended(SessionId(id)),
+        ];
+        for (event, kind) in events.iter().zip(Event::KINDS) {
+            assert_eq!(event.kind(), kind);
+            assert_eq!(serde_json::to_value(event).unwrap()["type"], kind);
+        }
+    }
+
+    #[test]
+    fn test_v1_payloads_still_deserialize() {
+        let event: Event = serde_json::from_str(&format!(
+            r#"{{"type":"ClientConnected","data":{{"client_id":"{CLIENT}","at":{AT}}}}}"#
+        ))
+        .unwrap();
+        match event {
+            Event::ClientConnected { client_id, name, at } => {
+                assert_eq!(client_id.0.to_string(), CLIENT);
+                assert_eq!(name, None);
+                assert_eq!(at, datetime!(2025-01-02 03:04:05 UTC));
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        let event: Event = serde_json::from_str(&format!(
+            r#"{{"type":"PairingCreated","data":{{"pairing_id":"{CLIENT}","at":{AT}}}}}"#
+        ))
+        .unwrap();
+        assert!(matches!(event, Event::PairingCreated { pin: None, .. }));
+    }
+
+    #[test]
+    fn test_empty_optional_fields_serialize_as_v1() {
+        let event = Event::ClientConnected {
+            client_id: ClientId(CLIENT.parse().unwrap()),
+            name: None,
+            at: datetime!(2025-01-02 03:04:05 UTC),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            format!(r#"{{"type":"ClientConnected","data":{{"client_id":"{CLIENT}","at":{AT}}}}}"#)
+        );
+    }
+
+    #[test]
+    fn test_rich_fields_round_trip() {
+        let event = Event::PairingCreated {
+            pairing_id: PairingId(CLIENT.parse().unwrap()),
+            pin: Some("1234".into()),
+            at: datetime!(2025-01-02 03:04:05 UTC),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["data"]["pin"], "1234");
+        match serde_json::from_value(json).unwrap() {
+            Event::PairingCreated { pin, .. } => assert_eq!(pin.as_deref(), Some("1234")),
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_fields_from_newer_versions_are_ignored() {
+        let event: Event = serde_json::from_str(&format!(
+            r#"{{"type":"ClientConnected","data":{{"client_id":"{CLIENT}","name":"Deck","os":"SteamOS","at":{AT}}}}}"#
+        ))
+        .unwrap();
+        assert!(matches!(event, Event::ClientConnected { name: Some(ref n), .. } if n == "Deck"));
+    }
+}
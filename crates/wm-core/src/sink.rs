@@ -0,0 +1,15 @@
+//! Destinations normalized events are fanned out to
+
+use async_trait::async_trait;
+
+use crate::Event;
+
+/// Somewhere events are delivered: the SSE broadcast, a webhook, a log file.
+/// Each sink is emitted to on its own, so one that fails or hangs costs only its own delivery.
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    /// Short label naming the sink in logs
+    fn name(&self) -> &str;
+
+    async fn emit(&self, event: &Event) -> anyhow::Result<()>;
+}
@@ -0,0 +1,150 @@
+//! Raw event payloads from Wolf's SSE feed and their mapping onto domain events
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{ClientId, Event, Normalize, PairingId, SessionId};
+
+/// One JSON payload from Wolf's event stream.
+/// Only `type` is required; the remaining fields depend on the event type.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WolfRawEvent {
+    #[serde(rename = "type", alias = "event_type")]
+    pub event_type: String,
+    /// When Wolf emitted the event; receive time is used if absent
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub timestamp: Option<OffsetDateTime>,
+    #[serde(flatten)]
+    pub data: Map<String, Value>,
+}
+
+impl WolfRawEvent {
+    fn uuid(&self, field: &str) -> Option<Uuid> {
+        self.data.get(field)?.as_str()?.parse().ok()
+    }
+
+    /// First non-empty string among `fields`
+    fn text(&self, fields: &[&str]) -> Option<String> {
+        fields
+            .iter()
+            .filter_map(|field| self.data.get(*field)?.as_str())
+            .find(|value| !value.is_empty())
+            .map(str::to_owned)
+    }
+}
+
+impl Normalize for WolfRawEvent {
+    fn normalize(self) -> Vec<Event> {
+        let at = self.timestamp.unwrap_or_else(OffsetDateTime::now_utc);
+
+        let event = match self.event_type.as_str() {
+            "client_connected" => self.uuid("client_id").map(|id| Event::ClientConnected {
+                client_id: ClientId(id),
+                name: self.text(&["client_name", "name"]),
+                at,
+            }),
+            "client_disconnected" => self.uuid("client_id").map(|id| Event::ClientDisconnected {
+                client_id: ClientId(id),
+                at,
+            }),
+            "pairing_created" | "pair_request" => self.uuid("pairing_id").map(|id| Event::PairingCreated {
+                pairing_id: PairingId(id),
+                pin: self.text(&["pin"]),
+                at,
+            }),
+            "session_started" | "stream_session_started" => {
+                self.uuid("session_id").map(|id| Event::SessionStarted {
+                    session_id: SessionId(id),
+                    at,
+                })
+            }
+            "session_ended" | "session_stopped" | "stream_session_stopped" => {
+                self.uuid("session_id").map(|id| Event::SessionEnded {
+                    session_id: SessionId(id),
+                    at,
+                })
+            }
+            // Wolf emits plenty we don't model yet
+            _ => None,
+        };
+
+        event.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn normalize(json: &str) -> Vec<Event> {
+        serde_json::from_str::<WolfRawEvent>(json).unwrap().normalize()
+    }
+
+    #[test]
+    fn test_client_connected() {
+        let events = normalize(
+            r#"{"type":"client_connected","client_id":"7f1c2d3e-0000-4000-8000-000000000001","timestamp":"2025-01-02T03:04:05Z","ip":"192.168.1.20"}"#,
+        );
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            Event::ClientConnected { client_id, name, at } => {
+                assert_eq!(client_id.0.to_string(), "7f1c2d3e-0000-4000-8000-000000000001");
+                assert_eq!(*name, None);
+                assert_eq!(*at, datetime!(2025-01-02 03:04:05 UTC));
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_client_name_and_pairing_pin() {
+        let events = normalize(
+            r#"{"type":"client_connected","client_id":"7f1c2d3e-0000-4000-8000-000000000001","client_name":"Steam Deck"}"#,
+        );
+        assert!(matches!(&events[..], [Event::ClientConnected { name: Some(name), .. }] if name == "Steam Deck"));
+
+        let events = normalize(
+            r#"{"type":"pair_request","pairing_id":"7f1c2d3e-0000-4000-8000-000000000002","pin":"4821"}"#,
+        );
+        assert!(matches!(&events[..], [Event::PairingCreated { pin: Some(pin), .. }] if pin == "4821"));
+    }
+
+    #[test]
+    fn test_session_lifecycle() {
+        let id = "7f1c2d3e-0000-4000-8000-0000000000aa";
+        let started = normalize(&format!(
+            r#"{{"type":"session_started","session_id":"{id}","timestamp":"2025-01-02T03:04:05+02:00","app_id":"steam"}}"#
+        ));
+        assert!(matches!(
+            started[..],
+            [Event::SessionStarted { at, .. }] if at == datetime!(2025-01-02 01:04:05 UTC)
+        ));
+
+        let ended = normalize(&format!(
+            r#"{{"event_type":"session_stopped","session_id":"{id}","timestamp":"2025-01-02T04:00:00Z"}}"#
+        ));
+        assert!(matches!(
+            ended[..],
+            [Event::SessionEnded { session_id, .. }] if session_id.0.to_string() == id
+        ));
+    }
+
+    #[test]
+    fn test_missing_timestamp_uses_receive_time() {
+        let before = OffsetDateTime::now_utc();
+        let events = normalize(
+            r#"{"type":"pairing_created","pairing_id":"7f1c2d3e-0000-4000-8000-000000000002"}"#,
+        );
+        assert!(matches!(events[..], [Event::PairingCreated { at, .. }] if at >= before));
+    }
+
+    #[test]
+    fn test_unknown_or_incomplete_events_are_empty() {
+        assert!(normalize(r#"{"type":"pause_stream","session_id":"abc"}"#).is_empty());
+        assert!(normalize(r#"{"type":"client_connected"}"#).is_empty());
+        assert!(normalize(r#"{"type":"client_connected","client_id":"not-a-uuid"}"#).is_empty());
+    }
+}
@@ -0,0 +1,2 @@
+-- Record which build started on each boot
+ALTER TABLE app_boot ADD COLUMN version TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,2 @@
+-- When a session ended; NULL while it is still running
+ALTER TABLE sessions ADD COLUMN ended_at TEXT;
@@ -1,25 +1,509 @@
-use anyhow::Result;
-use sqlx::{SqlitePool, sqlite::SqliteConnectOptions};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::{Sqlite, SqlitePool, Transaction, migrate::Migrator, sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous}};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::str::FromStr;
+use std::time::Duration;
+use time::{format_description::FormatItem, macros::format_description, OffsetDateTime, UtcOffset};
+use wm_core::{Event, SessionId, UserId};
+
+/// Fixed-width UTC timestamps so `at` sorts correctly as TEXT
+const TIMESTAMP_FORMAT: &[FormatItem<'static>] =
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:6]Z");
+
+fn format_timestamp(at: OffsetDateTime) -> Result<String> {
+    Ok(at.to_offset(UtcOffset::UTC).format(TIMESTAMP_FORMAT)?)
+}
+
+/// What SQLite's `CURRENT_TIMESTAMP` column defaults produce
+const SQLITE_TIMESTAMP_FORMAT: &[FormatItem<'static>] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
+
+fn parse_timestamp(s: &str) -> Result<OffsetDateTime> {
+    let at = time::PrimitiveDateTime::parse(s, TIMESTAMP_FORMAT)
+        .or_else(|_| time::PrimitiveDateTime::parse(s, SQLITE_TIMESTAMP_FORMAT))
+        .with_context(|| format!("invalid stored timestamp: {}", s))?;
+    Ok(at.assume_utc())
+}
+
+/// What a `with_transaction` closure returns: `Box::pin(async move { ... })`
+pub type TxFuture<'c, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'c>>;
+
+/// Run `f` inside a transaction: commit if it returns `Ok`, roll back if it returns `Err`.
+/// A failing rollback is logged; the closure's error is what's returned.
+pub async fn with_transaction<T, F>(pool: &SqlitePool, f: F) -> Result<T>
+where
+    F: for<'c> FnOnce(&'c mut Transaction<'static, Sqlite>) -> TxFuture<'c, T>,
+{
+    let mut tx = pool.begin().await?;
+    match f(&mut tx).await {
+        Ok(value) => {
+            tx.commit().await?;
+            Ok(value)
+        }
+        Err(e) => {
+            if let Err(rollback) = tx.rollback().await {
+                tracing::warn!("Transaction rollback failed: {}", rollback);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// A domain event as read back from the `events` table
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct StoredEvent {
+    pub id: i64,
+    pub kind: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
+    pub event: Event,
+}
+
+/// Size, wait and locking settings for the connection pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSettings {
+    pub max_connections: u32,
+    /// How long a caller (or the initial connect) waits for a connection before failing
+    pub acquire_timeout: Duration,
+    /// How long SQLite retries a locked database before returning `SQLITE_BUSY`
+    pub busy_timeout: Duration,
+    /// WAL journal with `synchronous=NORMAL`, so readers don't block the writer.
+    /// Turn off for databases that can't use WAL (e.g. on network filesystems).
+    pub wal: bool,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            acquire_timeout: Duration::from_secs(30),
+            busy_timeout: Duration::from_secs(5),
+            wal: true,
+        }
+    }
+}
+
+/// In-memory databases have no journal file, so WAL doesn't apply to them
+fn is_in_memory(database_url: &str) -> bool {
+    database_url.contains(":memory:") || database_url.contains("mode=memory")
+}
 
 pub async fn new_pool(database_url: &str) -> Result<SqlitePool> {
+    new_pool_with(database_url, PoolSettings::default()).await
+}
+
+/// `new_pool` with explicit pool limits
+pub async fn new_pool_with(database_url: &str, settings: PoolSettings) -> Result<SqlitePool> {
     // Use SQLite directly (simpler and primary database per constraints)
-    let opts = SqliteConnectOptions::from_str(database_url)?
-        .create_if_missing(true);
-    let pool = SqlitePool::connect_with(opts).await?;
-    Ok(pool)
+    let mut opts = SqliteConnectOptions::from_str(database_url)?
+        .create_if_missing(true)
+        .busy_timeout(settings.busy_timeout);
+    if settings.wal && !is_in_memory(database_url) {
+        opts = opts
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal);
+    }
+    let mut pool = SqlitePoolOptions::new()
+        .max_connections(settings.max_connections)
+        .acquire_timeout(settings.acquire_timeout);
+    if is_in_memory(database_url) {
+        // Pooled connections share one in-memory database (sqlx opens `:memory:` with a shared
+        // cache), which is dropped once its last connection closes; keep one open for good.
+        pool = pool.min_connections(1).idle_timeout(None).max_lifetime(None);
+    }
+    pool.connect_with(opts)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::PoolTimedOut => anyhow::anyhow!(
+                "timed out after {}ms connecting to database {}",
+                settings.acquire_timeout.as_millis(),
+                database_url
+            ),
+            e => anyhow::Error::from(e).context(format!("failed to connect to database {}", database_url)),
+        })
 }
 
+/// Migrations compiled into this binary
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// The only database backend so far
+pub const BACKEND: &str = "sqlite";
+
 pub async fn migrate(pool: &SqlitePool) -> Result<()> {
-    // Run migrations using the macro
-    sqlx::migrate!("./migrations").run(pool).await?;
+    MIGRATOR.run(pool).await?;
     Ok(())
 }
 
+/// Newest migration embedded in this binary
+pub fn latest_migration_version() -> Option<i64> {
+    MIGRATOR.iter().map(|m| m.version).max()
+}
+
+/// Newest migration successfully applied to the database, read from sqlx's
+/// `_sqlx_migrations` table; `None` before anything has been migrated
+pub async fn current_migration_version(pool: &SqlitePool) -> Result<Option<i64>> {
+    let tracked: Option<(String,)> =
+        sqlx::query_as("SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'")
+            .fetch_optional(pool)
+            .await?;
+    if tracked.is_none() {
+        return Ok(None);
+    }
+    let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+        .fetch_one(pool)
+        .await?;
+    Ok(version)
+}
+
+/// How the database schema compares with the migrations this binary carries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct MigrationStatus {
+    pub backend: &'static str,
+    /// Newest migration applied to the database, null if none
+    pub applied_version: Option<i64>,
+    /// Newest migration this binary knows
+    pub latest_version: Option<i64>,
+    /// The database is at exactly `latest_version`
+    pub up_to_date: bool,
+}
+
+impl MigrationStatus {
+    /// The database has migrations this binary doesn't, most likely after a downgrade
+    pub fn is_ahead(&self) -> bool {
+        match (self.applied_version, self.latest_version) {
+            (Some(applied), Some(latest)) => applied > latest,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+pub async fn migration_status(pool: &SqlitePool) -> Result<MigrationStatus> {
+    let applied_version = current_migration_version(pool).await?;
+    let latest_version = latest_migration_version();
+    Ok(MigrationStatus {
+        backend: BACKEND,
+        applied_version,
+        latest_version,
+        up_to_date: applied_version == latest_version,
+    })
+}
+
+/// A row from `app_boot`
+#[derive(Debug, Clone, Serialize)]
+pub struct BootRecord {
+    pub id: i64,
+    pub version: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
+}
+
+/// Note a process start; returns the new row id
+pub async fn record_boot(pool: &SqlitePool, version: &str) -> Result<i64> {
+    let result = sqlx::query("INSERT INTO app_boot (at, version) VALUES (?, ?)")
+        .bind(format_timestamp(OffsetDateTime::now_utc())?)
+        .bind(version)
+        .execute(pool)
+        .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Most recent boot, if any
+pub async fn latest_boot(pool: &SqlitePool) -> Result<Option<BootRecord>> {
+    let row: Option<(i64, String, String)> =
+        sqlx::query_as("SELECT id, version, at FROM app_boot ORDER BY id DESC LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    row.map(|(id, version, at)| {
+        Ok(BootRecord {
+            id,
+            version,
+            at: parse_timestamp(&at)?,
+        })
+    })
+    .transpose()
+}
+
+/// Append a domain event, stored as its tagged JSON form
+pub async fn insert_event(pool: &SqlitePool, event: &Event) -> Result<StoredEvent> {
+    insert_event_with(pool, event).await
+}
+
+/// `insert_event` on any executor, so it can join a transaction
+async fn insert_event_with<'e, E>(executor: E, event: &Event) -> Result<StoredEvent>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let payload = serde_json::to_string(event)?;
+    let result = sqlx::query("INSERT INTO events (kind, payload, at) VALUES (?, ?, ?)")
+        .bind(event.kind())
+        .bind(payload)
+        .bind(format_timestamp(event.at())?)
+        .execute(executor)
+        .await?;
+    Ok(StoredEvent {
+        id: result.last_insert_rowid(),
+        kind: event.kind().to_string(),
+        at: event.at(),
+        event: event.clone(),
+    })
+}
+
+fn stored_event((id, kind, payload, at): (i64, String, String, String)) -> Result<StoredEvent> {
+    let event = serde_json::from_str(&payload)
+        .with_context(|| format!("invalid payload for event {}", id))?;
+    Ok(StoredEvent {
+        id,
+        kind,
+        at: parse_timestamp(&at)?,
+        event,
+    })
+}
+
+/// Newest-first page of events that sort after the `(at, id)` cursor `before`, i.e. older
+/// than `at`, or at the same instant with a smaller id
+pub async fn list_events(
+    pool: &SqlitePool,
+    limit: u32,
+    before: Option<(OffsetDateTime, i64)>,
+) -> Result<Vec<StoredEvent>> {
+    let rows: Vec<(i64, String, String, String)> = match before {
+        Some((at, id)) => {
+            let at = format_timestamp(at)?;
+            sqlx::query_as(
+                "SELECT id, kind, payload, at FROM events WHERE at < ? OR (at = ? AND id < ?) ORDER BY at DESC, id DESC LIMIT ?",
+            )
+            .bind(&at)
+            .bind(&at)
+            .bind(id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?
+        }
+        None => {
+            sqlx::query_as("SELECT id, kind, payload, at FROM events ORDER BY at DESC, id DESC LIMIT ?")
+                .bind(limit)
+                .fetch_all(pool)
+                .await?
+        }
+    };
+
+    rows.into_iter().map(stored_event).collect()
+}
+
+/// Events with an id above `after`, oldest first, capped to the newest `limit` of them
+pub async fn list_events_after(pool: &SqlitePool, after: i64, limit: u32) -> Result<Vec<StoredEvent>> {
+    let rows: Vec<(i64, String, String, String)> = sqlx::query_as(
+        "SELECT id, kind, payload, at FROM (SELECT id, kind, payload, at FROM events WHERE id > ? ORDER BY id DESC LIMIT ?) ORDER BY id",
+    )
+    .bind(after)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(stored_event).collect()
+}
+
+/// Number of stored events per `kind`, counting only those at or after `since` when given.
+/// Kinds with no events are absent.
+pub async fn event_counts(pool: &SqlitePool, since: Option<OffsetDateTime>) -> Result<BTreeMap<String, u64>> {
+    let rows: Vec<(String, i64)> = match since {
+        Some(since) => {
+            sqlx::query_as("SELECT kind, COUNT(*) FROM events WHERE at >= ? GROUP BY kind")
+                .bind(format_timestamp(since)?)
+                .fetch_all(pool)
+                .await?
+        }
+        None => {
+            sqlx::query_as("SELECT kind, COUNT(*) FROM events GROUP BY kind")
+                .fetch_all(pool)
+                .await?
+        }
+    };
+    Ok(rows.into_iter().map(|(kind, count)| (kind, count as u64)).collect())
+}
+
+/// Delete events recorded strictly before `older_than`; returns how many were removed
+pub async fn prune_events(pool: &SqlitePool, older_than: OffsetDateTime) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM events WHERE at < ?")
+        .bind(format_timestamp(older_than)?)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// A row from `users`
+#[derive(Debug, Clone, Serialize)]
+pub struct User {
+    pub id: UserId,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// One page of users; pass `next_cursor` as `after` to fetch the next one
+#[derive(Debug, Clone, Serialize)]
+pub struct UserPage {
+    pub users: Vec<User>,
+    /// `None` on the last page
+    pub next_cursor: Option<UserId>,
+}
+
+pub async fn insert_user(pool: &SqlitePool, id: UserId) -> Result<()> {
+    sqlx::query("INSERT INTO users (id, created_at) VALUES (?, ?)")
+        .bind(id.to_string())
+        .bind(format_timestamp(OffsetDateTime::now_utc())?)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Page of users ordered by id, starting strictly after the `after` cursor
+pub async fn list_users(pool: &SqlitePool, limit: u32, after: Option<UserId>) -> Result<UserPage> {
+    // One extra row tells us whether another page follows
+    let fetch = i64::from(limit) + 1;
+    let rows: Vec<(String, String)> = match after {
+        Some(after) => {
+            sqlx::query_as("SELECT id, created_at FROM users WHERE id > ? ORDER BY id LIMIT ?")
+                .bind(after.to_string())
+                .bind(fetch)
+                .fetch_all(pool)
+                .await?
+        }
+        None => {
+            sqlx::query_as("SELECT id, created_at FROM users ORDER BY id LIMIT ?")
+                .bind(fetch)
+                .fetch_all(pool)
+                .await?
+        }
+    };
+
+    let mut users = rows
+        .into_iter()
+        .map(|(id, created_at)| {
+            let id = id
+                .parse()
+                .with_context(|| format!("invalid stored user id: {}", id))?;
+            Ok(User {
+                id: UserId(id),
+                created_at: parse_timestamp(&created_at)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let next_cursor = if users.len() > limit as usize {
+        users.truncate(limit as usize);
+        users.last().map(|u| u.id)
+    } else {
+        None
+    };
+    Ok(UserPage { users, next_cursor })
+}
+
+/// A row from `sessions`
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pub id: SessionId,
+    pub user_id: UserId,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// `None` while the session is running
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub ended_at: Option<OffsetDateTime>,
+}
+
+/// What `end_session` did
+#[derive(Debug, Clone)]
+pub enum SessionEnd {
+    /// The session was running and is now ended; carries the logged `SessionEnded`
+    Ended(Session, StoredEvent),
+    /// The session had already ended; nothing changed
+    AlreadyEnded(Session),
+    NotFound,
+}
+
+/// Start a session for `user_id`, logging `SessionStarted` in the same transaction.
+/// Returns `None` if the user doesn't exist.
+pub async fn create_session(
+    pool: &SqlitePool,
+    id: SessionId,
+    user_id: UserId,
+    at: OffsetDateTime,
+) -> Result<Option<(Session, StoredEvent)>> {
+    let event = with_transaction(pool, |tx| Box::pin(async move {
+        let inserted = sqlx::query(
+            "INSERT INTO sessions (id, user_id, created_at) SELECT ?, ?, ? WHERE EXISTS (SELECT 1 FROM users WHERE id = ?)",
+        )
+        .bind(id.to_string())
+        .bind(user_id.to_string())
+        .bind(format_timestamp(at)?)
+        .bind(user_id.to_string())
+        .execute(&mut **tx)
+        .await?;
+        if inserted.rows_affected() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(insert_event_with(&mut **tx, &Event::SessionStarted { session_id: id, at }).await?))
+    }))
+    .await?;
+    let Some(event) = event else {
+        return Ok(None);
+    };
+
+    Ok(get_session(pool, id).await?.map(|session| (session, event)))
+}
+
+/// End a running session, logging `SessionEnded` in the same transaction.
+/// Ending an ended session leaves it (and its `ended_at`) untouched.
+pub async fn end_session(pool: &SqlitePool, id: SessionId, at: OffsetDateTime) -> Result<SessionEnd> {
+    let event = with_transaction(pool, |tx| Box::pin(async move {
+        let updated = sqlx::query("UPDATE sessions SET ended_at = ? WHERE id = ? AND ended_at IS NULL")
+            .bind(format_timestamp(at)?)
+            .bind(id.to_string())
+            .execute(&mut **tx)
+            .await?;
+        if updated.rows_affected() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(insert_event_with(&mut **tx, &Event::SessionEnded { session_id: id, at }).await?))
+    }))
+    .await?;
+
+    Ok(match (get_session(pool, id).await?, event) {
+        (Some(session), Some(event)) => SessionEnd::Ended(session, event),
+        (Some(session), None) => SessionEnd::AlreadyEnded(session),
+        (None, _) => SessionEnd::NotFound,
+    })
+}
+
+pub async fn get_session(pool: &SqlitePool, id: SessionId) -> Result<Option<Session>> {
+    let row: Option<(String, String, Option<String>)> =
+        sqlx::query_as("SELECT user_id, created_at, ended_at FROM sessions WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(pool)
+            .await?;
+
+    row.map(|(user_id, created_at, ended_at)| {
+        Ok(Session {
+            id,
+            user_id: UserId(
+                user_id
+                    .parse()
+                    .with_context(|| format!("invalid stored user id: {}", user_id))?,
+            ),
+            created_at: parse_timestamp(&created_at)?,
+            ended_at: ended_at.as_deref().map(parse_timestamp).transpose()?,
+        })
+    })
+    .transpose()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sqlx::sqlite::SqlitePoolOptions;
 
     #[tokio::test]
     async fn test_migrate_sqlite() -> Result<()> {
@@ -39,4 +523,438 @@ mod tests {
 
         Ok(())
     }
+
+    async fn memory_pool() -> Result<SqlitePool> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await?;
+        migrate(&pool).await?;
+        Ok(pool)
+    }
+
+    fn session_started(at: OffsetDateTime) -> Event {
+        Event::SessionStarted {
+            session_id: wm_core::SessionId(uuid_like(at)),
+            at,
+        }
+    }
+
+    fn uuid_like(at: OffsetDateTime) -> uuid::Uuid {
+        uuid::Uuid::from_u128(at.unix_timestamp_nanos() as u128)
+    }
+
+    #[tokio::test]
+    async fn test_migration_status_up_to_date() -> Result<()> {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await?;
+        assert_eq!(current_migration_version(&pool).await?, None);
+        assert!(!migration_status(&pool).await?.up_to_date);
+
+        migrate(&pool).await?;
+        let status = migration_status(&pool).await?;
+        assert!(status.latest_version.is_some());
+        assert_eq!(status.applied_version, status.latest_version);
+        assert!(status.up_to_date);
+        assert!(!status.is_ahead());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_migration_status_ahead_of_binary() -> Result<()> {
+        let pool = memory_pool().await?;
+        // Left by a newer build that has since been rolled back
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (?, 'newer', 1, x'00', 0)",
+        )
+        .bind(20990101_i64)
+        .execute(&pool)
+        .await?;
+        // A failed attempt doesn't count as applied
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (?, 'failed', 0, x'00', 0)",
+        )
+        .bind(20991231_i64)
+        .execute(&pool)
+        .await?;
+
+        let status = migration_status(&pool).await?;
+        assert_eq!(status.applied_version, Some(20990101));
+        assert!(!status.up_to_date);
+        assert!(status.is_ahead());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_event_round_trip() -> Result<()> {
+        let pool = memory_pool().await?;
+        let at = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        let event = session_started(at);
+
+        let inserted = insert_event(&pool, &event).await?;
+        let stored = list_events(&pool, 10, None).await?;
+
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id, inserted.id);
+        assert_eq!(stored[0].kind, "SessionStarted");
+        assert_eq!(stored[0].at, at);
+        assert!(matches!(
+            stored[0].event,
+            Event::SessionStarted { session_id, .. } if session_id.0 == uuid_like(at)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_events_paginates_before_cursor() -> Result<()> {
+        let pool = memory_pool().await?;
+        let base = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        for secs in 0..5 {
+            insert_event(&pool, &session_started(base + time::Duration::seconds(secs))).await?;
+        }
+
+        let first = list_events(&pool, 2, None).await?;
+        let times: Vec<_> = first.iter().map(|e| e.at).collect();
+        assert_eq!(times, [base + time::Duration::seconds(4), base + time::Duration::seconds(3)]);
+
+        let second = list_events(&pool, 2, Some((first[1].at, first[1].id))).await?;
+        let times: Vec<_> = second.iter().map(|e| e.at).collect();
+        assert_eq!(times, [base + time::Duration::seconds(2), base + time::Duration::seconds(1)]);
+
+        let last = list_events(&pool, 2, Some((second[1].at, second[1].id))).await?;
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].at, base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_events_pages_through_equal_timestamps() -> Result<()> {
+        let pool = memory_pool().await?;
+        let base = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        insert_event(&pool, &session_started(base)).await?;
+        let mut expected = Vec::new();
+        for _ in 0..4 {
+            expected.push(insert_event(&pool, &session_started(base + time::Duration::seconds(1))).await?.id);
+        }
+        expected.reverse();
+
+        // Pages of 3 split the run of four events sharing a timestamp
+        let first = list_events(&pool, 3, None).await?;
+        let cursor = first.last().map(|e| (e.at, e.id));
+        let second = list_events(&pool, 3, cursor).await?;
+
+        let seen: Vec<_> = first.iter().chain(&second).map(|e| e.id).collect();
+        assert_eq!(seen[..4], expected[..]);
+        assert_eq!(second.len(), 2);
+        assert_eq!(second[1].at, base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_events_after_is_bounded_and_ascending() -> Result<()> {
+        let pool = memory_pool().await?;
+        let base = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        let mut ids = Vec::new();
+        for secs in 0..5 {
+            ids.push(insert_event(&pool, &session_started(base + time::Duration::seconds(secs))).await?.id);
+        }
+
+        let after: Vec<_> = list_events_after(&pool, ids[1], 10).await?.iter().map(|e| e.id).collect();
+        assert_eq!(after, ids[2..]);
+
+        // Only the newest `limit` are returned, still oldest first
+        let capped: Vec<_> = list_events_after(&pool, 0, 2).await?.iter().map(|e| e.id).collect();
+        assert_eq!(capped, ids[3..]);
+
+        assert!(list_events_after(&pool, ids[4], 10).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_event_counts_groups_by_kind() -> Result<()> {
+        let pool = memory_pool().await?;
+        let base = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        let hour = time::Duration::hours(1);
+        let client = wm_core::ClientId(uuid::Uuid::from_u128(1));
+        let events = [
+            Event::ClientConnected { client_id: client, name: None, at: base - hour * 2 },
+            session_started(base - hour * 2),
+            session_started(base),
+            session_started(base + time::Duration::seconds(1)),
+            Event::SessionEnded { session_id: wm_core::SessionId(uuid_like(base)), at: base + hour },
+        ];
+        for event in &events {
+            insert_event(&pool, event).await?;
+        }
+
+        let all = event_counts(&pool, None).await?;
+        let expected = [("ClientConnected", 1), ("SessionEnded", 1), ("SessionStarted", 3)];
+        assert_eq!(all, expected.map(|(k, n)| (k.to_owned(), n)).into());
+
+        // `since` is inclusive and filters before grouping
+        let recent = event_counts(&pool, Some(base)).await?;
+        assert_eq!(recent, [("SessionEnded", 1), ("SessionStarted", 2)].map(|(k, n)| (k.to_owned(), n)).into());
+
+        assert!(event_counts(&pool, Some(base + hour * 2)).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_prune_events_removes_only_older_rows() -> Result<()> {
+        let pool = memory_pool().await?;
+        let cutoff = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        for at in [cutoff - time::Duration::days(40), cutoff - time::Duration::seconds(1)] {
+            insert_event(&pool, &session_started(at)).await?;
+        }
+        let kept = [
+            insert_event(&pool, &session_started(cutoff)).await?.id,
+            insert_event(&pool, &session_started(cutoff + time::Duration::days(1))).await?.id,
+        ];
+
+        assert_eq!(prune_events(&pool, cutoff).await?, 2);
+        let remaining: Vec<_> = list_events_after(&pool, 0, 10).await?.iter().map(|e| e.id).collect();
+        assert_eq!(remaining, kept);
+
+        assert_eq!(prune_events(&pool, cutoff).await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pool_settings_are_honored() -> Result<()> {
+        let settings = PoolSettings {
+            max_connections: 2,
+            acquire_timeout: Duration::from_millis(50),
+            ..PoolSettings::default()
+        };
+        let pool = new_pool_with("sqlite::memory:", settings).await?;
+        assert_eq!(pool.options().get_max_connections(), 2);
+        assert_eq!(pool.options().get_acquire_timeout(), Duration::from_millis(50));
+
+        let _held = (pool.acquire().await?, pool.acquire().await?);
+        let err = pool.acquire().await.unwrap_err();
+        assert!(matches!(err, sqlx::Error::PoolTimedOut), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writers_wait_instead_of_failing_busy() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let url = format!("sqlite://{}", dir.path().join("wm.db").display());
+        let pool = new_pool_with(&url, PoolSettings { max_connections: 8, ..PoolSettings::default() }).await?;
+        migrate(&pool).await?;
+
+        let mode: (String,) = sqlx::query_as("PRAGMA journal_mode").fetch_one(&pool).await?;
+        assert_eq!(mode.0, "wal");
+
+        let at = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        let writers: Vec<_> = (0..16)
+            .map(|_| {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    for _ in 0..10 {
+                        insert_event(&pool, &session_started(at)).await?;
+                    }
+                    Ok::<_, anyhow::Error>(())
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.await??;
+        }
+
+        assert_eq!(list_events_after(&pool, 0, 1000).await?.len(), 160);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wal_can_be_turned_off() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let url = format!("sqlite://{}", dir.path().join("wm.db").display());
+        let pool = new_pool_with(&url, PoolSettings { wal: false, ..PoolSettings::default() }).await?;
+        let mode: (String,) = sqlx::query_as("PRAGMA journal_mode").fetch_one(&pool).await?;
+        assert_eq!(mode.0, "delete");
+
+        // In-memory databases never switch, whatever the setting
+        let memory = new_pool("sqlite::memory:").await?;
+        let mode: (String,) = sqlx::query_as("PRAGMA journal_mode").fetch_one(&memory).await?;
+        assert_eq!(mode.0, "memory");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_schema_is_shared_across_connections() -> Result<()> {
+        let pool = new_pool("sqlite::memory:").await?;
+        assert_eq!(pool.options().get_idle_timeout(), None);
+        assert_eq!(pool.options().get_max_lifetime(), None);
+        assert_eq!(pool.options().get_min_connections(), 1);
+
+        let mut first = pool.acquire().await?;
+        sqlx::migrate!("./migrations").run(&mut *first).await?;
+
+        // Held `first` forces the pool to hand out a different connection
+        let mut second = pool.acquire().await?;
+        let tables: Vec<(String,)> =
+            sqlx::query_as("SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'events'")
+                .fetch_all(&mut *second)
+                .await?;
+        assert_eq!(tables.len(), 1);
+        assert_eq!(pool.size(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_new_pool_reports_unreachable_database() {
+        let err = new_pool("sqlite:///nonexistent/dir/wm.db").await.unwrap_err();
+        assert!(err.to_string().contains("/nonexistent/dir/wm.db"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn test_with_transaction_commits_on_ok() -> Result<()> {
+        let pool = memory_pool().await?;
+        let at = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        let id = with_transaction(&pool, |tx| Box::pin(async move {
+            Ok(insert_event_with(&mut **tx, &session_started(at)).await?.id)
+        }))
+        .await?;
+
+        let stored: Vec<_> = list_events_after(&pool, 0, 10).await?.iter().map(|e| e.id).collect();
+        assert_eq!(stored, [id]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_with_transaction_rolls_back_on_err() -> Result<()> {
+        let pool = memory_pool().await?;
+        let at = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        let err = with_transaction::<(), _>(&pool, |tx| Box::pin(async move {
+            insert_event_with(&mut **tx, &session_started(at)).await?;
+            sqlx::query("INSERT INTO users (id, created_at) VALUES (?, ?)")
+                .bind(user_id(1).0.to_string())
+                .bind(format_timestamp(at)?)
+                .execute(&mut **tx)
+                .await?;
+            anyhow::bail!("second step failed")
+        }))
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "second step failed");
+        assert!(list_events_after(&pool, 0, 10).await?.is_empty());
+        assert!(list_users(&pool, 10, None).await?.users.is_empty());
+        Ok(())
+    }
+
+    fn user_id(n: u128) -> UserId {
+        UserId(uuid::Uuid::from_u128(n))
+    }
+
+    #[tokio::test]
+    async fn test_list_users_empty() -> Result<()> {
+        let pool = memory_pool().await?;
+        let page = list_users(&pool, 10, None).await?;
+        assert!(page.users.is_empty());
+        assert_eq!(page.next_cursor, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_users_single_page() -> Result<()> {
+        let pool = memory_pool().await?;
+        for n in [3, 1, 2] {
+            insert_user(&pool, user_id(n)).await?;
+        }
+
+        let page = list_users(&pool, 3, None).await?;
+        let ids: Vec<_> = page.users.iter().map(|u| u.id).collect();
+        assert_eq!(ids, [user_id(1), user_id(2), user_id(3)]);
+        assert_eq!(page.next_cursor, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_users_follows_cursor() -> Result<()> {
+        let pool = memory_pool().await?;
+        for n in 1..=5 {
+            insert_user(&pool, user_id(n)).await?;
+        }
+        // Rows created by the column default use SQLite's timestamp format
+        sqlx::query("INSERT INTO users (id) VALUES (?)")
+            .bind(user_id(6).0.to_string())
+            .execute(&pool)
+            .await?;
+
+        let mut seen = Vec::new();
+        let mut after = None;
+        loop {
+            let page = list_users(&pool, 2, after).await?;
+            assert!(page.users.len() <= 2);
+            seen.extend(page.users.iter().map(|u| u.id));
+            match page.next_cursor {
+                Some(cursor) => after = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(seen, (1..=6).map(user_id).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle() -> Result<()> {
+        let pool = memory_pool().await?;
+        let user = user_id(1);
+        let id = SessionId(uuid::Uuid::from_u128(10));
+        let started = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        let ended = started + time::Duration::minutes(30);
+
+        // Unknown users can't start sessions
+        assert!(create_session(&pool, id, user, started).await?.is_none());
+        assert!(get_session(&pool, id).await?.is_none());
+
+        insert_user(&pool, user).await?;
+        let (session, started_event) = create_session(&pool, id, user, started).await?.unwrap();
+        assert_eq!((session.user_id, session.created_at, session.ended_at), (user, started, None));
+        assert_eq!(started_event.kind, "SessionStarted");
+
+        let SessionEnd::Ended(session, ended_event) = end_session(&pool, id, ended).await? else {
+            panic!("expected the session to end");
+        };
+        assert_eq!(session.ended_at, Some(ended));
+        assert!(ended_event.id > started_event.id);
+
+        // A second end is a no-op that keeps the original end time
+        let later = ended + time::Duration::minutes(5);
+        let SessionEnd::AlreadyEnded(session) = end_session(&pool, id, later).await? else {
+            panic!("expected the session to be already ended");
+        };
+        assert_eq!(session.ended_at, Some(ended));
+        assert!(matches!(
+            end_session(&pool, SessionId(uuid::Uuid::from_u128(11)), later).await?,
+            SessionEnd::NotFound
+        ));
+
+        // Exactly one start and one end were logged
+        let kinds: Vec<_> = list_events(&pool, 10, None).await?.into_iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, ["SessionEnded", "SessionStarted"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_record_boot() -> Result<()> {
+        let pool = memory_pool().await?;
+        assert!(latest_boot(&pool).await?.is_none());
+
+        let first = record_boot(&pool, "0.1.0").await?;
+        assert!(first > 0);
+        let second = record_boot(&pool, "0.2.0").await?;
+        assert!(second > first);
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM app_boot")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(count, 2);
+
+        let latest = latest_boot(&pool).await?.unwrap();
+        assert_eq!(latest.id, second);
+        assert_eq!(latest.version, "0.2.0");
+        Ok(())
+    }
 }
\ No newline at end of file